@@ -1,6 +1,7 @@
 use std::io;
 use std::result;
 use std::string;
+use std::time::Duration;
 use thiserror::Error;
 
 pub type Result<T> = result::Result<T, Error>;
@@ -22,6 +23,12 @@ pub enum Error {
     #[error("http error: {0}")]
     Http(#[from] attohttpc::Error),
 
+    #[error("transport error: {0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("invalid url: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+
     #[error("failed to parse episode: {filename}")]
     EpisodeParseFailed { filename: String },
 
@@ -34,9 +41,31 @@ pub enum Error {
     #[error("bad AniList response ({code}): {message}")]
     BadAniListResponse { code: u16, message: String },
 
+    #[error("bad Kitsu response ({code}): {message}")]
+    BadKitsuResponse { code: u16, message: String },
+
+    #[error("bad MyAnimeList response ({code}): {message}")]
+    BadMyAnimeListResponse { code: u16, message: String },
+
+    #[cfg(feature = "async-support")]
+    #[error("async task failed: {0}")]
+    AsyncTask(#[from] tokio::task::JoinError),
+
+    #[error("rate limited by remote service, retry after {} seconds", .retry_after.as_secs())]
+    RateLimited { retry_after: Duration },
+
+    #[error("no recorded response found for request: {0}")]
+    NoRecordedResponse(String),
+
+    #[error("request is not supported by the remote service")]
+    Unsupported,
+
     #[error("must be authorized to make this request")]
     NeedAuthentication,
 
+    #[error("access token is invalid or has expired")]
+    InvalidToken,
+
     #[error("requested series is not an anime")]
     NotAnAnime,
 }
@@ -47,7 +76,15 @@ impl Error {
         use attohttpc::ErrorKind;
 
         match self {
-            Error::BadAniListResponse { code, .. } if http_code == *code => true,
+            Error::BadAniListResponse { code, .. }
+            | Error::BadKitsuResponse { code, .. }
+            | Error::BadMyAnimeListResponse { code, .. }
+                if http_code == *code =>
+            {
+                true
+            }
+            Error::RateLimited { .. } => http_code == 429,
+            Error::InvalidToken => http_code == 401,
             Error::Http(source) => match source.kind() {
                 ErrorKind::StatusCode(status) => status.as_u16() == http_code,
                 _ => false,
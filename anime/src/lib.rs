@@ -17,9 +17,10 @@ pub mod local;
 pub mod remote;
 
 pub use err::{Error, Result};
+use serde_derive::{Deserialize, Serialize};
 
 /// Represents the type of a series.
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 pub enum SeriesKind {
     /// TV episodes.
     Season,
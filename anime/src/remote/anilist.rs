@@ -1,21 +1,28 @@
 #![allow(clippy::doc_markdown)]
 
+use super::rate_limit::RateLimiter;
+use super::transport::{Client, Method};
 use super::{
-    AccessToken, RemoteService, ScoreParser, Sequel, SeriesDate, SeriesEntry, SeriesID, SeriesInfo,
-    SeriesKind, SeriesTitle, Status,
+    AccessToken, AiringEpisode, AiringStatus, NextEpisode, Recommendation, Relation, RelationKind,
+    RemoteService, ScoreParser, SearchOptions, SearchPage, Season, Sequel, SeriesDate, SeriesEntry,
+    SeriesID, SeriesInfo, SeriesKind, SeriesTitle, Status, UserListEntry,
 };
 use crate::err::{Error, Result};
 use serde_derive::{Deserialize, Serialize};
 use serde_json as json;
 use serde_json::json;
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::result;
-use std::time::Duration;
+use std::sync::Arc;
 
 /// The URL to the API endpoint.
 pub const API_URL: &str = "https://graphql.anilist.co";
 
+/// The maximum number of items AniList will return in a single page.
+const MAX_PAGE_SIZE: usize = 50;
+
 /// Returns the URL that the user needs to go to in order to authenticate their account
 /// so the API can make changes to it.
 ///
@@ -78,19 +85,19 @@ macro_rules! minimize_query {
 
 /// Send an API query to AniList, without attemping to parse a response.
 macro_rules! send {
-    ($token:expr, $file:expr, {$($vars:tt)*}, $($resp_root:expr)=>*) => {{
-        if cfg!(debug_assertions) && cfg!(feature = "print-requests-debug") {
-            println!("DEBUG: AniList request: {}", $file);
-        }
-
+    ($client:expr, $limiter:expr, $token:expr, $file:expr, {$($vars:tt)*}, $($resp_root:expr)=>*) => {{
         let vars = json!({
             $($vars)*
         });
 
+        if cfg!(debug_assertions) && cfg!(feature = "print-requests-debug") {
+            println!("DEBUG: AniList request: {} {}", $file, vars);
+        }
+
         let query = minimize_query!(include_str!(concat!("../../graphql/anilist/", $file, ".gql")));
 
         #[allow(unused_mut)]
-        match send_gql_request(query, &vars, $token) {
+        match send_gql_request($client, $limiter, query, &vars, $token) {
             Ok(mut json) => {
                 $(json = json[$resp_root].take();)*
                 Ok(json)
@@ -102,8 +109,8 @@ macro_rules! send {
 
 /// Send an API query to AniList, and attempt to parse the response into a specified type.
 macro_rules! query {
-    ($token:expr, $file:expr, {$($vars:tt)*}, $($resp_root:expr)=>*) => {
-        send!($token, $file, {$($vars)*}, $($resp_root)=>*).and_then(|json| {
+    ($client:expr, $limiter:expr, $token:expr, $file:expr, {$($vars:tt)*}, $($resp_root:expr)=>*) => {
+        send!($client, $limiter, $token, $file, {$($vars)*}, $($resp_root)=>*).and_then(|json| {
             json::from_value(json).map_err(Into::into)
         })
     };
@@ -111,44 +118,85 @@ macro_rules! query {
 
 /// A connection to the AniList API.
 #[derive(Debug)]
-pub enum AniList {
-    /// An `AniList` connection with authentication.
+pub struct AniList {
+    client: Client,
+    limiter: Arc<RateLimiter>,
+    auth: Option<Auth>,
+}
+
+impl AniList {
+    /// Create a new `AniList` connection with authentication.
     ///
     /// This mode will allow you to update the specified user's list.
     /// To get a user's token, they will need to visit the URL provided by
     /// the `auth_url` function and provide it to you. The token should then be
     /// stored as it is only visible once.
-    Authenticated(Auth),
-    /// An `AniList` connection without any authentication.
+    #[must_use]
+    pub fn authenticated(auth: Auth) -> Self {
+        Self {
+            client: Client::default(),
+            limiter: Arc::default(),
+            auth: Some(auth),
+        }
+    }
+
+    /// Create a new `AniList` connection without any authentication.
     ///
     /// In this mode, you can only search for series info by name and by ID.
     /// Trying to make any other request will return a `NeedAuthentication` error.
-    Unauthenticated,
-}
-
-impl AniList {
-    fn auth(&self) -> Result<&Auth> {
-        match &self {
-            Self::Authenticated(auth) => Ok(auth),
-            Self::Unauthenticated => Err(Error::NeedAuthentication),
+    #[must_use]
+    pub fn unauthenticated() -> Self {
+        Self {
+            client: Client::default(),
+            limiter: Arc::default(),
+            auth: None,
         }
     }
 
+    /// Send all requests through the specified `client`.
+    #[must_use]
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Use the specified `limiter` to stay within AniList's rate limit.
+    ///
+    /// By default, requests that are rate limited will be retried a few times before an
+    /// `Error::RateLimited` is returned.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Arc::new(limiter);
+        self
+    }
+
+    /// Returns the current user's authentication, if there is one.
+    #[inline(always)]
+    #[must_use]
+    pub fn auth(&self) -> Option<&Auth> {
+        self.auth.as_ref()
+    }
+
+    fn need_auth(&self) -> Result<&Auth> {
+        self.auth.as_ref().ok_or(Error::NeedAuthentication)
+    }
+
     fn auth_token(&self) -> Result<&AccessToken> {
-        self.auth().map(|auth| &auth.token)
+        self.need_auth().map(|auth| &auth.token)
     }
 
     fn score_format(&self) -> ScoreFormat {
-        match &self {
-            Self::Authenticated(auth) => auth.user.options.score_format,
-            Self::Unauthenticated => ScoreFormat::default(),
-        }
+        self.auth
+            .as_ref()
+            .map_or_else(ScoreFormat::default, |auth| auth.user.options.score_format)
     }
 }
 
 impl RemoteService for AniList {
     fn search_info_by_name(&self, name: &str) -> Result<Vec<SeriesInfo>> {
         let entries: Vec<Media> = query!(
+            &self.client,
+            &self.limiter,
             self.auth_token().ok(),
             "info_by_name",
             { "name": name },
@@ -163,17 +211,147 @@ impl RemoteService for AniList {
         Ok(entries)
     }
 
+    fn search_info(&self, name: &str, options: &SearchOptions) -> Result<SearchPage> {
+        let page: MediaPage = query!(
+            &self.client,
+            &self.limiter,
+            self.auth_token().ok(),
+            "search_info",
+            {
+                "name": name,
+                "page": options.page,
+                "perPage": options.per_page,
+                "formats": options.kind.map(MediaFormat::formats_of_kind),
+                "seasonYear": options.season_year,
+                "status": options.status.map(MediaAiringStatus::from),
+            },
+            "data" => "Page"
+        )?;
+
+        let results = page
+            .media
+            .into_iter()
+            .filter_map(|entry| entry.try_into().ok())
+            .collect();
+
+        Ok(SearchPage {
+            results,
+            has_next_page: page.info.has_next_page,
+        })
+    }
+
     fn search_info_by_id(&self, id: SeriesID) -> Result<SeriesInfo> {
-        let info: Media =
-            query!(self.auth_token().ok(), "info_by_id", { "id": id }, "data" => "Media")?;
+        let info: Media = query!(
+            &self.client,
+            &self.limiter,
+            self.auth_token().ok(),
+            "info_by_id",
+            { "id": id },
+            "data" => "Media"
+        )?;
 
         info.try_into().map_err(|_| Error::NotAnAnime)
     }
 
+    fn search_info_by_ids(&self, ids: &[SeriesID]) -> Result<Vec<SeriesInfo>> {
+        let mut results = Vec::with_capacity(ids.len());
+
+        for chunk in ids.chunks(MAX_PAGE_SIZE) {
+            let entries: Vec<Media> = query!(
+                &self.client,
+                &self.limiter,
+                self.auth_token().ok(),
+                "info_by_ids",
+                { "ids": chunk },
+                "data" => "Page" => "media"
+            )?;
+
+            results.extend(
+                entries
+                    .into_iter()
+                    .filter_map(|entry| entry.try_into().ok()),
+            );
+        }
+
+        Ok(results)
+    }
+
+    fn get_recommendations(&self, id: SeriesID) -> Result<Vec<Recommendation>> {
+        // The most recommendations worth showing, as the rest are rarely relevant
+        const MAX_RECOMMENDATIONS: u32 = 25;
+
+        let recommendations: Vec<MediaRecommendation> = query!(
+            &self.client,
+            &self.limiter,
+            self.auth_token().ok(),
+            "recommendations",
+            { "id": id, "perPage": MAX_RECOMMENDATIONS },
+            "data" => "Media" => "recommendations" => "nodes"
+        )?;
+
+        let results = recommendations
+            .into_iter()
+            .filter_map(|recommendation| {
+                // Recommendations of series that have been removed have no media
+                let info = recommendation.media?.try_into().ok()?;
+
+                Some(Recommendation {
+                    info,
+                    rating: recommendation.rating.unwrap_or(0),
+                })
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    fn get_season(
+        &self,
+        season: Season,
+        year: u32,
+        kind: Option<SeriesKind>,
+    ) -> Result<Vec<SeriesInfo>> {
+        let mut results = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let media: MediaPage = query!(
+                &self.client,
+                &self.limiter,
+                self.auth_token().ok(),
+                "season",
+                {
+                    "season": MediaSeason::from(season),
+                    "seasonYear": year,
+                    "page": page,
+                    "formats": kind.map(MediaFormat::formats_of_kind),
+                },
+                "data" => "Page"
+            )?;
+
+            results.extend(
+                media
+                    .media
+                    .into_iter()
+                    .filter_map(|entry| entry.try_into().ok()),
+            );
+
+            if !media.info.has_next_page {
+                break;
+            }
+
+            page += 1;
+        }
+
+        Ok(results)
+    }
+
     fn get_list_entry(&self, id: SeriesID) -> Result<Option<SeriesEntry>> {
-        let auth = self.auth()?;
+        let auth = self.need_auth()?;
 
         let query: Result<MediaEntry> = query!(
+            &self.client,
+            &self.limiter,
             Some(&auth.token),
             "get_list_entry",
             { "id": id, "userID": auth.user.id },
@@ -187,10 +365,169 @@ impl RemoteService for AniList {
         }
     }
 
-    fn update_list_entry(&self, entry: &SeriesEntry) -> Result<()> {
+    fn get_list_entries(&self, ids: &[SeriesID]) -> Result<Vec<SeriesEntry>> {
+        let auth = self.need_auth()?;
+        let mut results = Vec::with_capacity(ids.len());
+
+        for chunk in ids.chunks(MAX_PAGE_SIZE) {
+            let entries: Vec<MediaListEntry> = query!(
+                &self.client,
+                &self.limiter,
+                Some(&auth.token),
+                "get_list_entries",
+                { "ids": chunk, "userID": auth.user.id },
+                "data" => "Page" => "mediaList"
+            )?;
+
+            results.extend(
+                entries
+                    .into_iter()
+                    .map(|entry| entry.entry.into_series_entry(entry.media_id)),
+            );
+        }
+
+        Ok(results)
+    }
+
+    fn get_mal_ids(&self, ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        let mut results = HashMap::with_capacity(ids.len());
+
+        for chunk in ids.chunks(MAX_PAGE_SIZE) {
+            let entries: Vec<MediaIds> = query!(
+                &self.client,
+                &self.limiter,
+                self.auth_token().ok(),
+                "mal_ids",
+                { "ids": chunk },
+                "data" => "Page" => "media"
+            )?;
+
+            results.extend(
+                entries
+                    .into_iter()
+                    .filter_map(|entry| Some((entry.id, entry.mal_id?))),
+            );
+        }
+
+        Ok(results)
+    }
+
+    fn find_by_mal_ids(&self, mal_ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        let mut results = HashMap::with_capacity(mal_ids.len());
+
+        for chunk in mal_ids.chunks(MAX_PAGE_SIZE) {
+            let entries: Vec<MediaIds> = query!(
+                &self.client,
+                &self.limiter,
+                self.auth_token().ok(),
+                "ids_by_mal",
+                { "ids": chunk },
+                "data" => "Page" => "media"
+            )?;
+
+            results.extend(
+                entries
+                    .into_iter()
+                    .filter_map(|entry| Some((entry.mal_id?, entry.id))),
+            );
+        }
+
+        Ok(results)
+    }
+
+    fn get_airing_schedule(
+        &self,
+        ids: &[SeriesID],
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<AiringEpisode>> {
+        // AniList returns the schedule of every series when no IDs are given
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut results = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let schedule: AiringSchedulePage = query!(
+                &self.client,
+                &self.limiter,
+                self.auth_token().ok(),
+                "airing_schedule",
+                { "ids": ids, "page": page, "from": from, "to": to },
+                "data" => "Page"
+            )?;
+
+            results.extend(
+                schedule
+                    .schedules
+                    .into_iter()
+                    .map(Into::<AiringEpisode>::into),
+            );
+
+            if !schedule.info.has_next_page {
+                break;
+            }
+
+            page += 1;
+        }
+
+        Ok(results)
+    }
+
+    fn get_user_list(&self) -> Result<Vec<UserListEntry>> {
+        // The largest chunk size AniList allows
+        const CHUNK_SIZE: u32 = 500;
+
+        let auth = self.need_auth()?;
+        let mut results = Vec::new();
+        let mut seen = HashSet::new();
+        let mut chunk = 1;
+
+        loop {
+            let collection: MediaListCollection = query!(
+                &self.client,
+                &self.limiter,
+                Some(&auth.token),
+                "get_user_list",
+                { "userID": auth.user.id, "chunk": chunk, "perChunk": CHUNK_SIZE },
+                "data" => "MediaListCollection"
+            )?;
+
+            let entries = collection
+                .lists
+                .into_iter()
+                .flat_map(|list| list.entries)
+                .filter_map(|entry| {
+                    let favourite = entry.media.is_favourite.unwrap_or(false);
+                    let info: SeriesInfo = entry.media.try_into().ok()?;
+
+                    let mut entry = entry.list.entry.into_series_entry(entry.list.media_id);
+                    entry.favourite = favourite;
+
+                    Some(UserListEntry { info, entry })
+                });
+
+            // A series can appear in more than one list when the user has custom lists
+            results.extend(entries.filter(|entry| seen.insert(entry.info.id)));
+
+            if !collection.has_next_chunk {
+                break;
+            }
+
+            chunk += 1;
+        }
+
+        Ok(results)
+    }
+
+    fn update_list_entry(&self, entry: &SeriesEntry) -> Result<Option<i64>> {
         let token = self.auth_token()?;
 
-        send!(
+        let saved = send!(
+            &self.client,
+            &self.limiter,
             Some(token),
             "update_list_entry",
             {
@@ -201,7 +538,73 @@ impl RemoteService for AniList {
                 "times_rewatched": entry.times_rewatched,
                 "start_date": entry.start_date.map(MediaDate::from),
                 "finish_date": entry.end_date.map(MediaDate::from),
+                // Notes are only cleared when they're set to an empty string
+                "notes": entry.notes.as_deref().unwrap_or_default(),
+                "private": entry.private,
+                "hidden": entry.hidden,
+                "custom_lists": entry.custom_lists,
             },
+            "data" => "SaveMediaListEntry"
+        )?;
+
+        Ok(saved["updatedAt"]
+            .as_i64()
+            .filter(|&updated_at| updated_at > 0))
+    }
+
+    fn delete_list_entry(&self, id: SeriesID) -> Result<()> {
+        let auth = self.need_auth()?;
+
+        // AniList deletes list entries by their own ID, so we need to find it first
+        let query: Result<MediaListID> = query!(
+            &self.client,
+            &self.limiter,
+            Some(&auth.token),
+            "list_entry_id",
+            { "id": id, "userID": auth.user.id },
+            "data" => "MediaList"
+        );
+
+        let entry_id = match query {
+            Ok(entry) => entry.id,
+            Err(ref err) if err.is_http_code(404) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        send!(
+            &self.client,
+            &self.limiter,
+            Some(&auth.token),
+            "delete_list_entry",
+            { "id": entry_id },
+        )?;
+
+        Ok(())
+    }
+
+    fn set_favourite(&self, id: SeriesID, favourite: bool) -> Result<()> {
+        let token = self.auth_token()?;
+
+        // AniList can only toggle favourites, so we need to know the current state first
+        let current: MediaFavourite = query!(
+            &self.client,
+            &self.limiter,
+            Some(token),
+            "is_favourite",
+            { "id": id },
+            "data" => "Media"
+        )?;
+
+        if current.is_favourite == favourite {
+            return Ok(());
+        }
+
+        send!(
+            &self.client,
+            &self.limiter,
+            Some(token),
+            "toggle_favourite",
+            { "id": id },
         )?;
 
         Ok(())
@@ -252,12 +655,61 @@ impl Auth {
     }
 
     /// Retrieve the current authorization from AniList using the specified `token`.
+    #[inline(always)]
     pub fn retrieve(token: AccessToken) -> Result<Self> {
-        let user = query!(Some(&token), "user", {}, "data" => "Viewer")?;
+        Self::retrieve_with(&Client::default(), token)
+    }
+
+    /// Retrieve the current authorization from AniList using the specified `token`, sending the request through `client`.
+    pub fn retrieve_with(client: &Client, token: AccessToken) -> Result<Self> {
+        let limiter = RateLimiter::default();
+        let user = query!(client, &limiter, Some(&token), "user", {}, "data" => "Viewer")?;
         Ok(Self::new(user, token))
     }
 }
 
+/// The time an access token was issued and the time it expires, in seconds since the Unix epoch.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TokenLifetime {
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+impl TokenLifetime {
+    /// Read the lifetime of an AniList access `token` without making a request.
+    ///
+    /// AniList access tokens are JSON Web Tokens, which contain the time they were issued and expire at.
+    /// Returns `None` if the `token` isn't in that format.
+    #[must_use]
+    // The fractional part of each time is intentionally dropped
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_token(token: &AccessToken) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct Claims {
+            // These can have a fractional part
+            iat: f64,
+            exp: f64,
+        }
+
+        let token = token.decode().ok()?;
+        let payload = token.split('.').nth(1)?;
+        let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD).ok()?;
+        let claims: Claims = json::from_slice(&payload).ok()?;
+
+        Some(Self {
+            issued_at: claims.iat as i64,
+            expires_at: claims.exp as i64,
+        })
+    }
+
+    /// Returns true if the token has expired at the time `now`, in seconds since the Unix epoch.
+    #[inline(always)]
+    #[must_use]
+    pub fn is_expired(self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+}
+
 /// An AniList user.
 #[derive(Debug, Deserialize)]
 pub struct User {
@@ -341,6 +793,8 @@ impl Default for ScoreFormat {
 }
 
 fn send_gql_request<S>(
+    client: &Client,
+    limiter: &RateLimiter,
     query: S,
     vars: &json::Value,
     token: Option<&AccessToken>,
@@ -348,22 +802,20 @@ fn send_gql_request<S>(
 where
     S: AsRef<str>,
 {
-    const REQ_TIMEOUT_SEC: u64 = 15;
-
     let body = json!({
         "query": query.as_ref(),
         "variables": vars,
     });
 
-    let mut request = attohttpc::post(API_URL)
-        .timeout(Duration::from_secs(REQ_TIMEOUT_SEC))
+    let mut request = client
+        .request(Method::Post, client.base_url(API_URL))
         .json(&body)?;
 
     if let Some(token) = token {
         request = request.bearer_auth(&token.decode()?);
     }
 
-    let json: json::Value = request.send()?.json()?;
+    let json: json::Value = limiter.send(client, &request)?.json()?;
 
     if json["errors"] != json::Value::Null {
         let err = &json["errors"][0];
@@ -371,20 +823,58 @@ where
         let message = err["message"].as_str().unwrap_or("unknown").to_string();
         let code = err["status"].as_u64().unwrap_or(0) as u16;
 
+        // Expired and revoked tokens are reported as a bad request rather than with a 401
+        if token.is_some() && (code == 401 || message.eq_ignore_ascii_case("invalid token")) {
+            return Err(Error::InvalidToken);
+        }
+
         return Err(Error::BadAniListResponse { code, message });
     }
 
     Ok(json)
 }
 
+#[derive(Debug, Deserialize)]
+struct MediaPage {
+    #[serde(rename = "pageInfo")]
+    info: PageInfo,
+    media: Vec<Media>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageInfo {
+    #[serde(rename = "hasNextPage")]
+    has_next_page: bool,
+}
+
 #[derive(Debug, Deserialize)]
 struct Media {
     id: u32,
     title: MediaTitle,
+    #[serde(default)]
+    synonyms: Option<Vec<String>>,
     episodes: Option<u32>,
     duration: Option<u32>,
     relations: Option<MediaRelation>,
     format: MediaFormat,
+    #[serde(default)]
+    season: Option<MediaSeason>,
+    #[serde(default, rename = "seasonYear")]
+    season_year: Option<u32>,
+    #[serde(default)]
+    status: Option<MediaAiringStatus>,
+    #[serde(default)]
+    genres: Option<Vec<String>>,
+    #[serde(default)]
+    studios: Option<StudioConnection>,
+    #[serde(default, rename = "averageScore")]
+    average_score: Option<u8>,
+    #[serde(default, rename = "isAdult")]
+    is_adult: Option<bool>,
+    #[serde(default, rename = "isFavourite")]
+    is_favourite: Option<bool>,
+    #[serde(default, rename = "nextAiringEpisode")]
+    next_airing_episode: Option<AiringSchedule>,
 }
 
 impl Media {
@@ -400,6 +890,19 @@ impl Media {
             .filter_map(|edge| edge.try_into().ok())
             .collect()
     }
+
+    fn relations(&self) -> Vec<Relation> {
+        let relations = match self.relations.as_ref() {
+            Some(relations) => relations,
+            None => return Vec::new(),
+        };
+
+        relations
+            .edges
+            .iter()
+            .filter_map(MediaEdge::relation)
+            .collect()
+    }
 }
 
 impl TryInto<SeriesInfo> for Media {
@@ -408,14 +911,36 @@ impl TryInto<SeriesInfo> for Media {
     fn try_into(self) -> result::Result<SeriesInfo, Self::Error> {
         let kind = self.format.try_into()?;
         let sequels = self.sequels();
+        let relations = self.relations();
+
+        let studios = self
+            .studios
+            .map(|studios| {
+                studios
+                    .nodes
+                    .into_iter()
+                    .map(|studio| studio.name)
+                    .collect()
+            })
+            .unwrap_or_default();
 
         Ok(SeriesInfo {
             id: self.id,
             title: self.title.into(),
-            episodes: self.episodes.unwrap_or(1),
+            episodes: self.episodes.unwrap_or(0),
             episode_length: self.duration.unwrap_or(24),
             kind,
             sequels,
+            relations,
+            synonyms: self.synonyms.unwrap_or_default(),
+            season: self.season.map(Into::into),
+            year: self.season_year,
+            status: self.status.map(Into::into),
+            genres: self.genres.unwrap_or_default(),
+            studios,
+            average_score: self.average_score,
+            is_adult: self.is_adult.unwrap_or(false),
+            next_episode: self.next_airing_episode.map(Into::into),
         })
     }
 }
@@ -425,6 +950,10 @@ struct MediaTitle {
     romaji: String,
     #[serde(rename = "userPreferred")]
     preferred: String,
+    #[serde(default)]
+    english: Option<String>,
+    #[serde(default)]
+    native: Option<String>,
 }
 
 impl Into<SeriesTitle> for MediaTitle {
@@ -432,10 +961,111 @@ impl Into<SeriesTitle> for MediaTitle {
         SeriesTitle {
             romaji: self.romaji,
             preferred: self.preferred,
+            english: self.english,
+            native: self.native,
         }
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct AiringSchedulePage {
+    #[serde(rename = "pageInfo")]
+    info: PageInfo,
+    #[serde(rename = "airingSchedules")]
+    schedules: Vec<ScheduledEpisode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduledEpisode {
+    #[serde(rename = "mediaId")]
+    media_id: u32,
+    episode: u32,
+    #[serde(rename = "airingAt")]
+    airing_at: i64,
+}
+
+impl Into<AiringEpisode> for ScheduledEpisode {
+    fn into(self) -> AiringEpisode {
+        AiringEpisode {
+            id: self.media_id,
+            episode: self.episode,
+            airs_at: self.airing_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AiringSchedule {
+    episode: u32,
+    #[serde(rename = "airingAt")]
+    airing_at: i64,
+}
+
+impl Into<NextEpisode> for AiringSchedule {
+    fn into(self) -> NextEpisode {
+        NextEpisode {
+            number: self.episode,
+            airs_at: self.airing_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StudioConnection {
+    nodes: Vec<Studio>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Studio {
+    name: String,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+enum MediaSeason {
+    Winter,
+    Spring,
+    Summer,
+    Fall,
+}
+
+impl Into<Season> for MediaSeason {
+    fn into(self) -> Season {
+        match self {
+            Self::Winter => Season::Winter,
+            Self::Spring => Season::Spring,
+            Self::Summer => Season::Summer,
+            Self::Fall => Season::Fall,
+        }
+    }
+}
+
+impl From<Season> for MediaSeason {
+    fn from(season: Season) -> Self {
+        match season {
+            Season::Winter => Self::Winter,
+            Season::Spring => Self::Spring,
+            Season::Summer => Self::Summer,
+            Season::Fall => Self::Fall,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaIds {
+    id: u32,
+    #[serde(default, rename = "idMal")]
+    mal_id: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaRecommendation {
+    #[serde(default)]
+    rating: Option<i32>,
+    #[serde(rename = "mediaRecommendation")]
+    media: Option<Media>,
+}
+
 #[derive(Debug, Deserialize)]
 struct MediaRelation {
     edges: Vec<MediaEdge>,
@@ -469,13 +1099,37 @@ impl TryInto<Sequel> for &MediaEdge {
     }
 }
 
+impl MediaEdge {
+    /// Returns the relation the edge represents, or None if it doesn't point to an anime.
+    fn relation(&self) -> Option<Relation> {
+        // Series added before the media type was requested are assumed to be anime
+        if let Some(MediaType::Manga) = self.node.media_type {
+            return None;
+        }
+
+        let kind = self.relation.kind()?;
+        let series_kind = self.node.format.and_then(|fmt| fmt.try_into().ok());
+
+        Some(Relation::new(kind, self.node.id, series_kind))
+    }
+}
+
 #[derive(Copy, Clone, Debug, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 enum MediaRelationType {
+    Adaptation,
+    Prequel,
     Sequel,
-    #[serde(rename = "SIDE_STORY")]
+    Parent,
     SideStory,
+    Character,
+    Summary,
+    Alternative,
+    SpinOff,
     Other,
+    Source,
+    Compilation,
+    Contains,
     #[serde(other)]
     Unknown,
 }
@@ -483,19 +1137,45 @@ enum MediaRelationType {
 impl MediaRelationType {
     /// Returns true if the relation is considered to be some kind of sequel. Ex: a second season, OVA, ONA, movie, etc
     fn is_sequential(self) -> bool {
-        match self {
-            Self::Sequel | Self::SideStory | Self::Other => true,
-            Self::Unknown => false,
-        }
+        matches!(self, Self::Sequel | Self::SideStory | Self::Other)
+    }
+
+    fn kind(self) -> Option<RelationKind> {
+        let kind = match self {
+            Self::Adaptation | Self::Source => RelationKind::Source,
+            Self::Prequel => RelationKind::Prequel,
+            Self::Sequel => RelationKind::Sequel,
+            Self::Parent => RelationKind::Parent,
+            Self::SideStory => RelationKind::SideStory,
+            Self::Character => RelationKind::Character,
+            Self::Summary => RelationKind::Summary,
+            Self::Alternative => RelationKind::Alternative,
+            Self::SpinOff => RelationKind::SpinOff,
+            Self::Other => RelationKind::Other,
+            Self::Compilation => RelationKind::Compilation,
+            Self::Contains => RelationKind::Contains,
+            Self::Unknown => return None,
+        };
+
+        Some(kind)
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct MediaNode {
     id: u32,
+    #[serde(default, rename = "type")]
+    media_type: Option<MediaType>,
     format: Option<MediaFormat>,
 }
 
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+enum MediaType {
+    Anime,
+    Manga,
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 enum MediaFormat {
@@ -511,6 +1191,20 @@ enum MediaFormat {
     Other,
 }
 
+impl MediaFormat {
+    /// Returns the names of every format that is considered to be the specified `kind` of series.
+    fn formats_of_kind(kind: SeriesKind) -> &'static [&'static str] {
+        match kind {
+            SeriesKind::Season => &["TV", "TV_SHORT"],
+            SeriesKind::Movie => &["MOVIE"],
+            SeriesKind::Special => &["SPECIAL"],
+            SeriesKind::OVA => &["OVA"],
+            SeriesKind::ONA => &["ONA"],
+            SeriesKind::Music => &["MUSIC"],
+        }
+    }
+}
+
 impl TryInto<SeriesKind> for MediaFormat {
     type Error = ();
 
@@ -537,6 +1231,62 @@ struct MediaEntry {
     start_date: MediaDate,
     #[serde(rename = "completedAt")]
     complete_date: MediaDate,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    private: Option<bool>,
+    #[serde(default, rename = "hiddenFromStatusLists")]
+    hidden: Option<bool>,
+    #[serde(default, rename = "customLists")]
+    custom_lists: Option<Vec<CustomList>>,
+    #[serde(default, rename = "updatedAt")]
+    updated_at: Option<i64>,
+    #[serde(default)]
+    media: Option<MediaFavourite>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaFavourite {
+    #[serde(rename = "isFavourite")]
+    is_favourite: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CustomList {
+    name: String,
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaListID {
+    id: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaListEntry {
+    #[serde(rename = "mediaId")]
+    media_id: u32,
+    #[serde(flatten)]
+    entry: MediaEntry,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaListCollection {
+    #[serde(rename = "hasNextChunk", default)]
+    has_next_chunk: bool,
+    lists: Vec<MediaListGroup>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaListGroup {
+    entries: Vec<MediaCollectionEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaCollectionEntry {
+    #[serde(flatten)]
+    list: MediaListEntry,
+    media: Media,
 }
 
 impl MediaEntry {
@@ -551,6 +1301,18 @@ impl MediaEntry {
             times_rewatched: self.repeat,
             start_date: self.start_date.try_into().ok(),
             end_date: self.complete_date.try_into().ok(),
+            notes: self.notes.filter(|notes| !notes.is_empty()),
+            private: self.private.unwrap_or(false),
+            hidden: self.hidden.unwrap_or(false),
+            custom_lists: self
+                .custom_lists
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|list| list.enabled)
+                .map(|list| list.name)
+                .collect(),
+            updated_at: self.updated_at.filter(|&updated_at| updated_at > 0),
+            favourite: matches!(self.media, Some(MediaFavourite { is_favourite: true })),
         }
     }
 }
@@ -597,6 +1359,40 @@ impl From<Status> for MediaStatus {
     }
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum MediaAiringStatus {
+    Finished,
+    Releasing,
+    NotYetReleased,
+    Cancelled,
+    Hiatus,
+}
+
+impl From<AiringStatus> for MediaAiringStatus {
+    fn from(status: AiringStatus) -> Self {
+        match status {
+            AiringStatus::Finished => Self::Finished,
+            AiringStatus::Airing => Self::Releasing,
+            AiringStatus::NotYetAired => Self::NotYetReleased,
+            AiringStatus::Cancelled => Self::Cancelled,
+            AiringStatus::Hiatus => Self::Hiatus,
+        }
+    }
+}
+
+impl Into<AiringStatus> for MediaAiringStatus {
+    fn into(self) -> AiringStatus {
+        match self {
+            Self::Finished => AiringStatus::Finished,
+            Self::Releasing => AiringStatus::Airing,
+            Self::NotYetReleased => AiringStatus::NotYetAired,
+            Self::Cancelled => AiringStatus::Cancelled,
+            Self::Hiatus => AiringStatus::Hiatus,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 struct MediaDate {
     year: Option<u16>,
@@ -624,3 +1420,686 @@ impl TryInto<SeriesDate> for MediaDate {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::test_server::{TestResponse, TestServer};
+    use crate::remote::transport::ClientConfig;
+
+    const MEDIA_JSON: &str = r#"{
+        "data": {
+            "Media": {
+                "id": 21,
+                "title": { "romaji": "One Piece", "userPreferred": "One Piece" },
+                "episodes": null,
+                "duration": 24,
+                "format": "TV",
+                "relations": {
+                    "edges": [
+                        { "relationType": "SIDE_STORY", "node": { "id": 459, "format": "MOVIE" } },
+                        { "relationType": "ADAPTATION", "node": { "id": 30013, "format": "MANGA" } }
+                    ]
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn info_from_mock_server() {
+        let server = TestServer::spawn(vec![MEDIA_JSON.into()]);
+
+        let client = Client::new(ClientConfig {
+            base_url: Some(server.url()),
+            user_agent: Some("anime-test".into()),
+            ..ClientConfig::default()
+        });
+
+        let anilist = AniList::unauthenticated().with_client(client);
+        let info = anilist.search_info_by_id(21).unwrap();
+
+        assert_eq!(info.id, 21);
+        assert_eq!(info.title.romaji, "One Piece");
+        assert_eq!(info.kind, SeriesKind::Season);
+        assert_eq!(info.sequels.len(), 1);
+        assert_eq!(info.sequels[0].kind, SeriesKind::Movie);
+
+        let request = server.requests().remove(0);
+        assert!(request.starts_with("POST / "));
+        assert!(request.contains("user-agent: anime-test"));
+        assert!(request.contains(r#""variables":{"id":21}"#));
+    }
+
+    #[test]
+    fn relations_are_typed() {
+        let server = TestServer::spawn(vec![r#"{
+            "data": {
+                "Media": {
+                    "id": 20,
+                    "title": { "romaji": "Naruto", "userPreferred": "Naruto" },
+                    "episodes": 220,
+                    "duration": 23,
+                    "format": "TV",
+                    "relations": {
+                        "edges": [
+                            { "relationType": "SEQUEL", "node": { "id": 1735, "type": "ANIME", "format": "TV" } },
+                            { "relationType": "SPIN_OFF", "node": { "id": 21, "type": "ANIME", "format": "ONA" } },
+                            { "relationType": "CHARACTER", "node": { "id": 22, "type": "ANIME", "format": null } },
+                            { "relationType": "ADAPTATION", "node": { "id": 30011, "type": "MANGA", "format": "MANGA" } }
+                        ]
+                    }
+                }
+            }
+        }"#
+        .into()]);
+
+        let anilist = AniList::unauthenticated().with_client(server.client());
+        let info = anilist.search_info_by_id(20).unwrap();
+
+        let relations = info
+            .relations
+            .iter()
+            .map(|relation| (relation.kind, relation.id, relation.series_kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            relations,
+            [
+                (RelationKind::Sequel, 1735, Some(SeriesKind::Season)),
+                (RelationKind::SpinOff, 21, Some(SeriesKind::ONA)),
+                (RelationKind::Character, 22, None),
+            ]
+        );
+
+        // Spin-offs aren't considered sequels
+        assert_eq!(info.sequels.len(), 1);
+    }
+
+    #[test]
+    fn recommendations_without_media_are_skipped() {
+        let server = TestServer::spawn(vec![r#"{
+            "data": {
+                "Media": {
+                    "recommendations": {
+                        "nodes": [
+                            {
+                                "rating": 120,
+                                "mediaRecommendation": {
+                                    "id": 1535,
+                                    "title": { "romaji": "Death Note", "userPreferred": "Death Note" },
+                                    "episodes": 37,
+                                    "duration": 23,
+                                    "format": "TV",
+                                    "seasonYear": 2006
+                                }
+                            },
+                            { "rating": 40, "mediaRecommendation": null }
+                        ]
+                    }
+                }
+            }
+        }"#
+        .into()]);
+
+        let anilist = AniList::unauthenticated().with_client(server.client());
+        let recommendations = anilist.get_recommendations(20).unwrap();
+
+        assert_eq!(recommendations.len(), 1);
+        assert_eq!(recommendations[0].info.id, 1535);
+        assert_eq!(recommendations[0].info.year, Some(2006));
+        assert_eq!(recommendations[0].rating, 120);
+
+        let request = server.requests().remove(0);
+        assert!(request.contains(r#""id":20"#));
+    }
+
+    #[test]
+    fn extended_info_is_parsed() {
+        let server = TestServer::spawn(vec![r#"{
+            "data": {
+                "Media": {
+                    "id": 16498,
+                    "title": {
+                        "romaji": "Shingeki no Kyojin",
+                        "english": "Attack on Titan",
+                        "native": "進撃の巨人",
+                        "userPreferred": "Shingeki no Kyojin"
+                    },
+                    "synonyms": ["AoT", "SnK"],
+                    "episodes": 25,
+                    "duration": 24,
+                    "format": "TV",
+                    "season": "SPRING",
+                    "seasonYear": 2013,
+                    "status": "FINISHED",
+                    "genres": ["Action", "Drama"],
+                    "studios": { "nodes": [{ "name": "Wit Studio" }] },
+                    "averageScore": 84,
+                    "isAdult": false,
+                    "relations": null
+                }
+            }
+        }"#
+        .into()]);
+
+        let anilist = AniList::unauthenticated().with_client(server.client());
+        let info = anilist.search_info_by_id(16498).unwrap();
+
+        assert_eq!(info.title.english.as_deref(), Some("Attack on Titan"));
+        assert_eq!(info.title.native.as_deref(), Some("進撃の巨人"));
+        assert_eq!(info.synonyms, ["AoT", "SnK"]);
+        assert_eq!(info.season, Some(Season::Spring));
+        assert_eq!(info.year, Some(2013));
+        assert_eq!(info.status, Some(AiringStatus::Finished));
+        assert_eq!(info.genres, ["Action", "Drama"]);
+        assert_eq!(info.studios, ["Wit Studio"]);
+        assert_eq!(info.average_score, Some(84));
+        assert!(!info.is_adult);
+        assert_eq!(info.aired_episodes(), Some(25));
+    }
+
+    #[test]
+    fn airing_info_is_parsed() {
+        let server = TestServer::spawn(vec![r#"{
+            "data": {
+                "Media": {
+                    "id": 130003,
+                    "title": { "romaji": "Bocchi the Rock!", "userPreferred": "Bocchi the Rock!" },
+                    "episodes": null,
+                    "duration": 24,
+                    "format": "TV",
+                    "status": "RELEASING",
+                    "nextAiringEpisode": { "episode": 8, "airingAt": 1669474800 },
+                    "relations": null
+                }
+            }
+        }"#
+        .into()]);
+
+        let anilist = AniList::unauthenticated().with_client(server.client());
+        let info = anilist.search_info_by_id(130_003).unwrap();
+
+        assert_eq!(info.episode_count(), None);
+        assert_eq!(info.aired_episodes(), Some(7));
+        assert_eq!(info.next_episode.unwrap().airs_at, 1_669_474_800);
+    }
+
+    #[test]
+    fn airing_schedule_is_paged() {
+        let first_page = r#"{
+            "data": {
+                "Page": {
+                    "pageInfo": { "hasNextPage": true },
+                    "airingSchedules": [{ "mediaId": 1, "episode": 3, "airingAt": 1669474800 }]
+                }
+            }
+        }"#;
+
+        let second_page = r#"{
+            "data": {
+                "Page": {
+                    "pageInfo": { "hasNextPage": false },
+                    "airingSchedules": [{ "mediaId": 2, "episode": 10, "airingAt": 1669561200 }]
+                }
+            }
+        }"#;
+
+        let server = TestServer::spawn(vec![first_page.into(), second_page.into()]);
+        let anilist = AniList::unauthenticated().with_client(server.client());
+
+        let schedule = anilist
+            .get_airing_schedule(&[1, 2], 1_669_400_000, 1_670_000_000)
+            .unwrap();
+
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(schedule[0].id, 1);
+        assert_eq!(schedule[1].episode, 10);
+        assert_eq!(schedule[1].airs_at, 1_669_561_200);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains(r#""page":2"#));
+    }
+
+    #[test]
+    fn season_is_paged() {
+        let first_page = r#"{
+            "data": {
+                "Page": {
+                    "pageInfo": { "hasNextPage": true },
+                    "media": [{
+                        "id": 1,
+                        "title": { "romaji": "First", "userPreferred": "First" },
+                        "episodes": 12,
+                        "duration": 24,
+                        "format": "TV"
+                    }]
+                }
+            }
+        }"#;
+
+        let second_page = r#"{
+            "data": {
+                "Page": {
+                    "pageInfo": { "hasNextPage": false },
+                    "media": [{
+                        "id": 2,
+                        "title": { "romaji": "Second", "userPreferred": "Second" },
+                        "episodes": 12,
+                        "duration": 24,
+                        "format": "TV_SHORT"
+                    }]
+                }
+            }
+        }"#;
+
+        let server = TestServer::spawn(vec![first_page.into(), second_page.into()]);
+        let anilist = AniList::unauthenticated().with_client(server.client());
+
+        let season = anilist
+            .get_season(Season::Spring, 2021, Some(SeriesKind::Season))
+            .unwrap();
+
+        let ids = season.iter().map(|info| info.id).collect::<Vec<_>>();
+        assert_eq!(ids, [1, 2]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains(r#""season":"SPRING""#));
+        assert!(requests[0].contains(r#""seasonYear":2021"#));
+        assert!(requests[0].contains(r#""formats":["TV","TV_SHORT"]"#));
+        assert!(requests[1].contains(r#""page":2"#));
+    }
+
+    #[test]
+    fn search_options_are_sent() {
+        let server = TestServer::spawn(vec![r#"{
+            "data": {
+                "Page": {
+                    "pageInfo": { "hasNextPage": true },
+                    "media": [{
+                        "id": 5,
+                        "title": { "romaji": "Test Movie", "userPreferred": "Test Movie" },
+                        "episodes": 1,
+                        "duration": 90,
+                        "format": "MOVIE",
+                        "relations": null
+                    }]
+                }
+            }
+        }"#
+        .into()]);
+
+        let anilist = AniList::unauthenticated().with_client(server.client());
+
+        let options = SearchOptions::new()
+            .page(2)
+            .kind(SeriesKind::Movie)
+            .status(AiringStatus::Airing);
+
+        let page = anilist.search_info("test", &options).unwrap();
+
+        assert!(page.has_next_page);
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].kind, SeriesKind::Movie);
+
+        let request = server.requests().remove(0);
+        assert!(request.contains(r#""page":2"#));
+        assert!(request.contains(r#""formats":["MOVIE"]"#));
+        assert!(request.contains(r#""status":"RELEASING""#));
+        assert!(request.contains(r#""seasonYear":null"#));
+    }
+
+    #[test]
+    fn list_entries_are_batched() {
+        let user = r#"{
+            "data": {
+                "Viewer": { "id": 5, "name": "test", "mediaListOptions": { "scoreFormat": "POINT_100" } }
+            }
+        }"#;
+
+        let entries = r#"{
+            "data": {
+                "Page": {
+                    "mediaList": [
+                        {
+                            "mediaId": 1, "status": "CURRENT", "score": 0, "progress": 3, "repeat": 0,
+                            "startedAt": { "year": null, "month": null, "day": null },
+                            "completedAt": { "year": null, "month": null, "day": null }
+                        },
+                        {
+                            "mediaId": 3, "status": "COMPLETED", "score": 75, "progress": 12, "repeat": 1,
+                            "startedAt": { "year": 2020, "month": 4, "day": 1 },
+                            "completedAt": { "year": 2020, "month": 6, "day": 20 }
+                        }
+                    ]
+                }
+            }
+        }"#;
+
+        let server = TestServer::spawn(vec![user.into(), entries.into()]);
+        let client = server.client();
+        let auth = Auth::retrieve_with(&client, AccessToken::encode("token")).unwrap();
+        let anilist = AniList::authenticated(auth).with_client(client);
+
+        let entries = anilist.get_list_entries(&[1, 2, 3]).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 1);
+        assert_eq!(entries[0].watched_eps, 3);
+        assert_eq!(entries[1].id, 3);
+        assert_eq!(entries[1].status, Status::Completed);
+        assert_eq!(entries[1].score, Some(75));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains(r#""ids":[1,2,3]"#));
+    }
+
+    #[test]
+    fn extended_entry_fields_round_trip() {
+        let user = r#"{
+            "data": {
+                "Viewer": { "id": 5, "name": "test", "mediaListOptions": { "scoreFormat": "POINT_100" } }
+            }
+        }"#;
+
+        let entry = r#"{
+            "data": {
+                "MediaList": {
+                    "status": "CURRENT", "score": 0, "progress": 3, "repeat": 0,
+                    "startedAt": { "year": null, "month": null, "day": null },
+                    "completedAt": { "year": null, "month": null, "day": null },
+                    "notes": "rewatch with subs",
+                    "updatedAt": 1669474800,
+                    "private": true,
+                    "hiddenFromStatusLists": false,
+                    "customLists": [
+                        { "name": "Favorites", "enabled": true },
+                        { "name": "Backlog", "enabled": false }
+                    ]
+                }
+            }
+        }"#;
+
+        let saved =
+            r#"{ "data": { "SaveMediaListEntry": { "mediaId": 1, "updatedAt": 1669478400 } } }"#;
+
+        let server = TestServer::spawn(vec![user.into(), entry.into(), saved.into(), saved.into()]);
+        let client = server.client();
+        let auth = Auth::retrieve_with(&client, AccessToken::encode("token")).unwrap();
+        let anilist = AniList::authenticated(auth).with_client(client);
+
+        let mut entry = anilist.get_list_entry(1).unwrap().unwrap();
+
+        assert_eq!(entry.notes.as_deref(), Some("rewatch with subs"));
+        assert!(entry.private);
+        assert!(!entry.hidden);
+        assert_eq!(entry.custom_lists, ["Favorites"]);
+        assert_eq!(entry.updated_at, Some(1_669_474_800));
+
+        entry.hidden = true;
+        let updated_at = anilist.update_list_entry(&entry).unwrap();
+        assert_eq!(updated_at, Some(1_669_478_400));
+
+        entry.notes = None;
+        anilist.update_list_entry(&entry).unwrap();
+
+        let requests = server.requests();
+        assert!(requests[2].contains(r#""notes":"rewatch with subs""#));
+        assert!(requests[2].contains(r#""private":true"#));
+        assert!(requests[2].contains(r#""hidden":true"#));
+        assert!(requests[2].contains(r#""custom_lists":["Favorites"]"#));
+        // AniList ignores null notes, so they're cleared with an empty string instead
+        assert!(requests[3].contains(r#""notes":"""#));
+    }
+
+    #[test]
+    fn list_entry_is_deleted_by_its_own_id() {
+        let user = r#"{
+            "data": {
+                "Viewer": { "id": 5, "name": "test", "mediaListOptions": { "scoreFormat": "POINT_100" } }
+            }
+        }"#;
+
+        let entry_id = r#"{ "data": { "MediaList": { "id": 9001 } } }"#;
+        let deleted = r#"{ "data": { "DeleteMediaListEntry": { "deleted": true } } }"#;
+
+        let missing = TestResponse::with_status(
+            404,
+            r#"{ "data": null, "errors": [{ "message": "Not Found.", "status": 404 }] }"#,
+        );
+
+        let server = TestServer::spawn(vec![user.into(), entry_id.into(), deleted.into(), missing]);
+        let client = server.client();
+        let auth = Auth::retrieve_with(&client, AccessToken::encode("token")).unwrap();
+        let anilist = AniList::authenticated(auth).with_client(client);
+
+        anilist.delete_list_entry(21).unwrap();
+        // Series that aren't on the list have nothing to delete
+        anilist.delete_list_entry(22).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[1].contains(r#""id":21"#));
+        assert!(requests[2].contains("DeleteMediaListEntry"));
+        assert!(requests[2].contains(r#""id":9001"#));
+    }
+
+    #[test]
+    fn favourites_are_only_toggled_when_changed() {
+        let user = r#"{
+            "data": {
+                "Viewer": { "id": 5, "name": "test", "mediaListOptions": { "scoreFormat": "POINT_100" } }
+            }
+        }"#;
+
+        let entry = r#"{
+            "data": {
+                "MediaList": {
+                    "status": "CURRENT", "score": 0, "progress": 3, "repeat": 0,
+                    "startedAt": { "year": null, "month": null, "day": null },
+                    "completedAt": { "year": null, "month": null, "day": null },
+                    "media": { "isFavourite": true }
+                }
+            }
+        }"#;
+
+        let not_favourite = r#"{ "data": { "Media": { "isFavourite": false } } }"#;
+        let favourite = r#"{ "data": { "Media": { "isFavourite": true } } }"#;
+        let toggled =
+            r#"{ "data": { "ToggleFavourite": { "anime": { "pageInfo": { "total": 1 } } } } }"#;
+
+        let server = TestServer::spawn(vec![
+            user.into(),
+            entry.into(),
+            not_favourite.into(),
+            toggled.into(),
+            favourite.into(),
+        ]);
+
+        let client = server.client();
+        let auth = Auth::retrieve_with(&client, AccessToken::encode("token")).unwrap();
+        let anilist = AniList::authenticated(auth).with_client(client);
+
+        assert!(anilist.get_list_entry(1).unwrap().unwrap().favourite);
+
+        anilist.set_favourite(7, true).unwrap();
+        // Toggling a series that's already a favourite would remove it
+        anilist.set_favourite(7, true).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 5);
+        assert!(requests[3].contains("ToggleFavourite"));
+        assert!(requests[3].contains(r#""id":7"#));
+        assert!(!requests[4].contains("ToggleFavourite"));
+    }
+
+    #[test]
+    fn mal_ids_are_mapped_both_ways() {
+        let mal_ids = r#"{
+            "data": {
+                "Page": {
+                    "media": [{ "id": 1, "idMal": 101 }, { "id": 2, "idMal": null }]
+                }
+            }
+        }"#;
+
+        let anilist_ids = r#"{
+            "data": {
+                "Page": {
+                    "media": [{ "id": 3, "idMal": 103 }]
+                }
+            }
+        }"#;
+
+        let server = TestServer::spawn(vec![mal_ids.into(), anilist_ids.into()]);
+        let anilist = AniList::unauthenticated().with_client(server.client());
+
+        let mal_ids = anilist.get_mal_ids(&[1, 2]).unwrap();
+        assert_eq!(mal_ids.len(), 1);
+        assert_eq!(mal_ids.get(&1), Some(&101));
+
+        let anilist_ids = anilist.find_by_mal_ids(&[103, 104]).unwrap();
+        assert_eq!(anilist_ids.len(), 1);
+        assert_eq!(anilist_ids.get(&103), Some(&3));
+
+        let requests = server.requests();
+        assert!(requests[1].contains(r#""ids":[103,104]"#));
+    }
+
+    #[test]
+    fn user_list_is_retrieved_in_chunks() {
+        let user = r#"{
+            "data": {
+                "Viewer": { "id": 5, "name": "test", "mediaListOptions": { "scoreFormat": "POINT_100" } }
+            }
+        }"#;
+
+        let first_chunk = r#"{
+            "data": {
+                "MediaListCollection": {
+                    "hasNextChunk": true,
+                    "lists": [
+                        {
+                            "entries": [{
+                                "mediaId": 1, "status": "CURRENT", "score": 0, "progress": 3, "repeat": 0,
+                                "startedAt": { "year": null, "month": null, "day": null },
+                                "completedAt": { "year": null, "month": null, "day": null },
+                                "media": {
+                                    "id": 1, "title": { "romaji": "Test", "userPreferred": "Test" },
+                                    "episodes": 12, "duration": 24, "format": "TV", "relations": null
+                                }
+                            }]
+                        },
+                        {
+                            "entries": [{
+                                "mediaId": 1, "status": "CURRENT", "score": 0, "progress": 3, "repeat": 0,
+                                "startedAt": { "year": null, "month": null, "day": null },
+                                "completedAt": { "year": null, "month": null, "day": null },
+                                "media": {
+                                    "id": 1, "title": { "romaji": "Test", "userPreferred": "Test" },
+                                    "episodes": 12, "duration": 24, "format": "TV", "relations": null
+                                }
+                            }]
+                        }
+                    ]
+                }
+            }
+        }"#;
+
+        let second_chunk = r#"{
+            "data": {
+                "MediaListCollection": {
+                    "hasNextChunk": false,
+                    "lists": [{
+                        "entries": [{
+                            "mediaId": 2, "status": "COMPLETED", "score": 90, "progress": 1, "repeat": 0,
+                            "startedAt": { "year": 2020, "month": 1, "day": 1 },
+                            "completedAt": { "year": 2020, "month": 1, "day": 1 },
+                            "media": {
+                                "id": 2, "title": { "romaji": "Test Movie", "userPreferred": "Test Movie" },
+                                "episodes": 1, "duration": 90, "format": "MOVIE", "relations": null
+                            }
+                        }]
+                    }]
+                }
+            }
+        }"#;
+
+        let server = TestServer::spawn(vec![user.into(), first_chunk.into(), second_chunk.into()]);
+        let client = server.client();
+        let auth = Auth::retrieve_with(&client, AccessToken::encode("token")).unwrap();
+        let anilist = AniList::authenticated(auth).with_client(client);
+
+        let list = anilist.get_user_list().unwrap();
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].info.title.preferred, "Test");
+        assert_eq!(list[0].entry.watched_eps, 3);
+        assert_eq!(list[1].info.kind, SeriesKind::Movie);
+        assert_eq!(list[1].entry.id, 2);
+        assert_eq!(list[1].entry.score, Some(90));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].contains(r#""chunk":2"#));
+    }
+
+    #[test]
+    fn rejected_tokens_are_reported() {
+        let response = TestResponse::with_status(
+            400,
+            r#"{ "data": null, "errors": [{ "message": "Invalid token", "status": 400 }] }"#,
+        );
+
+        let server = TestServer::spawn(vec![response]);
+        let result = Auth::retrieve_with(&server.client(), AccessToken::encode("token"));
+
+        match result {
+            Err(err @ Error::InvalidToken) => assert!(err.is_http_code(401)),
+            other => panic!("expected invalid token error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn token_lifetime_is_read_from_token() {
+        // The payload is {"aud":"427","iat":1609459200.5,"exp":1640995200}
+        let token = AccessToken::encode(
+            "eyJ0eXAiOiJKV1QiLCJhbGciOiJSUzI1NiJ9.\
+             eyJhdWQiOiI0MjciLCJpYXQiOjE2MDk0NTkyMDAuNSwiZXhwIjoxNjQwOTk1MjAwfQ.\
+             c2lnbmF0dXJl",
+        );
+
+        let lifetime = TokenLifetime::from_token(&token).unwrap();
+
+        assert_eq!(lifetime.issued_at, 1_609_459_200);
+        assert_eq!(lifetime.expires_at, 1_640_995_200);
+        assert!(!lifetime.is_expired(1_640_995_199));
+        assert!(lifetime.is_expired(1_640_995_200));
+
+        assert_eq!(
+            TokenLifetime::from_token(&AccessToken::encode("token")),
+            None
+        );
+    }
+
+    #[test]
+    fn error_responses_are_reported() {
+        let response = TestResponse::with_status(
+            404,
+            r#"{ "data": null, "errors": [{ "message": "Not Found.", "status": 404 }] }"#,
+        );
+
+        let server = TestServer::spawn(vec![response]);
+        let anilist = AniList::unauthenticated().with_client(server.client());
+
+        match anilist.search_info_by_id(1) {
+            Err(Error::BadAniListResponse { code, message }) => {
+                assert_eq!(code, 404);
+                assert_eq!(message, "Not Found.");
+            }
+            other => panic!("expected bad response error, got {:?}", other),
+        }
+    }
+}
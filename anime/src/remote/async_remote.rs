@@ -0,0 +1,185 @@
+use super::{
+    AiringEpisode, Recommendation, RelationGraph, Remote, RemoteService, SearchOptions, SearchPage,
+    Season, SeriesEntry, SeriesID, SeriesInfo, UserListEntry,
+};
+use crate::err::Result;
+use crate::SeriesKind;
+use std::sync::Arc;
+use tokio::task;
+
+/// A remote service that can be used from async code.
+///
+/// Remote services send their requests on the calling thread, which would stall any other task running
+/// on the same thread. The async counterparts to the `RemoteService` methods provided here instead send their
+/// requests on tokio's blocking thread pool, so they can be awaited without blocking the runtime.
+///
+/// Cloning an `AsyncRemote` is cheap, as the underlying remote is shared between clones.
+#[derive(Clone, Debug)]
+pub struct AsyncRemote(Arc<Remote>);
+
+impl AsyncRemote {
+    #[inline(always)]
+    #[must_use]
+    pub fn new(remote: Remote) -> Self {
+        Self(Arc::new(remote))
+    }
+
+    /// Returns the underlying remote service.
+    ///
+    /// This can be used for functionality that doesn't need to make requests, like parsing scores.
+    #[inline(always)]
+    #[must_use]
+    pub fn get(&self) -> &Remote {
+        &self.0
+    }
+
+    /// Search for an anime's information by title and return all of the matches.
+    pub async fn search_info_by_name<S>(&self, name: S) -> Result<Vec<SeriesInfo>>
+    where
+        S: Into<String>,
+    {
+        let name = name.into();
+        self.run(move |remote| remote.search_info_by_name(&name))
+            .await
+    }
+
+    /// Search for an anime's information by title, narrowed down and paged through with `options`.
+    pub async fn search_info<S>(&self, name: S, options: SearchOptions) -> Result<SearchPage>
+    where
+        S: Into<String>,
+    {
+        let name = name.into();
+        self.run(move |remote| remote.search_info(&name, &options))
+            .await
+    }
+
+    /// Get an anime's information by its ID.
+    pub async fn search_info_by_id(&self, id: SeriesID) -> Result<SeriesInfo> {
+        self.run(move |remote| remote.search_info_by_id(id)).await
+    }
+
+    /// Get the information of several anime by their IDs.
+    pub async fn search_info_by_ids(&self, ids: Vec<SeriesID>) -> Result<Vec<SeriesInfo>> {
+        self.run(move |remote| remote.search_info_by_ids(&ids))
+            .await
+    }
+
+    /// Get every anime in the same franchise as the anime with the specified `id` by walking its relations.
+    pub async fn get_relation_graph(&self, id: SeriesID) -> Result<RelationGraph> {
+        self.run(move |remote| remote.get_relation_graph(id)).await
+    }
+
+    /// Get the anime recommended to people who liked the anime with the specified `id`, sorted by their rating.
+    pub async fn get_recommendations(&self, id: SeriesID) -> Result<Vec<Recommendation>> {
+        self.run(move |remote| remote.get_recommendations(id)).await
+    }
+
+    /// Get every anime that started airing in the specified `season` and `year`, sorted by their popularity.
+    pub async fn get_season(
+        &self,
+        season: Season,
+        year: u32,
+        kind: Option<SeriesKind>,
+    ) -> Result<Vec<SeriesInfo>> {
+        self.run(move |remote| remote.get_season(season, year, kind))
+            .await
+    }
+
+    /// Retrieve the anime list entry from the currently authenticated user.
+    pub async fn get_list_entry(&self, id: SeriesID) -> Result<Option<SeriesEntry>> {
+        self.run(move |remote| remote.get_list_entry(id)).await
+    }
+
+    /// Retrieve the anime list entries with the specified `ids` from the currently authenticated user.
+    pub async fn get_list_entries(&self, ids: Vec<SeriesID>) -> Result<Vec<SeriesEntry>> {
+        self.run(move |remote| remote.get_list_entries(&ids)).await
+    }
+
+    /// Retrieve every entry on the currently authenticated user's anime list, along with the information of each series.
+    pub async fn get_user_list(&self) -> Result<Vec<UserListEntry>> {
+        self.run(RemoteService::get_user_list).await
+    }
+
+    /// Retrieve every episode of the series with the specified `ids` that airs between `from` and `to`,
+    /// in seconds since the Unix epoch.
+    pub async fn get_airing_schedule(
+        &self,
+        ids: Vec<SeriesID>,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<AiringEpisode>> {
+        self.run(move |remote| remote.get_airing_schedule(&ids, from, to))
+            .await
+    }
+
+    /// Upload `entry` to the currently authenticated user's anime list.
+    ///
+    /// Returns the time the remote service recorded the update at, if it reports it.
+    pub async fn update_list_entry(&self, entry: SeriesEntry) -> Result<Option<i64>> {
+        self.run(move |remote| remote.update_list_entry(&entry))
+            .await
+    }
+
+    /// Remove the anime with the specified `id` from the currently authenticated user's list.
+    pub async fn delete_list_entry(&self, id: SeriesID) -> Result<()> {
+        self.run(move |remote| remote.delete_list_entry(id)).await
+    }
+
+    /// Mark or unmark the anime with the specified `id` as one of the currently authenticated user's favourites.
+    pub async fn set_favourite(&self, id: SeriesID, favourite: bool) -> Result<()> {
+        self.run(move |remote| remote.set_favourite(id, favourite))
+            .await
+    }
+
+    async fn run<F, T>(&self, func: F) -> Result<T>
+    where
+        F: FnOnce(&Remote) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let remote = Arc::clone(&self.0);
+        task::spawn_blocking(move || func(&remote)).await?
+    }
+}
+
+impl From<Remote> for AsyncRemote {
+    fn from(remote: Remote) -> Self {
+        Self::new(remote)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::anilist::AniList;
+    use crate::remote::test_server::TestServer;
+
+    #[test]
+    fn requests_can_be_awaited() {
+        let server = TestServer::spawn(vec![r#"{
+            "data": {
+                "Media": {
+                    "id": 1,
+                    "title": { "romaji": "Test", "userPreferred": "Test" },
+                    "episodes": 12,
+                    "duration": 24,
+                    "format": "TV",
+                    "relations": null
+                }
+            }
+        }"#
+        .into()]);
+
+        let anilist = AniList::unauthenticated().with_client(server.client());
+        let remote = AsyncRemote::new(anilist.into());
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let info = runtime.block_on(remote.search_info_by_id(1)).unwrap();
+
+        assert_eq!(info.id, 1);
+        assert_eq!(info.episodes, 12);
+        assert!(!remote.get().is_offline());
+    }
+}
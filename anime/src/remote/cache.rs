@@ -0,0 +1,619 @@
+//! Caching of the series information returned by a remote service.
+
+use super::{
+    AiringEpisode, Recommendation, Remote, RemoteService, ScoreParser, SearchOptions, SearchPage,
+    Season, SeriesEntry, SeriesID, SeriesInfo, UserListEntry,
+};
+use crate::err::{Error, Result};
+use crate::SeriesKind;
+use serde_derive::{Deserialize, Serialize};
+use serde_json as json;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// The minimum amount of time between each automatic save of a `Cached` remote's cache.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Every series info, search result, airing schedule, and `MyAnimeList` ID seen by a `Cached` remote.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct InfoCache {
+    series: HashMap<SeriesID, SeriesInfo>,
+    searches: HashMap<String, Vec<SeriesID>>,
+    #[serde(default)]
+    airing: HashMap<SeriesID, Vec<AiringEpisode>>,
+    /// The `MyAnimeList` ID of each series, keyed by its ID.
+    #[serde(default)]
+    mal_ids: HashMap<SeriesID, SeriesID>,
+}
+
+impl InfoCache {
+    /// Load a cache from the JSON file at `path`.
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(path)?;
+        json::from_reader(BufReader::new(file)).map_err(Into::into)
+    }
+
+    /// Save the cache as JSON to the file at `path`.
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::create(path)?;
+        json::to_writer(BufWriter::new(file), self).map_err(Into::into)
+    }
+
+    /// Returns the cached info of the series with the specified `id`.
+    #[must_use]
+    pub fn get(&self, id: SeriesID) -> Option<&SeriesInfo> {
+        self.series.get(&id)
+    }
+
+    /// Returns the cached results of searching for `name`.
+    ///
+    /// When `name` hasn't been searched for before, every cached series with a title containing `name` is returned instead.
+    #[must_use]
+    pub fn search(&self, name: &str) -> Vec<SeriesInfo> {
+        let name = Self::normalize(name);
+
+        if let Some(ids) = self.searches.get(&name) {
+            return ids.iter().filter_map(|&id| self.get(id)).cloned().collect();
+        }
+
+        self.series
+            .values()
+            .filter(|info| {
+                let title = &info.title;
+
+                Self::normalize(&title.romaji).contains(&name)
+                    || Self::normalize(&title.preferred).contains(&name)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Add `info` to the cache, replacing any existing info for the same series.
+    pub fn insert(&mut self, info: SeriesInfo) {
+        self.series.insert(info.id, info);
+    }
+
+    /// Add the `results` of searching for `name` to the cache.
+    pub fn insert_search(&mut self, name: &str, results: &[SeriesInfo]) {
+        let ids = results.iter().map(|info| info.id).collect();
+        self.searches.insert(Self::normalize(name), ids);
+
+        for info in results {
+            self.insert(info.clone());
+        }
+    }
+
+    /// Returns the cached episodes of the series with the specified `ids` that air between `from` and `to`, sorted by the time they air.
+    #[must_use]
+    pub fn airing_schedule(&self, ids: &[SeriesID], from: i64, to: i64) -> Vec<AiringEpisode> {
+        let mut episodes = ids
+            .iter()
+            .filter_map(|id| self.airing.get(id))
+            .flatten()
+            .filter(|episode| episode.airs_at > from && episode.airs_at < to)
+            .copied()
+            .collect::<Vec<_>>();
+
+        episodes.sort_by_key(|episode| episode.airs_at);
+        episodes
+    }
+
+    /// Replace the cached episodes of the series with the specified `ids` that air between `from` and `to` with `episodes`.
+    ///
+    /// Episodes that aired before `from` are removed from the cache entirely.
+    pub fn insert_airing_schedule(
+        &mut self,
+        ids: &[SeriesID],
+        from: i64,
+        to: i64,
+        episodes: &[AiringEpisode],
+    ) {
+        for id in ids {
+            let schedule = self.airing.entry(*id).or_default();
+            schedule.retain(|episode| episode.airs_at >= to);
+            schedule.extend(episodes.iter().filter(|episode| episode.id == *id));
+        }
+
+        self.airing.retain(|_, schedule| {
+            schedule.retain(|episode| episode.airs_at > from);
+            !schedule.is_empty()
+        });
+    }
+
+    /// Returns the cached `MyAnimeList` IDs of the series with the specified `ids`, keyed by their ID.
+    #[must_use]
+    pub fn mal_ids(&self, ids: &[SeriesID]) -> HashMap<SeriesID, SeriesID> {
+        ids.iter()
+            .filter_map(|id| self.mal_ids.get(id).map(|&mal_id| (*id, mal_id)))
+            .collect()
+    }
+
+    /// Returns the cached IDs of the series with the specified `mal_ids`, keyed by their `MyAnimeList` ID.
+    #[must_use]
+    pub fn find_by_mal_ids(&self, mal_ids: &[SeriesID]) -> HashMap<SeriesID, SeriesID> {
+        self.mal_ids
+            .iter()
+            .filter(|(_, mal_id)| mal_ids.contains(mal_id))
+            .map(|(&id, &mal_id)| (mal_id, id))
+            .collect()
+    }
+
+    /// Add the `MyAnimeList` IDs in `mal_ids`, keyed by the ID of their series, to the cache.
+    pub fn insert_mal_ids<I>(&mut self, mal_ids: I)
+    where
+        I: IntoIterator<Item = (SeriesID, SeriesID)>,
+    {
+        self.mal_ids.extend(mal_ids);
+    }
+
+    fn normalize(name: &str) -> String {
+        name.trim().to_lowercase()
+    }
+}
+
+/// A remote service that caches the series info returned by another remote.
+///
+/// Series info is answered from the cache when the inner remote is offline, or when it can't connect to its service.
+/// This allows series that have been seen before to be searched for without an internet connection.
+///
+/// Every other request is passed directly to the inner remote.
+#[derive(Debug)]
+pub struct Cached {
+    inner: Box<Remote>,
+    cache: Mutex<InfoCache>,
+    path: Option<PathBuf>,
+    changed: AtomicBool,
+    last_save: Mutex<Instant>,
+}
+
+impl Cached {
+    /// Create a new `Cached` remote that passes requests to `inner` and starts with the specified `cache`.
+    ///
+    /// The cache is only kept in memory unless `save_to` is used.
+    pub fn new<R>(inner: R, cache: InfoCache) -> Self
+    where
+        R: Into<Remote>,
+    {
+        Self {
+            inner: Box::new(inner.into()),
+            cache: Mutex::new(cache),
+            path: None,
+            changed: AtomicBool::new(false),
+            last_save: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Save the cache to the file at `path` as it changes.
+    ///
+    /// To avoid rewriting the file on every request, changes are saved at most once a minute, as well as when the
+    /// remote is dropped. The cache can be loaded from the same file with `InfoCache::load`.
+    #[must_use]
+    pub fn save_to<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Returns the remote service that requests are passed to.
+    #[inline(always)]
+    #[must_use]
+    pub fn inner(&self) -> &Remote {
+        &self.inner
+    }
+
+    /// Save the cache to the file set with `save_to` if it has changed since it was last saved.
+    ///
+    /// Automatic saves ignore errors and try again later, so this should be used to find out if the cache can
+    /// actually be saved.
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if !self.changed.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        // Only serializing the cache needs the lock, so it's released before the file is written to avoid holding
+        // up requests. A poisoned lock still holds a usable cache, so it's saved all the same
+        let serialized = {
+            let cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+            json::to_vec(&*cache)
+        };
+
+        let result = serialized
+            .map_err(Error::from)
+            .and_then(|json| fs::write(path, json).map_err(Into::into));
+
+        if result.is_err() {
+            self.changed.store(true, Ordering::Release);
+        }
+
+        result
+    }
+
+    fn update_cache<F>(&self, func: F)
+    where
+        F: FnOnce(&mut InfoCache),
+    {
+        func(&mut self.cache.lock().unwrap());
+        self.changed.store(true, Ordering::Release);
+
+        {
+            let mut last_save = self.last_save.lock().unwrap();
+
+            if last_save.elapsed() < SAVE_INTERVAL {
+                return;
+            }
+
+            *last_save = Instant::now();
+        }
+
+        // The cache is still marked as changed when this fails, so it will be tried again with the next save
+        self.save().ok();
+    }
+}
+
+impl Drop for Cached {
+    fn drop(&mut self) {
+        self.save().ok();
+    }
+}
+
+impl RemoteService for Cached {
+    fn search_info_by_name(&self, name: &str) -> Result<Vec<SeriesInfo>> {
+        let result = self.inner.search_info_by_name(name);
+
+        if should_use_cache(&result) {
+            let cached = self.cache.lock().unwrap().search(name);
+
+            if !cached.is_empty() {
+                return Ok(cached);
+            }
+        }
+
+        let results = result?;
+        self.update_cache(|cache| cache.insert_search(name, &results));
+
+        Ok(results)
+    }
+
+    fn search_info(&self, name: &str, options: &SearchOptions) -> Result<SearchPage> {
+        let result = self.inner.search_info(name, options);
+
+        // Everything cached is treated as being on the first page
+        if should_use_cache(&result) && options.page <= 1 {
+            let cached = self
+                .cache
+                .lock()
+                .unwrap()
+                .search(name)
+                .into_iter()
+                .filter(|info| options.matches(info))
+                .collect::<Vec<_>>();
+
+            if !cached.is_empty() {
+                return Ok(SearchPage {
+                    results: cached,
+                    has_next_page: false,
+                });
+            }
+        }
+
+        let page = result?;
+
+        self.update_cache(|cache| {
+            for info in &page.results {
+                cache.insert(info.clone());
+            }
+        });
+
+        Ok(page)
+    }
+
+    fn search_info_by_id(&self, id: SeriesID) -> Result<SeriesInfo> {
+        let result = self.inner.search_info_by_id(id);
+
+        if should_use_cache(&result) {
+            if let Some(info) = self.cache.lock().unwrap().get(id) {
+                return Ok(info.clone());
+            }
+        }
+
+        let info = result?;
+        self.update_cache(|cache| cache.insert(info.clone()));
+
+        Ok(info)
+    }
+
+    fn search_info_by_ids(&self, ids: &[SeriesID]) -> Result<Vec<SeriesInfo>> {
+        let result = self.inner.search_info_by_ids(ids);
+
+        if should_use_cache(&result) {
+            let cache = self.cache.lock().unwrap();
+            let cached = ids
+                .iter()
+                .filter_map(|&id| cache.get(id))
+                .cloned()
+                .collect();
+
+            return Ok(cached);
+        }
+
+        let results = result?;
+
+        self.update_cache(|cache| {
+            for info in &results {
+                cache.insert(info.clone());
+            }
+        });
+
+        Ok(results)
+    }
+
+    fn get_recommendations(&self, id: SeriesID) -> Result<Vec<Recommendation>> {
+        let recommendations = self.inner.get_recommendations(id)?;
+
+        self.update_cache(|cache| {
+            for recommendation in &recommendations {
+                cache.insert(recommendation.info.clone());
+            }
+        });
+
+        Ok(recommendations)
+    }
+
+    fn get_season(
+        &self,
+        season: Season,
+        year: u32,
+        kind: Option<SeriesKind>,
+    ) -> Result<Vec<SeriesInfo>> {
+        let infos = self.inner.get_season(season, year, kind)?;
+
+        self.update_cache(|cache| {
+            for info in &infos {
+                cache.insert(info.clone());
+            }
+        });
+
+        Ok(infos)
+    }
+
+    fn get_list_entry(&self, id: SeriesID) -> Result<Option<SeriesEntry>> {
+        self.inner.get_list_entry(id)
+    }
+
+    fn get_list_entries(&self, ids: &[SeriesID]) -> Result<Vec<SeriesEntry>> {
+        self.inner.get_list_entries(ids)
+    }
+
+    fn get_user_list(&self) -> Result<Vec<UserListEntry>> {
+        let list = self.inner.get_user_list()?;
+
+        self.update_cache(|cache| {
+            for list_entry in &list {
+                cache.insert(list_entry.info.clone());
+            }
+        });
+
+        Ok(list)
+    }
+
+    fn get_mal_ids(&self, ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        let result = self.inner.get_mal_ids(ids);
+
+        if should_use_cache(&result) {
+            return Ok(self.cache.lock().unwrap().mal_ids(ids));
+        }
+
+        let mal_ids = result?;
+        self.update_cache(|cache| {
+            cache.insert_mal_ids(mal_ids.iter().map(|(&id, &mal_id)| (id, mal_id)))
+        });
+
+        Ok(mal_ids)
+    }
+
+    fn find_by_mal_ids(&self, mal_ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        let result = self.inner.find_by_mal_ids(mal_ids);
+
+        if should_use_cache(&result) {
+            return Ok(self.cache.lock().unwrap().find_by_mal_ids(mal_ids));
+        }
+
+        let ids = result?;
+        self.update_cache(|cache| {
+            cache.insert_mal_ids(ids.iter().map(|(&mal_id, &id)| (id, mal_id)))
+        });
+
+        Ok(ids)
+    }
+
+    fn get_airing_schedule(
+        &self,
+        ids: &[SeriesID],
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<AiringEpisode>> {
+        let result = self.inner.get_airing_schedule(ids, from, to);
+
+        if should_use_cache(&result) {
+            return Ok(self.cache.lock().unwrap().airing_schedule(ids, from, to));
+        }
+
+        let episodes = result?;
+        self.update_cache(|cache| cache.insert_airing_schedule(ids, from, to, &episodes));
+
+        Ok(episodes)
+    }
+
+    fn update_list_entry(&self, entry: &SeriesEntry) -> Result<Option<i64>> {
+        self.inner.update_list_entry(entry)
+    }
+
+    fn delete_list_entry(&self, id: SeriesID) -> Result<()> {
+        self.inner.delete_list_entry(id)
+    }
+
+    fn set_favourite(&self, id: SeriesID, favourite: bool) -> Result<()> {
+        self.inner.set_favourite(id, favourite)
+    }
+
+    fn is_offline(&self) -> bool {
+        self.inner.is_offline()
+    }
+}
+
+/// Returns true if series info should be retrieved from the cache instead of the inner remote after `result`.
+fn should_use_cache<T>(result: &Result<T>) -> bool {
+    matches!(
+        result,
+        Err(Error::NeedExistingSeriesData | Error::Http(_) | Error::Transport(_))
+    )
+}
+
+impl ScoreParser for Cached {
+    fn parse_score(&self, score: &str) -> Option<u8> {
+        self.inner.parse_score(score)
+    }
+
+    fn score_to_str(&self, score: u8) -> Cow<str> {
+        self.inner.score_to_str(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::anilist::AniList;
+    use crate::remote::test_server::TestServer;
+    use crate::SeriesKind;
+
+    #[test]
+    fn cached_info_is_used_offline() {
+        let server = TestServer::spawn(vec![r#"{
+            "data": {
+                "Page": {
+                    "media": [{
+                        "id": 1,
+                        "title": { "romaji": "Kimi no Na wa.", "userPreferred": "Your Name." },
+                        "episodes": 1,
+                        "duration": 107,
+                        "format": "MOVIE",
+                        "relations": null
+                    }]
+                }
+            }
+        }"#
+        .into()]);
+
+        let anilist = AniList::unauthenticated().with_client(server.client());
+        let online = Cached::new(anilist, InfoCache::default());
+
+        online.search_info_by_name("your name").unwrap();
+
+        let cache = online.cache.lock().unwrap().clone();
+        let offline = Cached::new(Remote::offline(), cache);
+
+        let info = offline.search_info_by_id(1).unwrap();
+        assert_eq!(info.kind, SeriesKind::Movie);
+
+        assert_eq!(offline.search_info_by_name("Your Name").unwrap().len(), 1);
+        assert_eq!(offline.search_info_by_name("kimi no").unwrap().len(), 1);
+
+        let movies = SearchOptions::new().kind(SeriesKind::Movie);
+        let results = offline.search_info("your name", &movies).unwrap().results;
+        assert_eq!(results.len(), 1);
+
+        // The cached series has no year, so it can't be confirmed to match a year filter
+        assert!(offline
+            .search_info("your name", &movies.season_year(2016))
+            .is_err());
+
+        match offline.search_info_by_id(2) {
+            Err(Error::NeedExistingSeriesData) => (),
+            other => panic!("expected missing data error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cached_airing_schedule_is_used_offline() {
+        let episode = |id, airs_at| AiringEpisode {
+            id,
+            episode: 1,
+            airs_at,
+        };
+
+        let mut cache = InfoCache::default();
+        cache.insert_airing_schedule(&[1, 2], 100, 200, &[episode(1, 150), episode(2, 190)]);
+        cache.insert_airing_schedule(&[1], 160, 300, &[episode(1, 250)]);
+
+        let offline = Cached::new(Remote::offline(), cache);
+        let schedule = offline.get_airing_schedule(&[1, 2], 160, 300).unwrap();
+
+        let times = schedule.iter().map(|ep| ep.airs_at).collect::<Vec<_>>();
+        assert_eq!(times, [190, 250]);
+    }
+
+    #[test]
+    fn cached_mal_ids_are_used_offline() {
+        let mut cache = InfoCache::default();
+        cache.insert_mal_ids(vec![(1, 101), (2, 102)]);
+
+        let offline = Cached::new(Remote::offline(), cache);
+
+        let mal_ids = offline.get_mal_ids(&[1, 3]).unwrap();
+        assert_eq!(mal_ids.len(), 1);
+        assert_eq!(mal_ids.get(&1), Some(&101));
+
+        let ids = offline.find_by_mal_ids(&[102, 103]).unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids.get(&102), Some(&2));
+    }
+
+    #[test]
+    fn changes_are_saved_when_dropped() {
+        let path =
+            std::env::temp_dir().join(format!("anime-info-cache-{}.json", std::process::id()));
+
+        fs::remove_file(&path).ok();
+
+        let cached = Cached::new(Remote::offline(), InfoCache::default()).save_to(&path);
+
+        cached.update_cache(|cache| {
+            let episode = AiringEpisode {
+                id: 1,
+                episode: 1,
+                airs_at: 50,
+            };
+
+            cache.insert_airing_schedule(&[1], 0, 100, &[episode]);
+        });
+
+        // Changes are only saved periodically
+        assert!(!path.exists());
+
+        drop(cached);
+
+        let cache = InfoCache::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(cache.airing_schedule(&[1], 0, 100).len(), 1);
+    }
+}
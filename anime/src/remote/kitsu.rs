@@ -0,0 +1,1183 @@
+use super::transport::{Client, Method, Request};
+use super::{
+    parse_timestamp, AccessToken, AiringStatus, RemoteService, ScoreParser, Season, Sequel,
+    SeriesDate, SeriesEntry, SeriesID, SeriesInfo, SeriesKind, SeriesTitle, Status, TokenGrant,
+    UserListEntry,
+};
+use crate::err::{Error, Result};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{self as json, json};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::result;
+
+/// The URL to the API endpoint.
+pub const API_URL: &str = "https://kitsu.io/api/edge";
+
+/// The URL to the `OAuth2` endpoint.
+pub const OAUTH_URL: &str = "https://kitsu.io/api/oauth";
+
+/// The media type used by the `JSON:API` specification.
+const JSON_API_TYPE: &str = "application/vnd.api+json";
+
+/// The anime fields to request when retrieving series info.
+const ANIME_FIELDS: &str = "canonicalTitle,titles,abbreviatedTitles,episodeCount,episodeLength,subtype,startDate,status,averageRating,nsfw";
+
+/// The library entry fields to request when retrieving a list entry.
+const ENTRY_FIELDS: &str =
+    "status,progress,reconsuming,reconsumeCount,ratingTwenty,startedAt,finishedAt,updatedAt";
+
+/// The largest page size Kitsu allows for anime and mappings.
+const MAX_PAGE_SIZE: usize = 20;
+
+/// The largest page size Kitsu allows for library entries.
+const MAX_LIBRARY_PAGE_SIZE: usize = 500;
+
+/// The external site Kitsu maps anime from `MyAnimeList` with.
+const MAL_ANIME_SITE: &str = "myanimelist/anime";
+
+/// A connection to the Kitsu API.
+#[derive(Debug)]
+pub struct Kitsu {
+    client: Client,
+    auth: Option<Auth>,
+}
+
+impl Kitsu {
+    /// Create a new `Kitsu` connection without any authentication.
+    ///
+    /// In this mode, you can only search for series info by name and by ID.
+    /// Trying to make any other request will return a `NeedAuthentication` error.
+    #[must_use]
+    pub fn unauthenticated() -> Self {
+        Self {
+            client: Client::default(),
+            auth: None,
+        }
+    }
+
+    /// Create a new `Kitsu` connection with authentication.
+    ///
+    /// This mode will allow you to update the specified user's library.
+    #[must_use]
+    pub fn authenticated(auth: Auth) -> Self {
+        Self {
+            auth: Some(auth),
+            ..Self::unauthenticated()
+        }
+    }
+
+    /// Send all requests through the specified `client`.
+    #[must_use]
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Returns the current user's authentication, if there is one.
+    #[inline(always)]
+    #[must_use]
+    pub fn auth(&self) -> Option<&Auth> {
+        self.auth.as_ref()
+    }
+
+    fn need_auth(&self) -> Result<&Auth> {
+        self.auth.as_ref().ok_or(Error::NeedAuthentication)
+    }
+
+    fn request(&self, method: Method, path: &str) -> Result<Request> {
+        let url = format!("{}/{}", self.client.base_url(API_URL), path);
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("Accept", JSON_API_TYPE);
+
+        if let Some(auth) = &self.auth {
+            request = request.bearer_auth(&auth.token.decode()?);
+        }
+
+        Ok(request)
+    }
+
+    /// Find the library entry for the anime with the specified `id`.
+    fn find_library_entry(&self, id: SeriesID) -> Result<Option<Resource<LibraryEntry>>> {
+        let auth = self.need_auth()?;
+
+        let request = self
+            .request(Method::Get, "library-entries")?
+            .param("filter[userId]", auth.user.id)
+            .param("filter[animeId]", id)
+            .param("fields[libraryEntries]", ENTRY_FIELDS);
+
+        let response: Document<Vec<Resource<LibraryEntry>>> =
+            json::from_value(send_request(&self.client, request)?)?;
+
+        Ok(response.data.into_iter().next())
+    }
+}
+
+impl RemoteService for Kitsu {
+    fn search_info_by_name(&self, name: &str) -> Result<Vec<SeriesInfo>> {
+        let request = self
+            .request(Method::Get, "anime")?
+            .param("filter[text]", name)
+            .param("page[limit]", 20)
+            .param("fields[anime]", ANIME_FIELDS);
+
+        let response: Document<Vec<Resource<Anime>>> =
+            json::from_value(send_request(&self.client, request)?)?;
+
+        let entries = response
+            .data
+            .into_iter()
+            .filter_map(|anime| anime.into_series_info(&[]).ok())
+            .collect();
+
+        Ok(entries)
+    }
+
+    fn search_info_by_id(&self, id: SeriesID) -> Result<SeriesInfo> {
+        let request = self
+            .request(Method::Get, &format!("anime/{}", id))?
+            .param("include", "mediaRelationships.destination,categories");
+
+        let response: Document<Resource<Anime>> =
+            json::from_value(send_request(&self.client, request)?)?;
+
+        response
+            .data
+            .into_series_info(&response.included)
+            .map_err(|_| Error::NotAnAnime)
+    }
+
+    fn get_list_entry(&self, id: SeriesID) -> Result<Option<SeriesEntry>> {
+        let entry = self
+            .find_library_entry(id)?
+            .map(|entry| entry.attributes.into_series_entry(id));
+
+        Ok(entry)
+    }
+
+    fn get_user_list(&self) -> Result<Vec<UserListEntry>> {
+        let auth = self.need_auth()?;
+        let entry_fields = format!("{},anime", ENTRY_FIELDS);
+        let mut results = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let request = self
+                .request(Method::Get, "library-entries")?
+                .param("filter[userId]", auth.user.id)
+                .param("filter[kind]", "anime")
+                .param("include", "anime")
+                .param("fields[libraryEntries]", &entry_fields)
+                .param("fields[anime]", ANIME_FIELDS)
+                .param("page[limit]", MAX_LIBRARY_PAGE_SIZE)
+                .param("page[offset]", offset);
+
+            let page: Document<Vec<json::Value>> =
+                json::from_value(send_request(&self.client, request)?)?;
+
+            let has_next_page = page.links.next.is_some() && !page.data.is_empty();
+            offset += page.data.len();
+
+            let mut series = page
+                .included
+                .into_iter()
+                .filter(|value| value["type"] == "anime")
+                .filter_map(|value| json::from_value::<Resource<Anime>>(value).ok())
+                .filter_map(|anime| anime.into_series_info(&[]).ok())
+                .map(|info| (info.id, info))
+                .collect::<HashMap<_, _>>();
+
+            results.extend(page.data.into_iter().filter_map(|value| {
+                let anime: Identifier =
+                    json::from_value(value["relationships"]["anime"]["data"].clone()).ok()?;
+                let entry: Resource<LibraryEntry> = json::from_value(value).ok()?;
+                let info = series.remove(&anime.id)?;
+
+                Some(UserListEntry {
+                    entry: entry.attributes.into_series_entry(info.id),
+                    info,
+                })
+            }));
+
+            if !has_next_page {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn get_mal_ids(&self, ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        let mut results = HashMap::with_capacity(ids.len());
+
+        for chunk in ids.chunks(MAX_PAGE_SIZE) {
+            let request = self
+                .request(Method::Get, "anime")?
+                .param("filter[id]", join_ids(chunk))
+                .param("include", "mappings")
+                .param("fields[anime]", "mappings")
+                .param("fields[mappings]", "externalSite,externalId")
+                .param("page[limit]", MAX_PAGE_SIZE);
+
+            let response: Document<Vec<json::Value>> =
+                json::from_value(send_request(&self.client, request)?)?;
+
+            let mal_ids = response
+                .included
+                .into_iter()
+                .filter(|value| value["type"] == "mappings")
+                .filter_map(|value| json::from_value::<Resource<Mapping>>(value).ok())
+                .filter_map(|mapping| Some((mapping.id, mapping.attributes.mal_id()?)))
+                .collect::<HashMap<_, _>>();
+
+            results.extend(response.data.iter().filter_map(|anime| {
+                let id = anime["id"].as_str()?.parse().ok()?;
+
+                let mal_id = anime["relationships"]["mappings"]["data"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(|mapping| mapping["id"].as_str()?.parse::<u32>().ok())
+                    .find_map(|mapping| mal_ids.get(&mapping).copied())?;
+
+                Some((id, mal_id))
+            }));
+        }
+
+        Ok(results)
+    }
+
+    fn find_by_mal_ids(&self, mal_ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        let mut results = HashMap::with_capacity(mal_ids.len());
+
+        for chunk in mal_ids.chunks(MAX_PAGE_SIZE) {
+            let request = self
+                .request(Method::Get, "mappings")?
+                .param("filter[externalSite]", MAL_ANIME_SITE)
+                .param("filter[externalId]", join_ids(chunk))
+                .param("include", "item")
+                .param("fields[anime]", "subtype")
+                .param("page[limit]", MAX_PAGE_SIZE);
+
+            let response: Document<Vec<json::Value>> =
+                json::from_value(send_request(&self.client, request)?)?;
+
+            results.extend(response.data.into_iter().filter_map(|value| {
+                let item: Identifier =
+                    json::from_value(value["relationships"]["item"]["data"].clone()).ok()?;
+
+                if item.kind != "anime" {
+                    return None;
+                }
+
+                let mapping: Resource<Mapping> = json::from_value(value).ok()?;
+                Some((mapping.attributes.mal_id()?, item.id))
+            }));
+        }
+
+        Ok(results)
+    }
+
+    fn update_list_entry(&self, entry: &SeriesEntry) -> Result<Option<i64>> {
+        let auth = self.need_auth()?;
+        let attributes = LibraryEntryUpdate::from(entry);
+
+        // Kitsu identifies library entries by their own ID, so we need to find it first
+        // to determine whether we need to update an existing entry or create a new one
+        let request = if let Some(existing) = self.find_library_entry(entry.id)? {
+            let body = json!({
+                "data": {
+                    "type": "libraryEntries",
+                    "id": existing.id.to_string(),
+                    "attributes": attributes,
+                }
+            });
+
+            self.request(Method::Patch, &format!("library-entries/{}", existing.id))?
+                .json(&body)?
+        } else {
+            let body = json!({
+                "data": {
+                    "type": "libraryEntries",
+                    "attributes": attributes,
+                    "relationships": {
+                        "user": {
+                            "data": { "type": "users", "id": auth.user.id.to_string() }
+                        },
+                        "anime": {
+                            "data": { "type": "anime", "id": entry.id.to_string() }
+                        },
+                    },
+                }
+            });
+
+            self.request(Method::Post, "library-entries")?.json(&body)?
+        };
+
+        let updated = send_request(&self.client, request.header("Content-Type", JSON_API_TYPE))?;
+
+        Ok(updated["data"]["attributes"]["updatedAt"]
+            .as_str()
+            .and_then(parse_timestamp))
+    }
+
+    fn delete_list_entry(&self, id: SeriesID) -> Result<()> {
+        // Kitsu deletes library entries by their own ID, so we need to find it first
+        let existing = match self.find_library_entry(id)? {
+            Some(existing) => existing,
+            None => return Ok(()),
+        };
+
+        let request = self.request(Method::Delete, &format!("library-entries/{}", existing.id))?;
+        send_request(&self.client, request)?;
+
+        Ok(())
+    }
+}
+
+impl ScoreParser for Kitsu {
+    /// Parse a score between 1.0 - 10.0 in increments of 0.5, which is how Kitsu displays
+    /// advanced ratings.
+    #[allow(clippy::cast_possible_truncation)]
+    fn parse_score(&self, score: &str) -> Option<u8> {
+        let score = score.parse::<f32>().ok()?;
+
+        // A score of 0 means the entry hasn't been rated
+        if !(1.0..=10.0).contains(&score) || (score * 2.0).fract() != 0.0 {
+            return None;
+        }
+
+        Some((score * 10.0) as u8)
+    }
+
+    fn score_to_str(&self, score: u8) -> Cow<str> {
+        format!("{:.1}", f32::from(score) / 10.0).into()
+    }
+}
+
+/// An authenticated user.
+#[derive(Debug)]
+pub struct Auth {
+    /// The Kitsu user's account information.
+    pub user: User,
+    token: AccessToken,
+}
+
+impl Auth {
+    #[inline(always)]
+    #[must_use]
+    pub fn new(user: User, token: AccessToken) -> Self {
+        Self { user, token }
+    }
+
+    /// Retrieve the current authorization from Kitsu using the specified `token`.
+    #[inline(always)]
+    pub fn retrieve(token: AccessToken) -> Result<Self> {
+        Self::retrieve_with(&Client::default(), token)
+    }
+
+    /// Retrieve the current authorization from Kitsu using the specified `token`, sending the request through `client`.
+    pub fn retrieve_with(client: &Client, token: AccessToken) -> Result<Self> {
+        let url = format!("{}/users", client.base_url(API_URL));
+
+        let request = client
+            .request(Method::Get, url)
+            .header("Accept", JSON_API_TYPE)
+            .bearer_auth(&token.decode()?)
+            .param("filter[self]", "true")
+            .param("fields[users]", "name");
+
+        let response: Document<Vec<Resource<UserAttributes>>> =
+            json::from_value(send_request(client, request)?)?;
+
+        let user = response
+            .data
+            .into_iter()
+            .next()
+            .ok_or(Error::NeedAuthentication)?;
+
+        let user = User {
+            id: user.id,
+            name: user.attributes.name,
+        };
+
+        Ok(Self::new(user, token))
+    }
+
+    /// Login to Kitsu with the specified `username` and `password` and return an access token.
+    ///
+    /// `username` can either be the user's account name or email address.
+    pub fn login(username: &str, password: &str) -> Result<TokenGrant> {
+        let params = [
+            ("grant_type", "password"),
+            ("username", username),
+            ("password", password),
+        ];
+
+        request_token(&Client::default(), &params)
+    }
+
+    /// Get a new access token with the `refresh_token` that was granted along with the last one.
+    ///
+    /// This avoids having to ask for the user's password again once their access token expires.
+    #[inline(always)]
+    pub fn refresh(refresh_token: &AccessToken) -> Result<TokenGrant> {
+        Self::refresh_with(&Client::default(), refresh_token)
+    }
+
+    /// Get a new access token with the `refresh_token` that was granted along with the last one, sending the request through `client`.
+    pub fn refresh_with(client: &Client, refresh_token: &AccessToken) -> Result<TokenGrant> {
+        let refresh_token = refresh_token.decode()?;
+
+        let params = [
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ];
+
+        request_token(client, &params)
+    }
+}
+
+fn request_token(client: &Client, params: &[(&str, &str)]) -> Result<TokenGrant> {
+    let request = client
+        .request(
+            Method::Post,
+            format!("{}/token", client.base_url(OAUTH_URL)),
+        )
+        .form(&params)?;
+
+    let token: TokenResponse = json::from_value(send_request(client, request)?)?;
+    Ok(token.into())
+}
+
+/// A Kitsu user.
+#[derive(Debug)]
+pub struct User {
+    /// The user's account ID.
+    pub id: u32,
+    /// The user's account name.
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct UserAttributes {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+impl From<TokenResponse> for TokenGrant {
+    fn from(response: TokenResponse) -> Self {
+        Self {
+            token: AccessToken::encode(response.access_token),
+            refresh_token: response.refresh_token.map(AccessToken::encode),
+            expires_in: response.expires_in,
+        }
+    }
+}
+
+fn send_request(client: &Client, request: Request) -> Result<json::Value> {
+    if cfg!(debug_assertions) && cfg!(feature = "print-requests-debug") {
+        println!("DEBUG: Kitsu request: {}", request.url);
+    }
+
+    let response = client.send(request)?;
+
+    // Deletions are responded to without a body
+    let json: json::Value = if response.body.is_empty() {
+        json::Value::Null
+    } else {
+        response.json()?
+    };
+
+    if !response.is_success() {
+        let message = json["errors"][0]["detail"]
+            .as_str()
+            .or_else(|| json["errors"][0]["title"].as_str())
+            .or_else(|| json["error_description"].as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        return Err(Error::BadKitsuResponse {
+            code: response.status,
+            message,
+        });
+    }
+
+    Ok(json)
+}
+
+/// A `JSON:API` document.
+#[derive(Debug, Deserialize)]
+struct Document<T> {
+    data: T,
+    #[serde(default)]
+    included: Vec<json::Value>,
+    #[serde(default)]
+    links: Links,
+}
+
+/// The links of a `JSON:API` document, which are used to page through collections.
+#[derive(Debug, Default, Deserialize)]
+struct Links {
+    next: Option<String>,
+}
+
+/// A `JSON:API` resource object.
+#[derive(Debug, Deserialize)]
+struct Resource<T> {
+    #[serde(with = "string_id")]
+    id: u32,
+    attributes: T,
+}
+
+/// A reference to another resource.
+#[derive(Debug, Deserialize)]
+struct Identifier {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(with = "string_id")]
+    id: u32,
+}
+
+/// Kitsu represents all IDs as strings, so this deserializes them to integers.
+mod string_id {
+    use serde::de::{self, Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Anime {
+    canonical_title: String,
+    #[serde(default)]
+    titles: AnimeTitles,
+    abbreviated_titles: Option<Vec<String>>,
+    episode_count: Option<u32>,
+    /// The length of an episode in minutes.
+    episode_length: Option<u32>,
+    subtype: Option<Subtype>,
+    /// The date the anime started airing in the form of YYYY-MM-DD.
+    start_date: Option<String>,
+    status: Option<AnimeStatus>,
+    /// The average rating given by users as a percentage.
+    average_rating: Option<String>,
+    nsfw: Option<bool>,
+}
+
+impl Anime {
+    /// Returns the year and season the anime started airing in.
+    fn start_season(&self) -> (Option<u32>, Option<Season>) {
+        let date = match &self.start_date {
+            Some(date) => date,
+            None => return (None, None),
+        };
+
+        let mut parts = date.split('-');
+        let year = parts.next().and_then(|year| year.parse().ok());
+        let season = parts
+            .next()
+            .and_then(|month| month.parse().ok())
+            .and_then(Season::from_month);
+
+        (year, season)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AnimeTitles {
+    en: Option<String>,
+    en_jp: Option<String>,
+    ja_jp: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum AnimeStatus {
+    Current,
+    Finished,
+    Tba,
+    Unreleased,
+    Upcoming,
+}
+
+impl Into<AiringStatus> for AnimeStatus {
+    fn into(self) -> AiringStatus {
+        match self {
+            Self::Current => AiringStatus::Airing,
+            Self::Finished => AiringStatus::Finished,
+            Self::Tba | Self::Unreleased | Self::Upcoming => AiringStatus::NotYetAired,
+        }
+    }
+}
+
+impl Resource<Anime> {
+    /// Convert the anime to a `SeriesInfo`, using `included` resources to find sequels and genres.
+    #[allow(clippy::cast_possible_truncation)]
+    fn into_series_info(self, included: &[json::Value]) -> result::Result<SeriesInfo, ()> {
+        let anime = self.attributes;
+        let kind = anime.subtype.ok_or(())?.try_into()?;
+        let sequels = find_sequels(included);
+        let (year, season) = anime.start_season();
+
+        let canonical_title = anime.canonical_title;
+
+        let romaji = anime
+            .titles
+            .en_jp
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| canonical_title.clone());
+
+        Ok(SeriesInfo {
+            id: self.id,
+            title: SeriesTitle {
+                romaji,
+                preferred: canonical_title,
+                english: anime.titles.en.filter(|title| !title.is_empty()),
+                native: anime.titles.ja_jp.filter(|title| !title.is_empty()),
+            },
+            episodes: anime.episode_count.unwrap_or(0),
+            episode_length: anime.episode_length.filter(|&len| len > 0).unwrap_or(24),
+            kind,
+            sequels,
+            relations: Vec::new(),
+            synonyms: anime.abbreviated_titles.unwrap_or_default(),
+            season,
+            year,
+            status: anime.status.map(Into::into),
+            genres: find_genres(included),
+            // Studios are only available through the anime's productions, which aren't requested
+            studios: Vec::new(),
+            average_score: anime
+                .average_rating
+                .and_then(|rating| rating.parse::<f32>().ok())
+                .map(|rating| rating.round() as u8),
+            is_adult: anime.nsfw.unwrap_or(false),
+            next_episode: None,
+        })
+    }
+}
+
+/// Find the title of every category in the `included` resources of a document.
+///
+/// This requires the `categories` relationship to be included.
+fn find_genres(included: &[json::Value]) -> Vec<String> {
+    included
+        .iter()
+        .filter(|value| value["type"] == "categories")
+        .filter_map(|value| value["attributes"]["title"].as_str())
+        .map(Into::into)
+        .collect()
+}
+
+/// Find every sequential relation in the `included` resources of a document.
+///
+/// This requires the `mediaRelationships.destination` relationship to be included.
+fn find_sequels(included: &[json::Value]) -> Vec<Sequel> {
+    let find_anime_kind = |id: u32| {
+        included
+            .iter()
+            .filter(|value| value["type"] == "anime")
+            .find(|value| value["id"].as_str().and_then(|id| id.parse().ok()) == Some(id))
+            .and_then(|value| {
+                json::from_value::<Subtype>(value["attributes"]["subtype"].clone()).ok()
+            })
+            .and_then(|subtype| subtype.try_into().ok())
+    };
+
+    included
+        .iter()
+        .filter(|value| value["type"] == "mediaRelationships")
+        .filter_map(|relation| {
+            let role: Role = json::from_value(relation["attributes"]["role"].clone()).ok()?;
+
+            // It doesn't make sense to consider this relation a sequel
+            // if its a prequel, alternative, or character relation
+            if !role.is_sequential() {
+                return None;
+            }
+
+            let destination: Identifier =
+                json::from_value(relation["relationships"]["destination"]["data"].clone()).ok()?;
+
+            if destination.kind != "anime" {
+                return None;
+            }
+
+            let kind = find_anime_kind(destination.id)?;
+            Some(Sequel::new(kind, destination.id))
+        })
+        .collect()
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Role {
+    Sequel,
+    SideStory,
+    Other,
+    #[serde(other)]
+    Unknown,
+}
+
+impl Role {
+    /// Returns true if the relation is considered to be some kind of sequel. Ex: a second season, OVA, ONA, movie, etc
+    fn is_sequential(self) -> bool {
+        match self {
+            Self::Sequel | Self::SideStory | Self::Other => true,
+            Self::Unknown => false,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+enum Subtype {
+    TV,
+    OVA,
+    ONA,
+    #[serde(rename = "movie")]
+    Movie,
+    #[serde(rename = "special")]
+    Special,
+    #[serde(rename = "music")]
+    Music,
+    #[serde(other)]
+    Unknown,
+}
+
+impl TryInto<SeriesKind> for Subtype {
+    type Error = ();
+
+    fn try_into(self) -> result::Result<SeriesKind, Self::Error> {
+        match self {
+            Self::TV => Ok(SeriesKind::Season),
+            Self::Movie => Ok(SeriesKind::Movie),
+            Self::Special => Ok(SeriesKind::Special),
+            Self::OVA => Ok(SeriesKind::OVA),
+            Self::ONA => Ok(SeriesKind::ONA),
+            Self::Music => Ok(SeriesKind::Music),
+            Self::Unknown => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LibraryEntry {
+    status: LibraryStatus,
+    progress: u32,
+    reconsuming: bool,
+    reconsume_count: u32,
+    rating_twenty: Option<u8>,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
+impl LibraryEntry {
+    fn into_series_entry(self, id: u32) -> SeriesEntry {
+        let status = if self.reconsuming {
+            Status::Rewatching
+        } else {
+            self.status.into()
+        };
+
+        SeriesEntry {
+            id,
+            watched_eps: self.progress,
+            score: self.rating_twenty.map(|rating| rating.min(20) * 5),
+            status,
+            times_rewatched: self.reconsume_count,
+            start_date: self.started_at.as_deref().and_then(parse_date),
+            end_date: self.finished_at.as_deref().and_then(parse_date),
+            updated_at: self.updated_at.as_deref().and_then(parse_timestamp),
+            ..SeriesEntry::new(id)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum LibraryStatus {
+    Current,
+    Completed,
+    OnHold,
+    Dropped,
+    Planned,
+}
+
+impl Into<Status> for LibraryStatus {
+    fn into(self) -> Status {
+        match self {
+            Self::Current => Status::Watching,
+            Self::Completed => Status::Completed,
+            Self::OnHold => Status::OnHold,
+            Self::Dropped => Status::Dropped,
+            Self::Planned => Status::PlanToWatch,
+        }
+    }
+}
+
+impl From<Status> for LibraryStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            // Kitsu represents a rewatch as a current entry with a reconsuming flag
+            Status::Watching | Status::Rewatching => Self::Current,
+            Status::Completed => Self::Completed,
+            Status::OnHold => Self::OnHold,
+            Status::Dropped => Self::Dropped,
+            Status::PlanToWatch => Self::Planned,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LibraryEntryUpdate {
+    status: LibraryStatus,
+    progress: u32,
+    reconsuming: bool,
+    reconsume_count: u32,
+    rating_twenty: Option<u8>,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+}
+
+impl From<&SeriesEntry> for LibraryEntryUpdate {
+    fn from(entry: &SeriesEntry) -> Self {
+        let format_date =
+            |date: SeriesDate| format!("{:04}-{:02}-{:02}", date.year, date.month, date.day);
+
+        // Kitsu's lowest possible rating is 2 (1.0 when displayed)
+        let rating_twenty = entry
+            .score
+            .map(|score| score / 5)
+            .filter(|&rating| rating > 0)
+            .map(|rating| rating.max(2));
+
+        Self {
+            status: entry.status.into(),
+            progress: entry.watched_eps,
+            reconsuming: entry.status == Status::Rewatching,
+            reconsume_count: entry.times_rewatched,
+            rating_twenty,
+            started_at: entry.start_date.map(format_date),
+            finished_at: entry.end_date.map(format_date),
+        }
+    }
+}
+
+/// A mapping of a Kitsu resource to its ID on another site.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Mapping {
+    external_site: String,
+    external_id: String,
+}
+
+impl Mapping {
+    /// Returns the `MyAnimeList` ID of the anime, if this mapping is for one.
+    fn mal_id(&self) -> Option<SeriesID> {
+        if self.external_site != MAL_ANIME_SITE {
+            return None;
+        }
+
+        self.external_id.parse().ok()
+    }
+}
+
+/// Join `ids` into the comma separated list Kitsu filters by.
+fn join_ids(ids: &[SeriesID]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parse the date portion of an ISO 8601 timestamp.
+fn parse_date(date: &str) -> Option<SeriesDate> {
+    let mut fragments = date.get(..10)?.split('-');
+
+    let year = fragments.next()?.parse().ok()?;
+    let month = fragments.next()?.parse().ok()?;
+    let day = fragments.next()?.parse().ok()?;
+
+    Some(SeriesDate::from_ymd(year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::test_server::{TestResponse, TestServer};
+
+    const ANIME_JSON: &str = r#"{
+        "data": {
+            "id": "1376",
+            "type": "anime",
+            "attributes": {
+                "canonicalTitle": "Toradora!",
+                "titles": { "en": "Toradora!", "en_jp": "Toradora!" },
+                "episodeCount": 25,
+                "episodeLength": 23,
+                "subtype": "TV"
+            }
+        },
+        "included": [
+            {
+                "id": "100",
+                "type": "mediaRelationships",
+                "attributes": { "role": "side_story" },
+                "relationships": { "destination": { "data": { "type": "anime", "id": "4960" } } }
+            },
+            {
+                "id": "101",
+                "type": "mediaRelationships",
+                "attributes": { "role": "adaptation" },
+                "relationships": { "destination": { "data": { "type": "manga", "id": "20" } } }
+            },
+            { "id": "4960", "type": "anime", "attributes": { "subtype": "OVA" } }
+        ]
+    }"#;
+
+    const LIBRARY_JSON: &str = r#"{
+        "data": [{
+            "id": "5000",
+            "type": "libraryEntries",
+            "attributes": {
+                "status": "completed",
+                "progress": 25,
+                "reconsuming": false,
+                "reconsumeCount": 2,
+                "ratingTwenty": 17,
+                "startedAt": "2021-01-02T00:00:00.000Z",
+                "finishedAt": null,
+                "updatedAt": "2017-08-08T12:39:19.217Z"
+            }
+        }]
+    }"#;
+
+    fn test_auth() -> Auth {
+        let user = User {
+            id: 7,
+            name: "test".into(),
+        };
+
+        Auth::new(user, AccessToken::encode("token"))
+    }
+
+    #[test]
+    fn info_from_local_server() {
+        let server = TestServer::spawn(vec![ANIME_JSON.into()]);
+        let kitsu = Kitsu::unauthenticated().with_client(server.client());
+
+        let info = kitsu.search_info_by_id(1376).unwrap();
+
+        assert_eq!(info.id, 1376);
+        assert_eq!(info.title.preferred, "Toradora!");
+        assert_eq!(info.episodes, 25);
+        assert_eq!(info.episode_length, 23);
+        assert_eq!(info.kind, SeriesKind::Season);
+        assert_eq!(info.sequels.len(), 1);
+        assert_eq!(info.sequels[0].id, 4960);
+        assert_eq!(info.sequels[0].kind, SeriesKind::OVA);
+    }
+
+    #[test]
+    fn update_existing_entry() {
+        let updated =
+            r#"{ "data": { "attributes": { "updatedAt": "2021-03-04T05:06:07.000Z" } } }"#;
+
+        let server = TestServer::spawn(vec![
+            LIBRARY_JSON.into(),
+            LIBRARY_JSON.into(),
+            updated.into(),
+        ]);
+
+        let kitsu = Kitsu::authenticated(test_auth()).with_client(server.client());
+
+        let mut entry = kitsu.get_list_entry(1376).unwrap().unwrap();
+
+        assert_eq!(entry.score, Some(85));
+        assert_eq!(entry.times_rewatched, 2);
+        assert!(entry.start_date.is_some());
+        assert_eq!(entry.updated_at, Some(1_502_195_959));
+
+        entry.status = Status::Rewatching;
+        let updated_at = kitsu.update_list_entry(&entry).unwrap();
+        assert_eq!(updated_at, Some(1_614_834_367));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+
+        let update = &requests[2];
+        assert!(update.starts_with("PATCH /library-entries/5000"));
+        assert!(update.contains("content-type: application/vnd.api+json"));
+        assert!(update.contains(r#""status":"current""#));
+        assert!(update.contains(r#""reconsuming":true"#));
+        assert!(update.contains(r#""ratingTwenty":17"#));
+    }
+
+    #[test]
+    fn list_entry_is_deleted_by_its_own_id() {
+        let server = TestServer::spawn(vec![
+            LIBRARY_JSON.into(),
+            TestResponse::with_status(204, ""),
+            r#"{ "data": [] }"#.into(),
+        ]);
+
+        let kitsu = Kitsu::authenticated(test_auth()).with_client(server.client());
+
+        kitsu.delete_list_entry(1376).unwrap();
+        // Anime that aren't in the library are already deleted
+        kitsu.delete_list_entry(1377).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].starts_with("DELETE /library-entries/5000"));
+    }
+
+    #[test]
+    fn user_list_is_paged_through() {
+        let first_page = r#"{
+            "data": [{
+                "id": "5000",
+                "type": "libraryEntries",
+                "attributes": {
+                    "status": "completed",
+                    "progress": 25,
+                    "reconsuming": false,
+                    "reconsumeCount": 0,
+                    "ratingTwenty": null,
+                    "startedAt": null,
+                    "finishedAt": null
+                },
+                "relationships": { "anime": { "data": { "type": "anime", "id": "1376" } } }
+            }],
+            "included": [{
+                "id": "1376",
+                "type": "anime",
+                "attributes": { "canonicalTitle": "Toradora!", "episodeCount": 25, "subtype": "TV" }
+            }],
+            "links": { "next": "https://kitsu.io/api/edge/library-entries?page%5Boffset%5D=1" }
+        }"#;
+
+        let server = TestServer::spawn(vec![
+            first_page.into(),
+            r#"{ "data": [], "links": {} }"#.into(),
+        ]);
+
+        let kitsu = Kitsu::authenticated(test_auth()).with_client(server.client());
+        let list = kitsu.get_user_list().unwrap();
+
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].info.id, 1376);
+        assert_eq!(list[0].info.title.preferred, "Toradora!");
+        assert_eq!(list[0].entry.id, 1376);
+        assert_eq!(list[0].entry.status, Status::Completed);
+        assert_eq!(list[0].entry.watched_eps, 25);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("filter%5BuserId%5D=7"));
+        assert!(requests[1].contains("page%5Boffset%5D=1"));
+    }
+
+    #[test]
+    fn mal_ids_are_mapped() {
+        let anime = r#"{
+            "data": [
+                {
+                    "id": "1376",
+                    "type": "anime",
+                    "relationships": {
+                        "mappings": { "data": [{ "type": "mappings", "id": "10" }, { "type": "mappings", "id": "11" }] }
+                    }
+                },
+                { "id": "1377", "type": "anime", "relationships": { "mappings": { "data": [] } } }
+            ],
+            "included": [
+                { "id": "10", "type": "mappings", "attributes": { "externalSite": "anidb", "externalId": "5560" } },
+                { "id": "11", "type": "mappings", "attributes": { "externalSite": "myanimelist/anime", "externalId": "4224" } }
+            ]
+        }"#;
+
+        let mappings = r#"{
+            "data": [
+                {
+                    "id": "11",
+                    "type": "mappings",
+                    "attributes": { "externalSite": "myanimelist/anime", "externalId": "4224" },
+                    "relationships": { "item": { "data": { "type": "anime", "id": "1376" } } }
+                },
+                {
+                    "id": "12",
+                    "type": "mappings",
+                    "attributes": { "externalSite": "myanimelist/anime", "externalId": "4225" },
+                    "relationships": { "item": { "data": { "type": "manga", "id": "20" } } }
+                }
+            ]
+        }"#;
+
+        let server = TestServer::spawn(vec![anime.into(), mappings.into()]);
+        let kitsu = Kitsu::unauthenticated().with_client(server.client());
+
+        let mal_ids = kitsu.get_mal_ids(&[1376, 1377]).unwrap();
+        assert_eq!(mal_ids.len(), 1);
+        assert_eq!(mal_ids[&1376], 4224);
+
+        let ids = kitsu.find_by_mal_ids(&[4224, 4225]).unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[&4224], 1376);
+
+        let requests = server.requests();
+        assert!(requests[0].contains("filter%5Bid%5D=1376%2C1377"));
+        assert!(requests[1].starts_with("GET /mappings"));
+        assert!(requests[1].contains("filter%5BexternalId%5D=4224%2C4225"));
+    }
+
+    #[test]
+    fn score_parsing() {
+        let kitsu = Kitsu::unauthenticated();
+
+        assert_eq!(kitsu.parse_score("8.5"), Some(85));
+        assert_eq!(kitsu.parse_score("7.3"), None);
+        assert_eq!(kitsu.parse_score("11"), None);
+        assert_eq!(kitsu.parse_score("0"), None);
+        assert_eq!(kitsu.parse_score("1"), Some(10));
+        assert_eq!(kitsu.score_to_str(85), "8.5");
+    }
+
+    #[test]
+    fn token_is_refreshed() {
+        let server = TestServer::spawn(vec![
+            r#"{ "access_token": "new", "refresh_token": "next", "expires_in": 2592000 }"#.into(),
+        ]);
+
+        let grant = Auth::refresh_with(&server.client(), &AccessToken::encode("old")).unwrap();
+
+        assert_eq!(grant.token.decode().unwrap(), "new");
+        assert_eq!(grant.refresh_token.unwrap().decode().unwrap(), "next");
+        assert_eq!(grant.expires_in, Some(2_592_000));
+
+        let request = server.requests().remove(0);
+        assert!(request.starts_with("POST /token "));
+        assert!(request.contains("grant_type=refresh_token&refresh_token=old"));
+    }
+}
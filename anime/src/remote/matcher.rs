@@ -0,0 +1,392 @@
+//! Matching of series info against a name and the episodes found locally.
+
+use super::SeriesInfo;
+use crate::SeriesKind;
+use std::borrow::Cow;
+
+/// The minimum score a series should have to be used without asking the user to confirm it.
+///
+/// Titles are scored with Jaro similarity, and this is the Jaro equivalent of a Jaro-Winkler score of 0.85 for titles
+/// that share a 4 character prefix.
+pub const CONFIDENT_MATCH: f32 = 0.75;
+
+/// The most a series' score can be raised by fitting the local episodes.
+///
+/// This is enough to favor the right series among ones with nearly identical titles, but not to outweigh a title
+/// that matches noticeably better, such as a different season.
+const EPISODE_FIT_WEIGHT: f32 = 0.05;
+
+/// The highest value `SeriesMatcher::episode_fit` returns.
+const MAX_EPISODE_FIT: u8 = 5;
+
+/// Scores series info against a name, and optionally the episodes found locally.
+///
+/// Every title and synonym of a series is compared against the name, and the best score is used.
+/// Before comparison, titles are normalized so that different ways of writing the season number
+/// (such as "2nd Season", "Season 2", and "II") are treated as the same.
+///
+/// Series that fit the local episodes are given a small bonus, so they're favored over series whose titles match
+/// about as well.
+#[derive(Clone, Debug)]
+pub struct SeriesMatcher {
+    name: String,
+    episodes: Option<u32>,
+}
+
+impl SeriesMatcher {
+    /// Create a new `SeriesMatcher` that compares series titles against `name`.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: normalize_title(name),
+            episodes: None,
+        }
+    }
+
+    /// Favor series that can hold the specified number of local `episodes`, along with series whose format makes
+    /// sense for that many episodes.
+    #[must_use]
+    pub fn episodes<E>(mut self, episodes: E) -> Self
+    where
+        E: Into<Option<u32>>,
+    {
+        self.episodes = episodes.into();
+        self
+    }
+
+    /// Returns how closely `info` matches.
+    ///
+    /// This is a value between 0.0 and 1.0 for the title, plus up to 0.05 if `info` fits the local episodes.
+    #[must_use]
+    pub fn score(&self, info: &SeriesInfo) -> f32 {
+        let bonus = f32::from(self.episode_fit(info)) / f32::from(MAX_EPISODE_FIT);
+        self.title_score(info) + bonus * EPISODE_FIT_WEIGHT
+    }
+
+    /// Returns how closely the best title of `info` matches the name, as a value between 0.0 and 1.0.
+    fn title_score(&self, info: &SeriesInfo) -> f32 {
+        let title = &info.title;
+
+        let titles = std::iter::once(title.romaji.as_str())
+            .chain(std::iter::once(title.preferred.as_str()))
+            .chain(title.english.as_deref())
+            .chain(title.native.as_deref())
+            .chain(info.synonyms.iter().map(String::as_str));
+
+        // Jaro-Winkler isn't used since its prefix bonus scores titles that are a prefix of the name as exact matches,
+        // which would make the first season of a series indistinguishable from its sequels
+        titles
+            .map(|title| strsim::jaro(&normalize_title(title), &self.name) as f32)
+            .fold(0.0, f32::max)
+    }
+
+    /// Returns how well `info` fits the local episodes, from 0 to `MAX_EPISODE_FIT`.
+    fn episode_fit(&self, info: &SeriesInfo) -> u8 {
+        let local = match self.episodes {
+            Some(local) => local,
+            None => return 0,
+        };
+
+        // A series with fewer episodes than were found can't be the right one, but one with more may have only been
+        // partially downloaded, and one with an unknown amount may still be airing
+        let count_fit = match info.episode_count() {
+            Some(episodes) if episodes == local => 2,
+            Some(episodes) if episodes > local => 1,
+            None => 1,
+            Some(_) => 0,
+        };
+
+        count_fit * 2 + u8::from(format_fits_episodes(info.kind, local))
+    }
+
+    /// Find the series in `items` that best matches and return it if its score is at least `min_confidence`.
+    pub fn best_match<'a, I>(
+        &self,
+        items: I,
+        min_confidence: f32,
+    ) -> Option<(usize, Cow<'a, SeriesInfo>)>
+    where
+        I: Iterator<Item = Cow<'a, SeriesInfo>>,
+    {
+        let mut best: Option<(usize, Cow<'a, SeriesInfo>, f32)> = None;
+
+        for (i, info) in items.enumerate() {
+            let score = self.score(&info);
+
+            let is_better = match &best {
+                Some((_, _, best_score)) => score > *best_score,
+                None => true,
+            };
+
+            if is_better {
+                best = Some((i, info, score));
+            }
+        }
+
+        match best {
+            Some((i, info, score)) if score >= min_confidence => Some((i, info)),
+            _ => None,
+        }
+    }
+}
+
+/// Returns true if a series of the specified `kind` would usually have the specified number of `episodes`.
+fn format_fits_episodes(kind: SeriesKind, episodes: u32) -> bool {
+    match kind {
+        SeriesKind::Movie | SeriesKind::Music => episodes == 1,
+        SeriesKind::Season => episodes > 1,
+        SeriesKind::Special | SeriesKind::OVA | SeriesKind::ONA => true,
+    }
+}
+
+/// Normalize a series title for comparison.
+///
+/// This lowercases the title, removes punctuation, and rewrites season numbers into a single form.
+/// The first season marker is removed entirely, as first seasons are rarely labelled.
+///
+/// # Example
+///
+/// ```
+/// use anime::remote::matcher::normalize_title;
+///
+/// assert_eq!(normalize_title("Kaguya-sama: Love is War 2nd Season"), "kaguya sama love is war s2");
+/// assert_eq!(normalize_title("Kaguya-sama: Love is War Season 2"), "kaguya sama love is war s2");
+/// assert_eq!(normalize_title("Kaguya-sama: Love is War II"), "kaguya sama love is war s2");
+/// ```
+#[must_use]
+pub fn normalize_title(title: &str) -> String {
+    let cleaned = title
+        .chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() {
+                ch.to_lowercase().next().unwrap_or(ch)
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>();
+
+    let words = cleaned.split_whitespace().collect::<Vec<_>>();
+    let mut normalized = Vec::with_capacity(words.len());
+    let mut i = 0;
+
+    while i < words.len() {
+        let word = words[i];
+        let next = words.get(i + 1).copied();
+        let is_last = i + 1 == words.len();
+
+        // "Season 2"
+        if word == "season" {
+            if let Some(season) = next.and_then(season_number) {
+                push_season(&mut normalized, season);
+                i += 2;
+                continue;
+            }
+        }
+
+        // "2nd Season" or "Second Season"
+        if next == Some("season") {
+            if let Some(season) = ordinal_number(word) {
+                push_season(&mut normalized, season);
+                i += 2;
+                continue;
+            }
+        }
+
+        // "II", which is only considered a season number at the end of a title to avoid
+        // treating words like "x" as one
+        if is_last && !normalized.is_empty() {
+            if let Some(season) = roman_numeral(word) {
+                push_season(&mut normalized, season);
+                i += 1;
+                continue;
+            }
+        }
+
+        normalized.push(Cow::Borrowed(word));
+        i += 1;
+    }
+
+    normalized.join(" ")
+}
+
+fn push_season(words: &mut Vec<Cow<str>>, season: u32) {
+    if season > 1 {
+        words.push(format!("s{}", season).into());
+    }
+}
+
+fn season_number(word: &str) -> Option<u32> {
+    word.parse()
+        .ok()
+        .or_else(|| ordinal_number(word))
+        .or_else(|| roman_numeral(word))
+}
+
+fn ordinal_number(word: &str) -> Option<u32> {
+    const WORDS: [&str; 10] = [
+        "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+        "tenth",
+    ];
+
+    if let Some((_, number)) = WORDS.iter().zip(1..).find(|(&ordinal, _)| ordinal == word) {
+        return Some(number);
+    }
+
+    let suffix_start = word.find(|ch: char| !ch.is_ascii_digit())?;
+    let (number, suffix) = word.split_at(suffix_start);
+
+    match suffix {
+        "st" | "nd" | "rd" | "th" => number.parse().ok(),
+        _ => None,
+    }
+}
+
+fn roman_numeral(word: &str) -> Option<u32> {
+    // A single "i" is too common on its own to be treated as a numeral
+    const NUMERALS: [&str; 9] = ["ii", "iii", "iv", "v", "vi", "vii", "viii", "ix", "x"];
+
+    NUMERALS
+        .iter()
+        .zip(2..)
+        .find(|(&numeral, _)| numeral == word)
+        .map(|(_, number)| number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(id: u32, romaji: &str, english: Option<&str>, episodes: u32) -> SeriesInfo {
+        let mut info = SeriesInfo::with_title(id, romaji);
+        info.title.english = english.map(Into::into);
+        info.episodes = episodes;
+        info
+    }
+
+    #[test]
+    fn season_variants_are_normalized() {
+        let names = [
+            ("Mob Psycho 100 II", "mob psycho 100 s2"),
+            ("Mob Psycho 100 Season 2", "mob psycho 100 s2"),
+            ("Mob Psycho 100 2nd Season", "mob psycho 100 s2"),
+            ("Mob Psycho 100 Second Season", "mob psycho 100 s2"),
+            ("Mob Psycho 100 Season 1", "mob psycho 100"),
+            ("Hunter x Hunter", "hunter x hunter"),
+            (
+                "Re:Zero kara Hajimeru Isekai Seikatsu",
+                "re zero kara hajimeru isekai seikatsu",
+            ),
+        ];
+
+        for (name, expected) in &names {
+            assert_eq!(normalize_title(name), *expected, "normalizing {}", name);
+        }
+    }
+
+    #[test]
+    fn english_titles_are_matched() {
+        let items = [
+            info(1, "Shingeki no Kyojin", Some("Attack on Titan"), 25),
+            info(
+                2,
+                "Shingeki no Kyojin 2",
+                Some("Attack on Titan Season 2"),
+                12,
+            ),
+        ];
+
+        let matcher = SeriesMatcher::new("Attack on Titan Season 2");
+        let (index, _) = matcher
+            .best_match(items.iter().map(Cow::Borrowed), CONFIDENT_MATCH)
+            .unwrap();
+
+        assert_eq!(index, 1);
+    }
+
+    #[test]
+    fn episode_count_breaks_ties() {
+        let items = [
+            info(1, "Fruits Basket", None, 26),
+            info(2, "Fruits Basket", None, 25),
+        ];
+
+        let matcher = SeriesMatcher::new("Fruits Basket").episodes(25);
+        let (index, _) = matcher
+            .best_match(items.iter().map(Cow::Borrowed), CONFIDENT_MATCH)
+            .unwrap();
+
+        assert_eq!(index, 1);
+    }
+
+    #[test]
+    fn episode_count_does_not_outweigh_title() {
+        let items = [
+            info(1, "Mob Psycho 100", None, 12),
+            info(2, "Mob Psycho 100 II", None, 13),
+        ];
+
+        let matcher = SeriesMatcher::new("Mob Psycho 100 II").episodes(12);
+        let (index, _) = matcher
+            .best_match(items.iter().map(Cow::Borrowed), CONFIDENT_MATCH)
+            .unwrap();
+
+        assert_eq!(index, 1);
+    }
+
+    #[test]
+    fn episode_count_boosts_close_titles() {
+        let items = [
+            info(1, "Mahouka Koukou no Rettousei", None, 1),
+            info(2, "Mahouka Koukou no Rettosei", None, 26),
+        ];
+
+        let matcher = SeriesMatcher::new("Mahouka Koukou no Rettousei");
+        let items = || items.iter().map(Cow::Borrowed);
+
+        assert_eq!(matcher.best_match(items(), CONFIDENT_MATCH).unwrap().0, 0);
+
+        let (index, _) = matcher
+            .episodes(26)
+            .best_match(items(), CONFIDENT_MATCH)
+            .unwrap();
+
+        assert_eq!(index, 1);
+    }
+
+    #[test]
+    fn confident_matches_allow_abbreviated_titles() {
+        let items = [info(1, "Code Geass: Hangyaku no Lelouch", None, 25)];
+        let items = || items.iter().map(Cow::Borrowed);
+
+        assert!(SeriesMatcher::new("Code Geass")
+            .best_match(items(), CONFIDENT_MATCH)
+            .is_some());
+
+        // These score just above and below the threshold
+        assert!(SeriesMatcher::new("Code Gea")
+            .best_match(items(), CONFIDENT_MATCH)
+            .is_some());
+
+        assert!(SeriesMatcher::new("Code Ge")
+            .best_match(items(), CONFIDENT_MATCH)
+            .is_none());
+    }
+
+    #[test]
+    fn partially_downloaded_series_are_matched() {
+        let items = [
+            info(1, "Fruits Basket", None, 3),
+            info(2, "Fruits Basket", None, 25),
+            info(3, "Fruits Basket", None, 0),
+        ];
+
+        // Only the first 5 episodes of the series with 25 have been downloaded
+        let matcher = SeriesMatcher::new("Fruits Basket").episodes(5);
+        let (index, _) = matcher
+            .best_match(items.iter().map(Cow::Borrowed), CONFIDENT_MATCH)
+            .unwrap();
+
+        assert_eq!(index, 1);
+    }
+}
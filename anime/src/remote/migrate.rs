@@ -0,0 +1,180 @@
+//! Copying a user's list from one remote service to another.
+
+use super::{RemoteService, SeriesEntry, SeriesID, UserListEntry};
+use crate::err::{Error, Result};
+
+/// The outcome of copying a user's list to another remote service.
+#[derive(Debug, Default)]
+pub struct Migration {
+    /// The entries that were written to the target service, with the IDs they have on it.
+    pub migrated: Vec<SeriesEntry>,
+    /// The entries that couldn't be matched to an anime on the target service, with the IDs they have on the source
+    /// service.
+    pub unmapped: Vec<UserListEntry>,
+    /// The entries that the target service failed to write, with the IDs they have on it and the error it returned.
+    pub failed: Vec<(SeriesEntry, Error)>,
+}
+
+/// Copy every entry on the user's list from the `from` service to the `to` service.
+///
+/// Anime are matched between services by their `MyAnimeList` ID, so both services must support mapping IDs to and from
+/// one. Entries that fail to be written don't stop the rest from being copied, and are returned in the `failed` field
+/// instead. Existing entries on the target service are overwritten, so a migration can simply be ran again.
+pub fn migrate_list<F, T>(from: &F, to: &T) -> Result<Migration>
+where
+    F: RemoteService + ?Sized,
+    T: RemoteService + ?Sized,
+{
+    let list = from.get_user_list()?;
+
+    let ids = list
+        .iter()
+        .map(|list_entry| list_entry.entry.id)
+        .collect::<Vec<_>>();
+
+    let mal_ids = from.get_mal_ids(&ids)?;
+
+    let target_ids = {
+        let mut ids = mal_ids.values().copied().collect::<Vec<SeriesID>>();
+        ids.sort_unstable();
+        ids.dedup();
+
+        to.find_by_mal_ids(&ids)?
+    };
+
+    let mut migration = Migration::default();
+
+    for list_entry in list {
+        let target_id = mal_ids
+            .get(&list_entry.entry.id)
+            .and_then(|mal_id| target_ids.get(mal_id));
+
+        let target_id = match target_id {
+            Some(&id) => id,
+            None => {
+                migration.unmapped.push(list_entry);
+                continue;
+            }
+        };
+
+        let entry = SeriesEntry {
+            id: target_id,
+            // This is only meaningful to the service the entry came from
+            updated_at: None,
+            ..list_entry.entry
+        };
+
+        match to.update_list_entry(&entry) {
+            Ok(_) => migration.migrated.push(entry),
+            Err(err) => migration.failed.push((entry, err)),
+        }
+    }
+
+    Ok(migration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::anilist::{AniList, Auth};
+    use crate::remote::myanimelist::{self, MyAnimeList};
+    use crate::remote::test_server::{TestResponse, TestServer};
+    use crate::remote::{AccessToken, Status};
+
+    #[test]
+    fn list_is_copied_between_local_servers() {
+        let user = r#"{
+            "data": {
+                "Viewer": { "id": 5, "name": "test", "mediaListOptions": { "scoreFormat": "POINT_100" } }
+            }
+        }"#;
+
+        let list = r#"{
+            "data": {
+                "MediaListCollection": {
+                    "hasNextChunk": false,
+                    "lists": [{
+                        "entries": [
+                            {
+                                "mediaId": 31, "status": "CURRENT", "score": 0, "progress": 3, "repeat": 0,
+                                "startedAt": { "year": null, "month": null, "day": null },
+                                "completedAt": { "year": null, "month": null, "day": null },
+                                "media": {
+                                    "id": 31, "title": { "romaji": "Rejected", "userPreferred": "Rejected" },
+                                    "episodes": 12, "duration": 24, "format": "TV", "relations": null
+                                }
+                            },
+                            {
+                                "mediaId": 30, "status": "COMPLETED", "score": 90, "progress": 26, "repeat": 1,
+                                "startedAt": { "year": null, "month": null, "day": null },
+                                "completedAt": { "year": null, "month": null, "day": null },
+                                "media": {
+                                    "id": 30, "title": { "romaji": "Evangelion", "userPreferred": "Evangelion" },
+                                    "episodes": 26, "duration": 24, "format": "TV", "relations": null
+                                }
+                            },
+                            {
+                                "mediaId": 99, "status": "PLANNING", "score": 0, "progress": 0, "repeat": 0,
+                                "startedAt": { "year": null, "month": null, "day": null },
+                                "completedAt": { "year": null, "month": null, "day": null },
+                                "media": {
+                                    "id": 99, "title": { "romaji": "Unmapped", "userPreferred": "Unmapped" },
+                                    "episodes": 12, "duration": 24, "format": "TV", "relations": null
+                                }
+                            }
+                        ]
+                    }]
+                }
+            }
+        }"#;
+
+        let mal_ids = r#"{
+            "data": {
+                "Page": {
+                    "media": [
+                        { "id": 30, "idMal": 130 },
+                        { "id": 31, "idMal": 131 },
+                        { "id": 99, "idMal": null }
+                    ]
+                }
+            }
+        }"#;
+
+        let anilist_server = TestServer::spawn(vec![user.into(), list.into(), mal_ids.into()]);
+        let client = anilist_server.client();
+        let auth = Auth::retrieve_with(&client, AccessToken::encode("token")).unwrap();
+        let anilist = AniList::authenticated(auth).with_client(client);
+
+        let mal_server = TestServer::spawn(vec![
+            r#"{ "id": 1, "name": "test" }"#.into(),
+            TestResponse::with_status(400, r#"{ "error": "invalid_content" }"#),
+            r#"{ "status": "completed" }"#.into(),
+        ]);
+
+        let client = mal_server.client();
+        let auth =
+            myanimelist::Auth::retrieve_with(&client, "client", AccessToken::encode("token"))
+                .unwrap();
+        let mal = MyAnimeList::authenticated("client", auth).with_client(client);
+
+        let migration = migrate_list(&anilist, &mal).unwrap();
+
+        assert_eq!(migration.migrated.len(), 1);
+        assert_eq!(migration.migrated[0].id, 130);
+        assert_eq!(migration.migrated[0].status, Status::Completed);
+        assert_eq!(migration.migrated[0].times_rewatched, 1);
+
+        assert_eq!(migration.unmapped.len(), 1);
+        assert_eq!(migration.unmapped[0].info.title.preferred, "Unmapped");
+
+        // The rejected entry shouldn't stop the ones after it from being migrated
+        assert_eq!(migration.failed.len(), 1);
+        assert_eq!(migration.failed[0].0.id, 131);
+
+        let requests = mal_server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].starts_with("PATCH /anime/131/my_list_status "));
+        assert!(requests[2].starts_with("PATCH /anime/130/my_list_status "));
+        assert!(requests[2].contains("num_watched_episodes=26"));
+    }
+}
@@ -1,15 +1,43 @@
 pub mod anilist;
+pub mod cache;
+pub mod kitsu;
+pub mod matcher;
+pub mod myanimelist;
 pub mod offline;
+pub mod rate_limit;
+pub mod replay;
+pub mod transport;
 
-use crate::err::Result;
+mod migrate;
+mod relation;
+
+#[cfg(feature = "async-support")]
+mod async_remote;
+
+#[cfg(test)]
+mod test_server;
+
+use crate::err::{Error, Result};
 use crate::SeriesKind;
 use anilist::AniList;
+use cache::Cached;
 use enum_dispatch::enum_dispatch;
+use kitsu::Kitsu;
+use matcher::SeriesMatcher;
+use myanimelist::MyAnimeList;
 use offline::Offline;
+use replay::Replay;
 use serde_derive::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt;
 
+pub use migrate::{migrate_list, Migration};
+pub use relation::{Relation, RelationGraph, RelationKind};
+
+#[cfg(feature = "async-support")]
+pub use async_remote::AsyncRemote;
+
 #[cfg(feature = "diesel-support")]
 use {
     diesel::{
@@ -28,7 +56,11 @@ pub type SeriesID = u32;
 #[derive(Debug)]
 pub enum Remote {
     AniList,
+    Cached,
+    Kitsu,
+    MyAnimeList,
     Offline,
+    Replay,
 }
 
 impl Remote {
@@ -37,6 +69,15 @@ impl Remote {
     pub fn offline() -> Self {
         Offline::new().into()
     }
+
+    /// Returns the remote service that `self` caches series info for, or `self` if it isn't a `Cached` remote.
+    #[must_use]
+    pub fn uncached(&self) -> &Self {
+        match self {
+            Self::Cached(cached) => cached.inner().uncached(),
+            other => other,
+        }
+    }
 }
 
 /// Core functionality to interact with an anime tracking service.
@@ -45,20 +86,164 @@ pub trait RemoteService: ScoreParser {
     /// Search for an anime's information by title and return all of the matches.
     fn search_info_by_name(&self, name: &str) -> Result<Vec<SeriesInfo>>;
 
+    /// Search for an anime's information by title, narrowed down and paged through with `options`.
+    ///
+    /// By default, this returns every result from `search_info_by_name` on the first page, with the filters of
+    /// `options` applied to them afterwards. Services that can filter and page through their results will do so on
+    /// their end instead.
+    fn search_info(&self, name: &str, options: &SearchOptions) -> Result<SearchPage> {
+        if options.page > 1 {
+            return Ok(SearchPage::default());
+        }
+
+        let results = self
+            .search_info_by_name(name)?
+            .into_iter()
+            .filter(|info| options.matches(info))
+            .collect();
+
+        Ok(SearchPage {
+            results,
+            has_next_page: false,
+        })
+    }
+
     /// Get an anime's information by its ID.
     /// Note that the ID will differ from service to service.
     fn search_info_by_id(&self, id: SeriesID) -> Result<SeriesInfo>;
 
+    /// Get the information of several anime by their IDs.
+    ///
+    /// Series that can't be found are left out of the results, and the results may not be in the same order as `ids`.
+    /// By default, this will call `search_info_by_id` for each ID, but services that can look up several series at once
+    /// will do so in as few requests as possible.
+    fn search_info_by_ids(&self, ids: &[SeriesID]) -> Result<Vec<SeriesInfo>> {
+        let mut results = Vec::with_capacity(ids.len());
+
+        for &id in ids {
+            match self.search_info_by_id(id) {
+                Ok(info) => results.push(info),
+                Err(Error::NotAnAnime) => (),
+                Err(err) if err.is_http_code(404) => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Get every anime in the same franchise as the anime with the specified `id` by walking its relations.
+    ///
+    /// By default, this will call `search_info_by_ids` once for each level of relations. Services that don't
+    /// provide relations will only return the anime itself.
+    fn get_relation_graph(&self, id: SeriesID) -> Result<RelationGraph> {
+        let root = self.search_info_by_id(id)?;
+        RelationGraph::walk(root, |ids| self.search_info_by_ids(ids))
+    }
+
+    /// Get the anime recommended to people who liked the anime with the specified `id`, sorted by their rating.
+    ///
+    /// Returns an `Unsupported` error by default.
+    fn get_recommendations(&self, id: SeriesID) -> Result<Vec<Recommendation>> {
+        let _ = id;
+        Err(Error::Unsupported)
+    }
+
+    /// Get every anime that started airing in the specified `season` and `year`, sorted by their popularity.
+    ///
+    /// When `kind` is specified, only anime of that kind will be returned. Returns an `Unsupported` error by default.
+    fn get_season(
+        &self,
+        season: Season,
+        year: u32,
+        kind: Option<SeriesKind>,
+    ) -> Result<Vec<SeriesInfo>> {
+        let _ = (season, year, kind);
+        Err(Error::Unsupported)
+    }
+
     /// Retrieve the anime list entry from the currently authenticated user.
     ///
     /// `id` is the ID of the anime, which differs from service to service.
     fn get_list_entry(&self, id: SeriesID) -> Result<Option<SeriesEntry>>;
 
+    /// Retrieve the anime list entries with the specified `ids` from the currently authenticated user.
+    ///
+    /// Series that aren't on the user's list are left out of the results. By default, this will call `get_list_entry`
+    /// for each ID.
+    fn get_list_entries(&self, ids: &[SeriesID]) -> Result<Vec<SeriesEntry>> {
+        let mut results = Vec::with_capacity(ids.len());
+
+        for &id in ids {
+            if let Some(entry) = self.get_list_entry(id)? {
+                results.push(entry);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Retrieve every entry on the currently authenticated user's anime list, along with the information of each series.
+    ///
+    /// Returns an `Unsupported` error by default.
+    fn get_user_list(&self) -> Result<Vec<UserListEntry>> {
+        Err(Error::Unsupported)
+    }
+
+    /// Get the `MyAnimeList` IDs of the anime with the specified `ids`, keyed by their ID on this service.
+    ///
+    /// `MyAnimeList` IDs are used to match anime between services. Anime without one are left out.
+    /// Returns an `Unsupported` error by default.
+    fn get_mal_ids(&self, ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        let _ = ids;
+        Err(Error::Unsupported)
+    }
+
+    /// Get the IDs on this service of the anime with the specified `MyAnimeList` `mal_ids`, keyed by their `MyAnimeList` ID.
+    ///
+    /// Anime that can't be found are left out. Returns an `Unsupported` error by default.
+    fn find_by_mal_ids(&self, mal_ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        let _ = mal_ids;
+        Err(Error::Unsupported)
+    }
+
+    /// Retrieve every episode of the series with the specified `ids` that airs between `from` and `to`,
+    /// in seconds since the Unix epoch.
+    ///
+    /// Episodes are sorted by the time they air. Returns an `Unsupported` error by default.
+    fn get_airing_schedule(
+        &self,
+        ids: &[SeriesID],
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<AiringEpisode>> {
+        let _ = (ids, from, to);
+        Err(Error::Unsupported)
+    }
+
     /// Upload `entry` to the currently authenticated user's anime list.
     ///
+    /// Returns the time the remote service recorded the update at, in seconds since the Unix epoch, if it reports it.
+    ///
     /// Please ensure that the `SeriesEntry` you are using comes from the current service
     /// in use, or you may overwrite a completely different list entry.
-    fn update_list_entry(&self, entry: &SeriesEntry) -> Result<()>;
+    fn update_list_entry(&self, entry: &SeriesEntry) -> Result<Option<i64>>;
+
+    /// Remove the anime with the specified `id` from the currently authenticated user's list.
+    ///
+    /// Nothing will be done if the anime isn't on the user's list. Returns an `Unsupported` error by default.
+    fn delete_list_entry(&self, id: SeriesID) -> Result<()> {
+        let _ = id;
+        Err(Error::Unsupported)
+    }
+
+    /// Mark or unmark the anime with the specified `id` as one of the currently authenticated user's favourites.
+    ///
+    /// Nothing will be done if the anime is already in the requested state. Returns an `Unsupported` error by default.
+    fn set_favourite(&self, id: SeriesID, favourite: bool) -> Result<()> {
+        let _ = (id, favourite);
+        Err(Error::Unsupported)
+    }
 
     /// Indicates whether or not this service is meant to be used without an internet connection.
     ///
@@ -90,13 +275,13 @@ pub trait ScoreParser {
 }
 
 /// General information for an anime series.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SeriesInfo {
     /// The ID of the series.
     pub id: SeriesID,
     /// The titles of the series.
     pub title: SeriesTitle,
-    /// The number of episodes.
+    /// The number of episodes, or 0 if it isn't known yet.
     pub episodes: u32,
     /// The length of a single episode in minutes.
     pub episode_length: u32,
@@ -104,6 +289,38 @@ pub struct SeriesInfo {
     pub kind: SeriesKind,
     /// An ID pointing to the sequel of this series.
     pub sequels: Vec<Sequel>,
+    /// Every anime the series is related to, including the ones in `sequels`.
+    ///
+    /// This is only provided by `AniList`.
+    #[serde(default)]
+    pub relations: Vec<Relation>,
+    /// Alternative titles the series is known by.
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+    /// The season the series started airing in.
+    #[serde(default)]
+    pub season: Option<Season>,
+    /// The year the series started airing in.
+    #[serde(default)]
+    pub year: Option<u32>,
+    /// The current airing status of the series.
+    #[serde(default)]
+    pub status: Option<AiringStatus>,
+    /// The genres of the series.
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// The main studios that produced the series.
+    #[serde(default)]
+    pub studios: Vec<String>,
+    /// The average score given to the series by users, out of 100.
+    #[serde(default)]
+    pub average_score: Option<u8>,
+    /// Indicates whether or not the series is intended for adults only.
+    #[serde(default)]
+    pub is_adult: bool,
+    /// The next episode to air, if the series is currently airing.
+    #[serde(default)]
+    pub next_episode: Option<NextEpisode>,
 }
 
 impl SeriesInfo {
@@ -117,13 +334,26 @@ impl SeriesInfo {
         I: Iterator<Item = Cow<'a, Self>>,
         S: Into<String>,
     {
-        let mut name = name.into();
-        name.make_ascii_lowercase();
+        let name = name.into();
+        SeriesMatcher::new(&name).best_match(items, min_confidence)
+    }
 
-        crate::closest_match(items, min_confidence, |info| {
-            let title = info.title.romaji.to_ascii_lowercase();
-            Some(strsim::jaro_winkler(&title, &name) as f32)
-        })
+    /// Returns the number of episodes of the series, or None if it isn't known yet.
+    #[inline]
+    #[must_use]
+    pub fn episode_count(&self) -> Option<u32> {
+        Some(self.episodes).filter(|&episodes| episodes > 0)
+    }
+
+    /// Returns the number of episodes that have aired so far, if it's known.
+    #[must_use]
+    pub fn aired_episodes(&self) -> Option<u32> {
+        match (self.next_episode, self.status) {
+            (Some(next), _) => Some(next.number.saturating_sub(1)),
+            (None, Some(AiringStatus::Finished)) => self.episode_count(),
+            (None, Some(AiringStatus::NotYetAired)) => Some(0),
+            (None, _) => None,
+        }
     }
 
     /// Returns the first sequel that is the same kind as the current series.
@@ -143,6 +373,36 @@ impl SeriesInfo {
     }
 }
 
+#[cfg(test)]
+impl SeriesInfo {
+    /// Create the info of a 12 episode TV season titled `title`, with every other field left empty.
+    pub(crate) fn with_title(id: SeriesID, title: &str) -> Self {
+        Self {
+            id,
+            title: SeriesTitle {
+                romaji: title.into(),
+                preferred: title.into(),
+                english: None,
+                native: None,
+            },
+            episodes: 12,
+            episode_length: 24,
+            kind: SeriesKind::Season,
+            sequels: Vec::new(),
+            relations: Vec::new(),
+            synonyms: Vec::new(),
+            season: None,
+            year: None,
+            status: None,
+            genres: Vec::new(),
+            studios: Vec::new(),
+            average_score: None,
+            is_adult: false,
+            next_episode: None,
+        }
+    }
+}
+
 impl<'a> Into<Cow<'a, Self>> for SeriesInfo {
     fn into(self) -> Cow<'a, Self> {
         Cow::Owned(self)
@@ -155,8 +415,268 @@ impl<'a> Into<Cow<'a, SeriesInfo>> for &'a SeriesInfo {
     }
 }
 
-/// A sequel to a series.
+/// Options to narrow down and page through the results of searching for series by name.
 #[derive(Clone, Debug)]
+pub struct SearchOptions {
+    /// The page of results to return, starting from 1.
+    pub page: u32,
+    /// The maximum number of results on each page.
+    pub per_page: u32,
+    /// Only return series of this kind.
+    pub kind: Option<SeriesKind>,
+    /// Only return series that started airing in this year.
+    pub season_year: Option<u32>,
+    /// Only return series with this airing status.
+    pub status: Option<AiringStatus>,
+}
+
+impl SearchOptions {
+    /// The number of results on each page by default.
+    pub const DEFAULT_PER_PAGE: u32 = 30;
+
+    /// Create a new `SearchOptions` for the first page of results without any filters.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            page: 1,
+            per_page: Self::DEFAULT_PER_PAGE,
+            kind: None,
+            season_year: None,
+            status: None,
+        }
+    }
+
+    #[must_use]
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = page;
+        self
+    }
+
+    #[must_use]
+    pub fn per_page(mut self, per_page: u32) -> Self {
+        self.per_page = per_page;
+        self
+    }
+
+    #[must_use]
+    pub fn kind<K>(mut self, kind: K) -> Self
+    where
+        K: Into<Option<SeriesKind>>,
+    {
+        self.kind = kind.into();
+        self
+    }
+
+    #[must_use]
+    pub fn season_year<Y>(mut self, season_year: Y) -> Self
+    where
+        Y: Into<Option<u32>>,
+    {
+        self.season_year = season_year.into();
+        self
+    }
+
+    #[must_use]
+    pub fn status<S>(mut self, status: S) -> Self
+    where
+        S: Into<Option<AiringStatus>>,
+    {
+        self.status = status.into();
+        self
+    }
+
+    /// Returns the same options for the page after the current one.
+    #[must_use]
+    pub fn next_page(&self) -> Self {
+        self.clone().page(self.page + 1)
+    }
+
+    /// Returns true if `info` passes every filter of the options.
+    ///
+    /// Series that don't provide a field being filtered on are left out, as they can't be confirmed to match.
+    #[must_use]
+    pub fn matches(&self, info: &SeriesInfo) -> bool {
+        (self.kind.is_none() || self.kind == Some(info.kind))
+            && (self.season_year.is_none() || info.year == self.season_year)
+            && (self.status.is_none() || info.status == self.status)
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single page of results from searching for series by name.
+#[derive(Debug, Default)]
+pub struct SearchPage {
+    /// The series found on the page.
+    pub results: Vec<SeriesInfo>,
+    /// Indicates whether or not there are more results on the next page.
+    pub has_next_page: bool,
+}
+
+/// The airing status of an anime series.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "diesel-support",
+    derive(AsExpression, FromSqlRow),
+    sql_type = "SmallInt"
+)]
+pub enum AiringStatus {
+    Finished,
+    Airing,
+    NotYetAired,
+    Cancelled,
+    Hiatus,
+}
+
+impl Into<&'static str> for AiringStatus {
+    fn into(self) -> &'static str {
+        match self {
+            Self::Finished => "Finished",
+            Self::Airing => "Airing",
+            Self::NotYetAired => "Not Yet Aired",
+            Self::Cancelled => "Cancelled",
+            Self::Hiatus => "Hiatus",
+        }
+    }
+}
+
+#[cfg(feature = "diesel-support")]
+impl<DB> FromSql<SmallInt, DB> for AiringStatus
+where
+    DB: diesel::backend::Backend,
+    i16: FromSql<SmallInt, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        match i16::from_sql(bytes)? {
+            1 => Ok(AiringStatus::Finished),
+            2 => Ok(AiringStatus::Airing),
+            3 => Ok(AiringStatus::NotYetAired),
+            4 => Ok(AiringStatus::Cancelled),
+            5 => Ok(AiringStatus::Hiatus),
+            other => Err(format!("invalid airing status: {}", other).into()),
+        }
+    }
+}
+
+#[cfg(feature = "diesel-support")]
+impl<DB> ToSql<SmallInt, DB> for AiringStatus
+where
+    DB: diesel::backend::Backend,
+    i16: ToSql<SmallInt, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        let value = match self {
+            AiringStatus::Finished => 1,
+            AiringStatus::Airing => 2,
+            AiringStatus::NotYetAired => 3,
+            AiringStatus::Cancelled => 4,
+            AiringStatus::Hiatus => 5,
+        };
+
+        value.to_sql(out)
+    }
+}
+
+/// The season of the year a series started airing in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "diesel-support",
+    derive(AsExpression, FromSqlRow),
+    sql_type = "SmallInt"
+)]
+pub enum Season {
+    Winter,
+    Spring,
+    Summer,
+    Fall,
+}
+
+impl Season {
+    /// Returns the season that `month` falls in, where January is 1.
+    #[must_use]
+    pub fn from_month(month: u8) -> Option<Self> {
+        match month {
+            1..=3 => Some(Self::Winter),
+            4..=6 => Some(Self::Spring),
+            7..=9 => Some(Self::Summer),
+            10..=12 => Some(Self::Fall),
+            _ => None,
+        }
+    }
+}
+
+impl Into<&'static str> for Season {
+    fn into(self) -> &'static str {
+        match self {
+            Self::Winter => "Winter",
+            Self::Spring => "Spring",
+            Self::Summer => "Summer",
+            Self::Fall => "Fall",
+        }
+    }
+}
+
+#[cfg(feature = "diesel-support")]
+impl<DB> FromSql<SmallInt, DB> for Season
+where
+    DB: diesel::backend::Backend,
+    i16: FromSql<SmallInt, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        match i16::from_sql(bytes)? {
+            1 => Ok(Season::Winter),
+            2 => Ok(Season::Spring),
+            3 => Ok(Season::Summer),
+            4 => Ok(Season::Fall),
+            other => Err(format!("invalid season: {}", other).into()),
+        }
+    }
+}
+
+#[cfg(feature = "diesel-support")]
+impl<DB> ToSql<SmallInt, DB> for Season
+where
+    DB: diesel::backend::Backend,
+    i16: ToSql<SmallInt, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        let value = match self {
+            Season::Winter => 1,
+            Season::Spring => 2,
+            Season::Summer => 3,
+            Season::Fall => 4,
+        };
+
+        value.to_sql(out)
+    }
+}
+
+/// The next episode to air of a series that is currently airing.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct NextEpisode {
+    /// The number of the episode.
+    pub number: u32,
+    /// The time the episode airs at, in seconds since the Unix epoch.
+    pub airs_at: i64,
+}
+
+/// An episode of a series that is scheduled to air.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct AiringEpisode {
+    /// The ID of the series the episode belongs to.
+    pub id: SeriesID,
+    /// The number of the episode.
+    pub episode: u32,
+    /// The time the episode airs at, in seconds since the Unix epoch.
+    pub airs_at: i64,
+}
+
+/// A sequel to a series.
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Sequel {
     /// The kind of sequel this is.
     pub kind: SeriesKind,
@@ -173,16 +693,22 @@ impl Sequel {
 }
 
 /// Various title formats for an anime series.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SeriesTitle {
     /// The title in romaji.
     pub romaji: String,
     /// The title in the user's preferred format.
     pub preferred: String,
+    /// The official English title, if there is one.
+    #[serde(default)]
+    pub english: Option<String>,
+    /// The title in its native language.
+    #[serde(default)]
+    pub native: Option<String>,
 }
 
 /// A list entry for an anime series.
-#[derive(Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SeriesEntry {
     /// The ID of the anime.
     pub id: u32,
@@ -198,6 +724,22 @@ pub struct SeriesEntry {
     pub start_date: Option<SeriesDate>,
     /// The date the user finished watching the series.
     pub end_date: Option<SeriesDate>,
+    /// The user's notes on the series.
+    pub notes: Option<String>,
+    /// Indicates whether or not the entry is only visible to the user.
+    pub private: bool,
+    /// Indicates whether or not the entry is hidden from the user's status lists.
+    pub hidden: bool,
+    /// The names of the user's custom lists that the entry is on.
+    pub custom_lists: Vec<String>,
+    /// The time the entry was last updated on the remote service, in seconds since the Unix epoch.
+    ///
+    /// This is only known for entries retrieved from services that report it.
+    pub updated_at: Option<i64>,
+    /// Indicates whether or not the user has marked the series as a favourite.
+    ///
+    /// Changes to this are not sent with `update_list_entry`. Use `set_favourite` instead.
+    pub favourite: bool,
 }
 
 impl SeriesEntry {
@@ -213,12 +755,36 @@ impl SeriesEntry {
             times_rewatched: 0,
             start_date: None,
             end_date: None,
+            notes: None,
+            private: false,
+            hidden: false,
+            custom_lists: Vec::new(),
+            updated_at: None,
+            favourite: false,
         }
     }
 }
 
+/// A series recommended to people who liked another series.
+#[derive(Clone, Debug)]
+pub struct Recommendation {
+    /// The information of the recommended series.
+    pub info: SeriesInfo,
+    /// The number of users that agree with the recommendation, minus the number that don't.
+    pub rating: i32,
+}
+
+/// An entry on a user's anime list, along with the series it belongs to.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UserListEntry {
+    /// The information of the series.
+    pub info: SeriesInfo,
+    /// The user's list entry for the series.
+    pub entry: SeriesEntry,
+}
+
 /// The watch status of an anime series.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 #[cfg_attr(
     feature = "diesel-support",
     derive(AsExpression, FromSqlRow),
@@ -298,7 +864,7 @@ where
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(
     feature = "diesel-support",
     derive(AsExpression, FromSqlRow),
@@ -319,6 +885,71 @@ impl SeriesDate {
     }
 }
 
+/// Parse an ISO 8601 timestamp, such as `2021-03-04T05:06:07.890Z` or `2021-03-04T05:06:07+09:00`, into seconds
+/// since the Unix epoch.
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let mut date = timestamp.get(..10)?.split('-').map(str::parse::<i64>);
+    let mut time = timestamp.get(11..19)?.split(':').map(str::parse::<i64>);
+
+    let year = date.next()?.ok()?;
+    let month = date.next()?.ok()?;
+    let day = date.next()?.ok()?;
+
+    let hour = time.next()?.ok()?;
+    let minute = time.next()?.ok()?;
+    let second = time.next()?.ok()?;
+
+    // Fractional seconds come before the UTC offset and aren't needed
+    let zone = timestamp[19..].trim_start_matches(|ch: char| ch == '.' || ch.is_ascii_digit());
+
+    let offset = match zone {
+        "" | "Z" => 0,
+        _ => {
+            let sign = match zone.get(..1)? {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+
+            let hours = zone.get(1..3)?.parse::<i64>().ok()?;
+
+            // The offset can be written as +hh, +hhmm, or +hh:mm
+            let minutes = match zone.get(3..)? {
+                "" => 0,
+                minutes => {
+                    let minutes = minutes.strip_prefix(':').unwrap_or(minutes);
+
+                    if minutes.len() != 2 {
+                        return None;
+                    }
+
+                    minutes.parse::<i64>().ok()?
+                }
+            };
+
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+
+    let days = days_from_civil(year, month, day);
+
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+/// Returns the number of days from the Unix epoch to the specified date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil> for how this works.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
 #[cfg(feature = "diesel-support")]
 impl<DB> FromSql<Date, DB> for SeriesDate
 where
@@ -430,3 +1061,80 @@ impl fmt::Debug for AccessToken {
         write!(f, "AccessToken {{}}")
     }
 }
+
+/// An access token granted by a remote service, along with what is needed to refresh it.
+#[derive(Clone, Debug)]
+pub struct TokenGrant {
+    /// The token to make requests with.
+    pub token: AccessToken,
+    /// The token to get a new access token with once the current one expires, if the service issued one.
+    pub refresh_token: Option<AccessToken>,
+    /// The number of seconds until the access token expires, if the service reports it.
+    pub expires_in: Option<i64>,
+}
+
+impl From<AccessToken> for TokenGrant {
+    fn from(token: AccessToken) -> Self {
+        Self {
+            token,
+            refresh_token: None,
+            expires_in: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_are_parsed() {
+        let timestamps = [
+            ("1970-01-01T00:00:00Z", Some(0)),
+            ("2017-11-11T19:51:22+00:00", Some(1_510_429_882)),
+            ("2017-08-08T12:39:19.217Z", Some(1_502_195_959)),
+            ("2021-03-04T05:06:07+09:00", Some(1_614_801_967)),
+            ("2021-03-04T05:06:07+0900", Some(1_614_801_967)),
+            ("2021-03-04T05:06:07+09", Some(1_614_801_967)),
+            ("2021-03-04T05:06:07-05:30", Some(1_614_854_167)),
+            ("2021-03-04T05:06:07Z", Some(1_614_834_367)),
+            ("2021-03-04", None),
+        ];
+
+        for (timestamp, expected) in &timestamps {
+            assert_eq!(
+                parse_timestamp(timestamp),
+                *expected,
+                "parsing {}",
+                timestamp
+            );
+        }
+    }
+
+    #[test]
+    fn search_options_filter_series() {
+        let info = SeriesInfo {
+            year: Some(2019),
+            status: Some(AiringStatus::Finished),
+            ..SeriesInfo::with_title(1, "Kaguya-sama: Love is War")
+        };
+
+        assert!(SearchOptions::new().matches(&info));
+        assert!(SearchOptions::new()
+            .kind(SeriesKind::Season)
+            .season_year(2019)
+            .status(AiringStatus::Finished)
+            .matches(&info));
+
+        assert!(!SearchOptions::new().kind(SeriesKind::Movie).matches(&info));
+        assert!(!SearchOptions::new().season_year(2020).matches(&info));
+        assert!(!SearchOptions::new()
+            .status(AiringStatus::Airing)
+            .matches(&info));
+
+        let unknown_year = SeriesInfo::with_title(2, "Kaguya-sama: Love is War?");
+        assert!(!SearchOptions::new()
+            .season_year(2019)
+            .matches(&unknown_year));
+    }
+}
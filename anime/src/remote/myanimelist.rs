@@ -0,0 +1,1069 @@
+#![allow(clippy::doc_markdown)]
+
+use super::transport::{Client, Method, Request};
+use super::{
+    parse_timestamp, AccessToken, AiringStatus, RemoteService, ScoreParser, Season, Sequel,
+    SeriesDate, SeriesEntry, SeriesID, SeriesInfo, SeriesKind, SeriesTitle, Status, TokenGrant,
+    UserListEntry,
+};
+use crate::err::{Error, Result};
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+use serde_json as json;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::result;
+use url::form_urlencoded;
+
+/// The URL to the API endpoint.
+pub const API_URL: &str = "https://api.myanimelist.net/v2";
+
+/// The URL to the OAuth2 endpoint.
+pub const OAUTH_URL: &str = "https://myanimelist.net/v1/oauth2";
+
+/// The fields to request when retrieving series info.
+const INFO_FIELDS: &str =
+    "id,title,alternative_titles,num_episodes,average_episode_duration,media_type,start_season,status,genres,studios,mean,nsfw,related_anime{node{media_type}}";
+
+/// The fields to request when retrieving a list entry.
+const LIST_ENTRY_FIELDS: &str = "my_list_status{num_times_rewatched,updated_at}";
+
+/// Returns the URL that the user needs to go to in order to authenticate their account
+/// so the API can make changes to it.
+///
+/// `client_id` is the ID of the application you wish to use the API with.
+/// It can be retrieved from the `API` section of your account settings.
+///
+/// `challenge` must be kept until the user has been redirected back with an authorization code,
+/// as it is needed to exchange the code for an access token with `Auth::exchange_code`.
+///
+/// `redirect_url` only needs to be specified when the application has more than one redirect URL registered.
+/// The same one must then be used to exchange the code.
+#[inline]
+#[must_use]
+pub fn auth_url(client_id: &str, challenge: &PkceChallenge, redirect_url: Option<&str>) -> String {
+    let mut url = format!(
+        "{}/authorize?response_type=code&client_id={}&code_challenge={}&code_challenge_method=plain",
+        OAUTH_URL, client_id, challenge.verifier
+    );
+
+    if let Some(redirect_url) = redirect_url {
+        let encoded = form_urlencoded::Serializer::new(String::new())
+            .append_pair("redirect_uri", redirect_url)
+            .finish();
+
+        url.push('&');
+        url.push_str(&encoded);
+    }
+
+    url
+}
+
+/// A PKCE code verifier used to authenticate a user with OAuth2.
+///
+/// MyAnimeList only supports the `plain` challenge method, so the code challenge
+/// sent with the auth URL is the same as the verifier.
+#[derive(Clone)]
+pub struct PkceChallenge {
+    verifier: String,
+}
+
+impl PkceChallenge {
+    /// The number of characters to use for the code verifier.
+    ///
+    /// This is the maximum length allowed by the OAuth2 PKCE specification.
+    const LEN: usize = 128;
+
+    /// Generate a new random code verifier.
+    #[must_use]
+    pub fn new() -> Self {
+        const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
+
+        let mut rng = rand::thread_rng();
+
+        let verifier = (0..Self::LEN)
+            .map(|_| CHARS[rng.gen_range(0..CHARS.len())] as char)
+            .collect();
+
+        Self { verifier }
+    }
+}
+
+impl Default for PkceChallenge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A connection to the MyAnimeList API.
+#[derive(Debug)]
+pub struct MyAnimeList {
+    client: Client,
+    client_id: String,
+    auth: Option<Auth>,
+}
+
+impl MyAnimeList {
+    /// Create a new `MyAnimeList` connection without any authentication.
+    ///
+    /// In this mode, you can only search for series info by name and by ID.
+    /// Trying to make any other request will return a `NeedAuthentication` error.
+    #[must_use]
+    pub fn unauthenticated<S>(client_id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            client: Client::default(),
+            client_id: client_id.into(),
+            auth: None,
+        }
+    }
+
+    /// Create a new `MyAnimeList` connection with authentication.
+    ///
+    /// This mode will allow you to update the specified user's list.
+    #[must_use]
+    pub fn authenticated<S>(client_id: S, auth: Auth) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            auth: Some(auth),
+            ..Self::unauthenticated(client_id)
+        }
+    }
+
+    /// Send all requests through the specified `client`.
+    #[must_use]
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Returns the current user's authentication, if there is one.
+    #[inline(always)]
+    #[must_use]
+    pub fn auth(&self) -> Option<&Auth> {
+        self.auth.as_ref()
+    }
+
+    fn auth_token(&self) -> Result<&AccessToken> {
+        self.auth
+            .as_ref()
+            .map(|auth| &auth.token)
+            .ok_or(Error::NeedAuthentication)
+    }
+
+    fn request(&self, method: Method, path: &str, token: Option<&AccessToken>) -> Result<Request> {
+        let url = format!("{}/{}", self.client.base_url(API_URL), path);
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("X-MAL-CLIENT-ID", self.client_id.as_str());
+
+        if let Some(token) = token {
+            request = request.bearer_auth(&token.decode()?);
+        }
+
+        Ok(request)
+    }
+}
+
+impl RemoteService for MyAnimeList {
+    fn search_info_by_name(&self, name: &str) -> Result<Vec<SeriesInfo>> {
+        let request = self
+            .request(Method::Get, "anime", self.auth_token().ok())?
+            .param("q", name)
+            .param("limit", 30)
+            .param("fields", INFO_FIELDS);
+
+        let page: Page<AnimeNode> = json::from_value(send_request(&self.client, request)?)?;
+
+        let entries = page
+            .data
+            .into_iter()
+            .filter_map(|entry| entry.node.try_into().ok())
+            .collect();
+
+        Ok(entries)
+    }
+
+    fn search_info_by_id(&self, id: SeriesID) -> Result<SeriesInfo> {
+        let request = self
+            .request(
+                Method::Get,
+                &format!("anime/{}", id),
+                self.auth_token().ok(),
+            )?
+            .param("fields", INFO_FIELDS);
+
+        let anime: Anime = json::from_value(send_request(&self.client, request)?)?;
+        anime.try_into().map_err(|_| Error::NotAnAnime)
+    }
+
+    fn get_list_entry(&self, id: SeriesID) -> Result<Option<SeriesEntry>> {
+        let token = self.auth_token()?;
+
+        let request = self
+            .request(Method::Get, &format!("anime/{}", id), Some(token))?
+            .param("fields", LIST_ENTRY_FIELDS);
+
+        let anime: AnimeListStatus = json::from_value(send_request(&self.client, request)?)?;
+
+        Ok(anime
+            .my_list_status
+            .map(|status| status.into_series_entry(id)))
+    }
+
+    fn get_user_list(&self) -> Result<Vec<UserListEntry>> {
+        // The largest page size MyAnimeList allows
+        const PAGE_SIZE: usize = 1000;
+
+        let token = self.auth_token()?;
+        let fields = format!(
+            "list_status{{num_times_rewatched,updated_at}},{}",
+            INFO_FIELDS
+        );
+        let mut results = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let request = self
+                .request(Method::Get, "users/@me/animelist", Some(token))?
+                .param("fields", &fields)
+                .param("limit", PAGE_SIZE)
+                .param("offset", offset)
+                .param("nsfw", true);
+
+            let page: ListPage = json::from_value(send_request(&self.client, request)?)?;
+            let has_next_page = page.paging.next.is_some();
+
+            // Entries that aren't anime are skipped below, so they can't be used to tell where the next page starts
+            offset += page.data.len();
+
+            results.extend(page.data.into_iter().filter_map(|entry| {
+                let id = entry.node.id;
+                let info = entry.node.try_into().ok()?;
+
+                Some(UserListEntry {
+                    info,
+                    entry: entry.list_status.into_series_entry(id),
+                })
+            }));
+
+            if !has_next_page {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn get_mal_ids(&self, ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        Ok(ids.iter().map(|&id| (id, id)).collect())
+    }
+
+    fn find_by_mal_ids(&self, mal_ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        Ok(mal_ids.iter().map(|&id| (id, id)).collect())
+    }
+
+    fn update_list_entry(&self, entry: &SeriesEntry) -> Result<Option<i64>> {
+        let token = self.auth_token()?;
+        let status = ListStatusUpdate::from(entry);
+
+        let request = self
+            .request(
+                Method::Patch,
+                &format!("anime/{}/my_list_status", entry.id),
+                Some(token),
+            )?
+            .form(&status)?;
+
+        // The updated list status is sent back
+        let updated = send_request(&self.client, request)?;
+
+        Ok(updated["updated_at"].as_str().and_then(parse_timestamp))
+    }
+
+    fn delete_list_entry(&self, id: SeriesID) -> Result<()> {
+        let token = self.auth_token()?;
+
+        let request = self.request(
+            Method::Delete,
+            &format!("anime/{}/my_list_status", id),
+            Some(token),
+        )?;
+
+        match send_request(&self.client, request) {
+            Ok(_) => Ok(()),
+            // The anime isn't on the user's list
+            Err(ref err) if err.is_http_code(404) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl ScoreParser for MyAnimeList {
+    fn parse_score(&self, score: &str) -> Option<u8> {
+        score
+            .parse::<u8>()
+            .ok()
+            .and_then(|score| (score <= 10).then(|| score * 10))
+    }
+
+    fn score_to_str(&self, score: u8) -> Cow<str> {
+        (score / 10).to_string().into()
+    }
+}
+
+/// An authenticated user.
+#[derive(Debug)]
+pub struct Auth {
+    /// The MyAnimeList user's account information.
+    pub user: User,
+    token: AccessToken,
+}
+
+impl Auth {
+    #[inline(always)]
+    #[must_use]
+    pub fn new(user: User, token: AccessToken) -> Self {
+        Self { user, token }
+    }
+
+    /// Retrieve the current authorization from MyAnimeList using the specified `token`.
+    #[inline(always)]
+    pub fn retrieve(client_id: &str, token: AccessToken) -> Result<Self> {
+        Self::retrieve_with(&Client::default(), client_id, token)
+    }
+
+    /// Retrieve the current authorization from MyAnimeList using the specified `token`, sending the request through `client`.
+    pub fn retrieve_with(client: &Client, client_id: &str, token: AccessToken) -> Result<Self> {
+        let url = format!("{}/users/@me", client.base_url(API_URL));
+
+        let request = client
+            .request(Method::Get, url)
+            .header("X-MAL-CLIENT-ID", client_id)
+            .bearer_auth(&token.decode()?);
+
+        let user = json::from_value(send_request(client, request)?)?;
+        Ok(Self::new(user, token))
+    }
+
+    /// Exchange the authorization `code` the user was redirected with for an access token.
+    ///
+    /// `challenge` and `redirect_url` must be the same ones that were used to generate the auth URL.
+    #[inline(always)]
+    pub fn exchange_code(
+        client_id: &str,
+        code: &str,
+        challenge: &PkceChallenge,
+        redirect_url: Option<&str>,
+    ) -> Result<TokenGrant> {
+        Self::exchange_code_with(&Client::default(), client_id, code, challenge, redirect_url)
+    }
+
+    /// Exchange the authorization `code` the user was redirected with for an access token, sending the request through `client`.
+    ///
+    /// `challenge` and `redirect_url` must be the same ones that were used to generate the auth URL.
+    pub fn exchange_code_with(
+        client: &Client,
+        client_id: &str,
+        code: &str,
+        challenge: &PkceChallenge,
+        redirect_url: Option<&str>,
+    ) -> Result<TokenGrant> {
+        let mut params = vec![
+            ("client_id", client_id),
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("code_verifier", challenge.verifier.as_str()),
+        ];
+
+        if let Some(redirect_url) = redirect_url {
+            params.push(("redirect_uri", redirect_url));
+        }
+
+        request_token(client, &params)
+    }
+
+    /// Get a new access token with the `refresh_token` that was granted along with the last one.
+    ///
+    /// MyAnimeList access tokens expire after about a month, so this should be done before then.
+    #[inline(always)]
+    pub fn refresh(client_id: &str, refresh_token: &AccessToken) -> Result<TokenGrant> {
+        Self::refresh_with(&Client::default(), client_id, refresh_token)
+    }
+
+    /// Get a new access token with the `refresh_token` that was granted along with the last one, sending the request through `client`.
+    pub fn refresh_with(
+        client: &Client,
+        client_id: &str,
+        refresh_token: &AccessToken,
+    ) -> Result<TokenGrant> {
+        let refresh_token = refresh_token.decode()?;
+
+        let params = [
+            ("client_id", client_id),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ];
+
+        request_token(client, &params)
+    }
+}
+
+fn request_token(client: &Client, params: &[(&str, &str)]) -> Result<TokenGrant> {
+    let request = client
+        .request(
+            Method::Post,
+            format!("{}/token", client.base_url(OAUTH_URL)),
+        )
+        .form(&params)?;
+
+    let token: TokenResponse = json::from_value(send_request(client, request)?)?;
+    Ok(token.into())
+}
+
+/// A MyAnimeList user.
+#[derive(Debug, Deserialize)]
+pub struct User {
+    /// The user's account ID.
+    pub id: u32,
+    /// The user's account name.
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+impl From<TokenResponse> for TokenGrant {
+    fn from(response: TokenResponse) -> Self {
+        Self {
+            token: AccessToken::encode(response.access_token),
+            refresh_token: response.refresh_token.map(AccessToken::encode),
+            expires_in: response.expires_in,
+        }
+    }
+}
+
+fn send_request(client: &Client, request: Request) -> Result<json::Value> {
+    if cfg!(debug_assertions) && cfg!(feature = "print-requests-debug") {
+        println!("DEBUG: MyAnimeList request: {}", request.url);
+    }
+
+    let response = client.send(request)?;
+
+    // Deletions are responded to without a body
+    let json: json::Value = if response.body.is_empty() {
+        json::Value::Null
+    } else {
+        response.json()?
+    };
+
+    if !response.is_success() {
+        let message = json["message"]
+            .as_str()
+            .or_else(|| json["error"].as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        return Err(Error::BadMyAnimeListResponse {
+            code: response.status,
+            message,
+        });
+    }
+
+    Ok(json)
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    data: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnimeNode {
+    node: Anime,
+}
+
+#[derive(Debug, Deserialize)]
+struct Anime {
+    id: u32,
+    title: String,
+    alternative_titles: Option<AlternativeTitles>,
+    num_episodes: Option<u32>,
+    /// The average length of an episode in seconds.
+    average_episode_duration: Option<u32>,
+    media_type: Option<MediaType>,
+    start_season: Option<StartSeason>,
+    status: Option<AnimeStatus>,
+    #[serde(default)]
+    genres: Vec<Named>,
+    #[serde(default)]
+    studios: Vec<Named>,
+    /// The average score given by users, out of 10.
+    mean: Option<f32>,
+    nsfw: Option<String>,
+    #[serde(default)]
+    related_anime: Vec<RelatedAnime>,
+}
+
+impl Anime {
+    fn sequels(&self) -> Vec<Sequel> {
+        self.related_anime
+            .iter()
+            .filter_map(|related| related.try_into().ok())
+            .collect()
+    }
+}
+
+impl TryInto<SeriesInfo> for Anime {
+    type Error = ();
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn try_into(self) -> result::Result<SeriesInfo, Self::Error> {
+        let kind = self.media_type.ok_or(())?.try_into()?;
+        let sequels = self.sequels();
+        let title = self.title;
+        let alt_titles = self.alternative_titles.unwrap_or_default();
+
+        let english = alt_titles.en.filter(|title| !title.is_empty());
+        let preferred = english.clone().unwrap_or_else(|| title.clone());
+
+        let episode_length = self
+            .average_episode_duration
+            .filter(|&secs| secs > 0)
+            .map_or(24, |secs| (secs / 60).max(1));
+
+        Ok(SeriesInfo {
+            id: self.id,
+            title: SeriesTitle {
+                romaji: title,
+                preferred,
+                english,
+                native: alt_titles.ja.filter(|title| !title.is_empty()),
+            },
+            episodes: self.num_episodes.unwrap_or(0),
+            episode_length,
+            kind,
+            sequels,
+            relations: Vec::new(),
+            synonyms: alt_titles.synonyms,
+            season: self.start_season.as_ref().map(|start| start.season.into()),
+            year: self.start_season.map(|start| start.year),
+            status: self.status.map(Into::into),
+            genres: self.genres.into_iter().map(|genre| genre.name).collect(),
+            studios: self.studios.into_iter().map(|studio| studio.name).collect(),
+            average_score: self.mean.map(|mean| (mean * 10.0).round() as u8),
+            // MyAnimeList marks explicit series as "black"
+            is_adult: self.nsfw.as_deref() == Some("black"),
+            next_episode: None,
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AlternativeTitles {
+    en: Option<String>,
+    ja: Option<String>,
+    #[serde(default)]
+    synonyms: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StartSeason {
+    year: u32,
+    season: AnimeSeason,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum AnimeSeason {
+    Winter,
+    Spring,
+    Summer,
+    Fall,
+}
+
+impl Into<Season> for AnimeSeason {
+    fn into(self) -> Season {
+        match self {
+            Self::Winter => Season::Winter,
+            Self::Spring => Season::Spring,
+            Self::Summer => Season::Summer,
+            Self::Fall => Season::Fall,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AnimeStatus {
+    FinishedAiring,
+    CurrentlyAiring,
+    NotYetAired,
+}
+
+impl Into<AiringStatus> for AnimeStatus {
+    fn into(self) -> AiringStatus {
+        match self {
+            Self::FinishedAiring => AiringStatus::Finished,
+            Self::CurrentlyAiring => AiringStatus::Airing,
+            Self::NotYetAired => AiringStatus::NotYetAired,
+        }
+    }
+}
+
+/// A genre or studio.
+#[derive(Debug, Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelatedAnime {
+    node: RelatedNode,
+    relation_type: RelationType,
+}
+
+impl TryInto<Sequel> for &RelatedAnime {
+    type Error = ();
+
+    fn try_into(self) -> result::Result<Sequel, Self::Error> {
+        // It doesn't make sense to consider this relation a sequel
+        // if its a prequel, alternative, or character relation
+        if !self.relation_type.is_sequential() {
+            return Err(());
+        }
+
+        let kind = self.node.media_type.ok_or(())?.try_into()?;
+
+        Ok(Sequel::new(kind, self.node.id))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RelatedNode {
+    id: u32,
+    media_type: Option<MediaType>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RelationType {
+    Sequel,
+    SideStory,
+    Other,
+    #[serde(other)]
+    Unknown,
+}
+
+impl RelationType {
+    /// Returns true if the relation is considered to be some kind of sequel. Ex: a second season, OVA, ONA, movie, etc
+    fn is_sequential(self) -> bool {
+        match self {
+            Self::Sequel | Self::SideStory | Self::Other => true,
+            Self::Unknown => false,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum MediaType {
+    TV,
+    OVA,
+    Movie,
+    Special,
+    ONA,
+    Music,
+    #[serde(other)]
+    Unknown,
+}
+
+impl TryInto<SeriesKind> for MediaType {
+    type Error = ();
+
+    fn try_into(self) -> result::Result<SeriesKind, Self::Error> {
+        match self {
+            Self::TV => Ok(SeriesKind::Season),
+            Self::Movie => Ok(SeriesKind::Movie),
+            Self::Special => Ok(SeriesKind::Special),
+            Self::OVA => Ok(SeriesKind::OVA),
+            Self::ONA => Ok(SeriesKind::ONA),
+            Self::Music => Ok(SeriesKind::Music),
+            Self::Unknown => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ListPage {
+    data: Vec<ListNode>,
+    #[serde(default)]
+    paging: Paging,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Paging {
+    /// The URL of the next page, which is only present when there is one.
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListNode {
+    node: Anime,
+    list_status: ListStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnimeListStatus {
+    my_list_status: Option<ListStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListStatus {
+    status: ListStatusKind,
+    score: u8,
+    num_episodes_watched: u32,
+    is_rewatching: bool,
+    #[serde(default)]
+    num_times_rewatched: u32,
+    start_date: Option<String>,
+    finish_date: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
+impl ListStatus {
+    fn into_series_entry(self, id: u32) -> SeriesEntry {
+        let score = (self.score > 0).then(|| self.score.min(10) * 10);
+
+        let status = if self.is_rewatching {
+            Status::Rewatching
+        } else {
+            self.status.into()
+        };
+
+        SeriesEntry {
+            id,
+            watched_eps: self.num_episodes_watched,
+            score,
+            status,
+            times_rewatched: self.num_times_rewatched,
+            start_date: self.start_date.as_deref().and_then(parse_date),
+            end_date: self.finish_date.as_deref().and_then(parse_date),
+            updated_at: self.updated_at.as_deref().and_then(parse_timestamp),
+            ..SeriesEntry::new(id)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ListStatusKind {
+    Watching,
+    Completed,
+    OnHold,
+    Dropped,
+    PlanToWatch,
+}
+
+impl Into<Status> for ListStatusKind {
+    fn into(self) -> Status {
+        match self {
+            Self::Watching => Status::Watching,
+            Self::Completed => Status::Completed,
+            Self::OnHold => Status::OnHold,
+            Self::Dropped => Status::Dropped,
+            Self::PlanToWatch => Status::PlanToWatch,
+        }
+    }
+}
+
+impl From<Status> for ListStatusKind {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Watching => Self::Watching,
+            // MyAnimeList represents a rewatch as a completed entry with a rewatching flag
+            Status::Completed | Status::Rewatching => Self::Completed,
+            Status::OnHold => Self::OnHold,
+            Status::Dropped => Self::Dropped,
+            Status::PlanToWatch => Self::PlanToWatch,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ListStatusUpdate {
+    status: ListStatusKind,
+    is_rewatching: bool,
+    score: u8,
+    num_watched_episodes: u32,
+    num_times_rewatched: u32,
+    /// An empty date clears the one set on MyAnimeList.
+    start_date: String,
+    finish_date: String,
+}
+
+impl From<&SeriesEntry> for ListStatusUpdate {
+    fn from(entry: &SeriesEntry) -> Self {
+        let format_date = |date: Option<SeriesDate>| {
+            date.map_or_else(String::new, |date| {
+                format!("{:04}-{:02}-{:02}", date.year, date.month, date.day)
+            })
+        };
+
+        Self {
+            status: entry.status.into(),
+            is_rewatching: entry.status == Status::Rewatching,
+            score: entry.score.map_or(0, |score| score / 10),
+            num_watched_episodes: entry.watched_eps,
+            num_times_rewatched: entry.times_rewatched,
+            start_date: format_date(entry.start_date),
+            finish_date: format_date(entry.end_date),
+        }
+    }
+}
+
+/// Parse a date in the form of `YYYY-MM-DD`.
+///
+/// MyAnimeList can also return partial dates (such as `YYYY-MM`), which will return None.
+fn parse_date(date: &str) -> Option<SeriesDate> {
+    let mut fragments = date.split('-');
+
+    let year = fragments.next()?.parse().ok()?;
+    let month = fragments.next()?.parse().ok()?;
+    let day = fragments.next()?.parse().ok()?;
+
+    Some(SeriesDate::from_ymd(year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::test_server::{TestResponse, TestServer};
+
+    const ANIME_JSON: &str = r#"{
+        "id": 30,
+        "title": "Shinseiki Evangelion",
+        "alternative_titles": { "en": "Neon Genesis Evangelion" },
+        "num_episodes": 26,
+        "average_episode_duration": 1440,
+        "media_type": "tv",
+        "related_anime": [
+            { "node": { "id": 32, "media_type": "movie" }, "relation_type": "sequel" },
+            { "node": { "id": 33, "media_type": "movie" }, "relation_type": "alternative_version" }
+        ]
+    }"#;
+
+    const LIST_STATUS_JSON: &str = r#"{
+        "id": 30,
+        "my_list_status": {
+            "status": "completed",
+            "score": 8,
+            "num_episodes_watched": 3,
+            "is_rewatching": true,
+            "num_times_rewatched": 1,
+            "start_date": "2021-03-05",
+            "finish_date": "2021-03",
+            "updated_at": "2017-11-11T19:51:22+00:00"
+        }
+    }"#;
+
+    fn test_auth() -> Auth {
+        let user = User {
+            id: 1,
+            name: "test".into(),
+        };
+
+        Auth::new(user, AccessToken::encode("token"))
+    }
+
+    #[test]
+    fn info_from_local_server() {
+        let server = TestServer::spawn(vec![ANIME_JSON.into()]);
+        let mal = MyAnimeList::unauthenticated("client").with_client(server.client());
+
+        let info = mal.search_info_by_id(30).unwrap();
+
+        assert_eq!(info.id, 30);
+        assert_eq!(info.title.romaji, "Shinseiki Evangelion");
+        assert_eq!(info.title.preferred, "Neon Genesis Evangelion");
+        assert_eq!(info.episodes, 26);
+        assert_eq!(info.episode_length, 24);
+        assert_eq!(info.kind, SeriesKind::Season);
+        assert_eq!(info.sequels.len(), 1);
+        assert_eq!(info.sequels[0].id, 32);
+
+        let request = server.requests().remove(0);
+        assert!(request.starts_with("GET /anime/30?"));
+        assert!(request.contains("x-mal-client-id: client"));
+    }
+
+    #[test]
+    fn list_entry_from_local_server() {
+        let server = TestServer::spawn(vec![LIST_STATUS_JSON.into()]);
+        let mal = MyAnimeList::authenticated("client", test_auth()).with_client(server.client());
+
+        let entry = mal.get_list_entry(30).unwrap().unwrap();
+
+        assert_eq!(entry.id, 30);
+        assert_eq!(entry.watched_eps, 3);
+        assert_eq!(entry.score, Some(80));
+        assert_eq!(entry.status, Status::Rewatching);
+        assert_eq!(entry.times_rewatched, 1);
+        assert!(entry.start_date.is_some());
+        assert!(entry.end_date.is_none());
+        assert_eq!(entry.updated_at, Some(1_510_429_882));
+    }
+
+    #[test]
+    fn user_list_is_paged() {
+        let first_page = r#"{
+            "data": [{
+                "node": { "id": 30, "title": "Shinseiki Evangelion", "media_type": "tv" },
+                "list_status": { "status": "watching", "score": 0, "num_episodes_watched": 5, "is_rewatching": false }
+            }],
+            "paging": { "next": "https://api.myanimelist.net/v2/users/@me/animelist?offset=1" }
+        }"#;
+
+        let second_page = r#"{
+            "data": [{
+                "node": { "id": 32, "title": "The End of Evangelion", "media_type": "movie" },
+                "list_status": { "status": "completed", "score": 9, "num_episodes_watched": 1, "is_rewatching": false }
+            }],
+            "paging": {}
+        }"#;
+
+        let server = TestServer::spawn(vec![first_page.into(), second_page.into()]);
+        let mal = MyAnimeList::authenticated("client", test_auth()).with_client(server.client());
+
+        let list = mal.get_user_list().unwrap();
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].entry.watched_eps, 5);
+        assert_eq!(list[1].info.kind, SeriesKind::Movie);
+        assert_eq!(list[1].entry.id, 32);
+        assert_eq!(list[1].entry.score, Some(90));
+
+        let requests = server.requests();
+        assert!(requests[0].starts_with("GET /users/@me/animelist?"));
+        assert!(requests[1].contains("offset=1"));
+    }
+
+    #[test]
+    fn auth_url_requests_redirect() {
+        let challenge = PkceChallenge::new();
+        let url = auth_url("client", &challenge, Some("http://localhost:7421/callback"));
+
+        assert!(url.ends_with("&redirect_uri=http%3A%2F%2Flocalhost%3A7421%2Fcallback"));
+        assert!(!auth_url("client", &challenge, None).contains("redirect_uri"));
+    }
+
+    #[test]
+    fn code_is_exchanged() {
+        let server = TestServer::spawn(vec![
+            r#"{ "access_token": "new", "refresh_token": "next", "expires_in": 2678400 }"#.into(),
+        ]);
+
+        let challenge = PkceChallenge::new();
+
+        let grant = Auth::exchange_code_with(
+            &server.client(),
+            "client",
+            "code",
+            &challenge,
+            Some("http://localhost:7421/callback"),
+        )
+        .unwrap();
+
+        assert_eq!(grant.token.decode().unwrap(), "new");
+        assert_eq!(grant.refresh_token.unwrap().decode().unwrap(), "next");
+
+        let verifier = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("code_verifier", &challenge.verifier)
+            .finish();
+
+        let request = server.requests().remove(0);
+        assert!(request.starts_with("POST /token "));
+        assert!(request.contains("grant_type=authorization_code"));
+        assert!(request.contains("code=code"));
+        assert!(request.contains(&verifier));
+        assert!(request.contains("redirect_uri=http%3A%2F%2Flocalhost%3A7421%2Fcallback"));
+    }
+
+    #[test]
+    fn token_is_refreshed() {
+        let server = TestServer::spawn(vec![
+            r#"{ "access_token": "new", "refresh_token": "next", "expires_in": 2678400 }"#.into(),
+        ]);
+
+        let grant =
+            Auth::refresh_with(&server.client(), "client", &AccessToken::encode("old")).unwrap();
+
+        assert_eq!(grant.token.decode().unwrap(), "new");
+        assert_eq!(grant.refresh_token.unwrap().decode().unwrap(), "next");
+        assert_eq!(grant.expires_in, Some(2_678_400));
+
+        let request = server.requests().remove(0);
+        assert!(request.starts_with("POST /token "));
+        assert!(request.contains("grant_type=refresh_token"));
+        assert!(request.contains("refresh_token=old"));
+    }
+
+    #[test]
+    fn cleared_dates_are_sent() {
+        let updated = r#"{ "status": "watching", "updated_at": "2021-03-05T10:00:00+09:00" }"#;
+
+        let server = TestServer::spawn(vec![updated.into()]);
+        let mal = MyAnimeList::authenticated("client", test_auth()).with_client(server.client());
+
+        let entry = SeriesEntry {
+            start_date: Some(SeriesDate::from_ymd(2021, 3, 5)),
+            ..SeriesEntry::new(30)
+        };
+
+        let updated_at = mal.update_list_entry(&entry).unwrap();
+        assert_eq!(updated_at, Some(1_614_906_000));
+
+        let request = server.requests().remove(0);
+        assert!(request.contains("start_date=2021-03-05"));
+        assert!(request.ends_with("finish_date="));
+    }
+
+    #[test]
+    fn list_entry_is_deleted() {
+        let server = TestServer::spawn(vec![
+            "[]".into(),
+            TestResponse::with_status(404, r#"{ "error": "not_found" }"#),
+        ]);
+
+        let mal = MyAnimeList::authenticated("client", test_auth()).with_client(server.client());
+
+        mal.delete_list_entry(30).unwrap();
+        // Anime that aren't on the list are already deleted
+        mal.delete_list_entry(31).unwrap();
+
+        let requests = server.requests();
+        assert!(requests[0].starts_with("DELETE /anime/30/my_list_status"));
+        assert!(requests[1].starts_with("DELETE /anime/31/my_list_status"));
+    }
+
+    #[test]
+    fn score_parsing() {
+        let mal = MyAnimeList::unauthenticated("client");
+
+        assert_eq!(mal.parse_score("7"), Some(70));
+        assert_eq!(mal.parse_score("11"), None);
+        assert_eq!(mal.score_to_str(70), "7");
+    }
+}
@@ -1,5 +1,6 @@
-use super::{RemoteService, ScoreParser, SeriesEntry, SeriesID, SeriesInfo};
+use super::{AiringEpisode, RemoteService, ScoreParser, SeriesEntry, SeriesID, SeriesInfo};
 use crate::err::{self, Result};
+use std::collections::HashMap;
 
 /// A remote service that will not connect to the internet.
 ///
@@ -7,9 +8,9 @@ use crate::err::{self, Result};
 /// for your application by letting you seamlessly switch between online and offline
 /// modes.
 ///
-/// Note that the `search_info_by_name` and `search_info_by_id` methods will always
-/// return an error with the variant `NeedExistingSeriesData`. All other methods simply
-/// do nothing.
+/// Note that the `search_info_by_name`, `search_info_by_id`, `get_mal_ids`, `find_by_mal_ids`, and
+/// `get_airing_schedule` methods will always return an error with the variant `NeedExistingSeriesData`.
+/// All other methods simply do nothing.
 #[derive(Debug, Default)]
 pub struct Offline;
 
@@ -29,11 +30,27 @@ impl RemoteService for Offline {
         Err(err::Error::NeedExistingSeriesData)
     }
 
+    fn get_mal_ids(&self, _: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        Err(err::Error::NeedExistingSeriesData)
+    }
+
+    fn find_by_mal_ids(&self, _: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        Err(err::Error::NeedExistingSeriesData)
+    }
+
+    fn get_airing_schedule(&self, _: &[SeriesID], _: i64, _: i64) -> Result<Vec<AiringEpisode>> {
+        Err(err::Error::NeedExistingSeriesData)
+    }
+
     fn get_list_entry(&self, _: SeriesID) -> Result<Option<SeriesEntry>> {
         Ok(None)
     }
 
-    fn update_list_entry(&self, _: &SeriesEntry) -> Result<()> {
+    fn update_list_entry(&self, _: &SeriesEntry) -> Result<Option<i64>> {
+        Ok(None)
+    }
+
+    fn delete_list_entry(&self, _: SeriesID) -> Result<()> {
         Ok(())
     }
 
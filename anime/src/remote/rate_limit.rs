@@ -0,0 +1,205 @@
+//! Handling of the rate limits that remote services place on their APIs.
+
+use super::transport::{Client, Request, Response};
+use crate::err::{Error, Result};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Sends requests while respecting the rate limit headers returned by a remote service.
+///
+/// Requests are sent one at a time. When the service reports that no requests are remaining, or responds with
+/// 429 Too Many Requests, the next request will wait until the service allows it again. Requests that were rate limited
+/// are retried with an exponential backoff.
+///
+/// If a request would have to wait longer than the maximum wait time, an `Error::RateLimited` is returned instead.
+#[derive(Debug)]
+pub struct RateLimiter {
+    next_allowed: Mutex<Option<Instant>>,
+    max_retries: u32,
+    backoff: Duration,
+    max_wait: Duration,
+}
+
+impl RateLimiter {
+    /// The number of times a rate limited request will be retried by default.
+    pub const DEFAULT_MAX_RETRIES: u32 = 3;
+    /// The amount of time to wait before retrying a rate limited request by default, when the service doesn't specify one.
+    pub const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+    /// The longest amount of time a request will wait to be sent by default.
+    pub const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(60);
+
+    /// Create a new `RateLimiter` with the default settings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            next_allowed: Mutex::new(None),
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+            backoff: Self::DEFAULT_BACKOFF,
+            max_wait: Self::DEFAULT_MAX_WAIT,
+        }
+    }
+
+    /// Set the number of times a rate limited request will be retried.
+    #[must_use]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the initial amount of time to wait before retrying a rate limited request.
+    ///
+    /// This doubles after each retry, and is only used when the service doesn't say how long to wait.
+    #[must_use]
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set the longest amount of time a request is allowed to wait to be sent.
+    #[must_use]
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Send the `request` through `client`, waiting and retrying as needed to stay within the rate limit.
+    pub(crate) fn send(&self, client: &Client, request: &Request) -> Result<Response> {
+        // Holding the lock while waiting ensures that requests are sent in the order they were made
+        let mut next_allowed = self.next_allowed.lock().unwrap();
+        let mut attempt = 0;
+
+        loop {
+            if let Some(time) = next_allowed.take() {
+                self.wait_until(time)?;
+            }
+
+            let response = client.send(request.clone())?;
+            let limit = Limit::from_response(&response);
+
+            if !limit.exceeded {
+                *next_allowed = limit.next_allowed();
+                return Ok(response);
+            }
+
+            let backoff = self.backoff * 2u32.saturating_pow(attempt);
+            let retry_after = limit.retry_after.unwrap_or(backoff);
+
+            *next_allowed = Some(Instant::now() + retry_after);
+
+            if attempt >= self.max_retries || retry_after > self.max_wait {
+                return Err(Error::RateLimited { retry_after });
+            }
+
+            attempt += 1;
+        }
+    }
+
+    fn wait_until(&self, time: Instant) -> Result<()> {
+        let remaining = time.saturating_duration_since(Instant::now());
+
+        if remaining > self.max_wait {
+            return Err(Error::RateLimited {
+                retry_after: remaining,
+            });
+        }
+
+        thread::sleep(remaining);
+        Ok(())
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The rate limit information found in a response.
+struct Limit {
+    exceeded: bool,
+    remaining: Option<u32>,
+    retry_after: Option<Duration>,
+    reset: Option<Duration>,
+}
+
+impl Limit {
+    fn from_response(response: &Response) -> Self {
+        let parse_secs = |name| {
+            response
+                .header(name)
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+
+        let remaining = response
+            .header("X-RateLimit-Remaining")
+            .and_then(|value| value.trim().parse().ok());
+
+        // The reset time is a Unix timestamp, so it needs to be converted to a duration from now
+        let reset = parse_secs("X-RateLimit-Reset").map(|reset| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+
+            Duration::from_secs(reset.saturating_sub(now))
+        });
+
+        Self {
+            exceeded: response.status == 429,
+            remaining,
+            retry_after: parse_secs("Retry-After").map(Duration::from_secs),
+            reset,
+        }
+    }
+
+    fn next_allowed(&self) -> Option<Instant> {
+        match self.remaining {
+            Some(0) => {
+                let wait = self.retry_after.or(self.reset)?;
+                Some(Instant::now() + wait)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::test_server::{TestResponse, TestServer};
+    use crate::remote::transport::Method;
+
+    #[test]
+    fn rate_limited_requests_are_retried() {
+        let rate_limited = TestResponse::with_status(429, "{}")
+            .header("Retry-After", "0")
+            .header("X-RateLimit-Remaining", "0");
+
+        let server = TestServer::spawn(vec![rate_limited, "{}".into()]);
+        let client = server.client();
+        let limiter = RateLimiter::new().backoff(Duration::from_millis(1));
+
+        let request = client.request(Method::Get, server.url());
+        let response = limiter.send(&client, &request).unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn long_waits_are_reported() {
+        let rate_limited = TestResponse::with_status(429, "{}").header("Retry-After", "120");
+        let server = TestServer::spawn(vec![rate_limited]);
+        let client = server.client();
+
+        let request = client.request(Method::Get, server.url());
+
+        match RateLimiter::new().send(&client, &request) {
+            Err(Error::RateLimited { retry_after }) => {
+                assert_eq!(retry_after, Duration::from_secs(120));
+            }
+            other => panic!("expected rate limit error, got {:?}", other),
+        }
+    }
+}
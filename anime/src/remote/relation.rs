@@ -0,0 +1,263 @@
+//! Relations between series, and walking them to find every series in a franchise.
+
+use super::{SeriesID, SeriesInfo};
+use crate::err::Result;
+use crate::SeriesKind;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The way one series relates to another.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+pub enum RelationKind {
+    /// The related series takes place before the current one.
+    Prequel,
+    /// The related series takes place after the current one.
+    Sequel,
+    /// The related series is the main story the current one is based on.
+    Parent,
+    /// The related series is a side story of the current one.
+    SideStory,
+    /// The related series is a spin-off of the current one.
+    SpinOff,
+    /// The related series is an alternative setting or version of the current one.
+    Alternative,
+    /// The related series is a summary of the current one.
+    Summary,
+    /// The related series is a compilation of the current one.
+    Compilation,
+    /// The related series is contained within the current one.
+    Contains,
+    /// The related series is the source material of the current one.
+    Source,
+    /// The related series only shares characters with the current one.
+    Character,
+    /// Any other kind of relation.
+    Other,
+}
+
+impl RelationKind {
+    /// Returns true if series with this relation are considered to be part of the same franchise.
+    ///
+    /// Series that only share characters are left out, as they're usually crossovers with other franchises.
+    #[inline]
+    #[must_use]
+    pub fn is_franchise(self) -> bool {
+        !matches!(self, Self::Character)
+    }
+}
+
+impl Into<&'static str> for RelationKind {
+    fn into(self) -> &'static str {
+        match self {
+            Self::Prequel => "Prequel",
+            Self::Sequel => "Sequel",
+            Self::Parent => "Parent",
+            Self::SideStory => "Side Story",
+            Self::SpinOff => "Spin-off",
+            Self::Alternative => "Alternative",
+            Self::Summary => "Summary",
+            Self::Compilation => "Compilation",
+            Self::Contains => "Contains",
+            Self::Source => "Source",
+            Self::Character => "Character",
+            Self::Other => "Other",
+        }
+    }
+}
+
+/// A series related to another one.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+pub struct Relation {
+    /// The way the series relates to the one it belongs to.
+    pub kind: RelationKind,
+    /// The ID of the related series.
+    pub id: SeriesID,
+    /// The type of the related series, if it's known.
+    pub series_kind: Option<SeriesKind>,
+}
+
+impl Relation {
+    #[inline(always)]
+    #[must_use]
+    pub fn new(kind: RelationKind, id: SeriesID, series_kind: Option<SeriesKind>) -> Self {
+        Self {
+            kind,
+            id,
+            series_kind,
+        }
+    }
+}
+
+/// Every series that can be reached from a starting series through its relations.
+#[derive(Clone, Debug)]
+pub struct RelationGraph {
+    root: SeriesID,
+    series: Vec<SeriesInfo>,
+    reached_by: HashMap<SeriesID, (SeriesID, RelationKind)>,
+}
+
+impl RelationGraph {
+    /// The most series that will be walked to.
+    ///
+    /// Some franchises relate to hundreds of series, so this keeps the number of requests made reasonable.
+    pub const MAX_SERIES: usize = 100;
+
+    /// Walk every franchise relation starting from `root`.
+    ///
+    /// `search` is used to retrieve the info of several series at once, and is called once for each level of relations.
+    /// Series it doesn't return are left out of the graph.
+    pub fn walk<F>(root: SeriesInfo, mut search: F) -> Result<Self>
+    where
+        F: FnMut(&[SeriesID]) -> Result<Vec<SeriesInfo>>,
+    {
+        let root_id = root.id;
+        let mut seen = HashSet::new();
+        let mut reached_by = HashMap::new();
+        let mut series = Vec::new();
+        let mut found = vec![root];
+
+        seen.insert(root_id);
+
+        while !found.is_empty() {
+            let mut pending = Vec::new();
+
+            for info in found {
+                let relations = info
+                    .relations
+                    .iter()
+                    .filter(|relation| relation.kind.is_franchise());
+
+                for relation in relations {
+                    if seen.len() >= Self::MAX_SERIES || !seen.insert(relation.id) {
+                        continue;
+                    }
+
+                    reached_by.insert(relation.id, (info.id, relation.kind));
+                    pending.push(relation.id);
+                }
+
+                series.push(info);
+            }
+
+            if pending.is_empty() {
+                break;
+            }
+
+            found = search(&pending)?;
+        }
+
+        Ok(Self {
+            root: root_id,
+            series,
+            reached_by,
+        })
+    }
+
+    /// Returns the ID of the series the graph was walked from.
+    #[inline(always)]
+    #[must_use]
+    pub fn root(&self) -> SeriesID {
+        self.root
+    }
+
+    /// Returns every series in the graph, in the order they were reached from the root series.
+    #[inline(always)]
+    #[must_use]
+    pub fn series(&self) -> &[SeriesInfo] {
+        &self.series
+    }
+
+    /// Returns the info of the series with the specified `id`, if it's in the graph.
+    #[must_use]
+    pub fn get(&self, id: SeriesID) -> Option<&SeriesInfo> {
+        self.series.iter().find(|info| info.id == id)
+    }
+
+    /// Returns the ID of the series that the series with the specified `id` was first reached from, along with how
+    /// it relates to it.
+    ///
+    /// Returns None for the root series.
+    #[must_use]
+    pub fn reached_by(&self, id: SeriesID) -> Option<(SeriesID, RelationKind)> {
+        self.reached_by.get(&id).copied()
+    }
+
+    /// Returns the number of series in the graph.
+    #[inline(always)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.series.len()
+    }
+
+    /// Returns true if the graph has no series, which can only happen if it was walked from a series without info.
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(id: SeriesID, relations: &[(RelationKind, SeriesID)]) -> SeriesInfo {
+        SeriesInfo {
+            relations: relations
+                .iter()
+                .map(|&(kind, id)| Relation::new(kind, id, Some(SeriesKind::Season)))
+                .collect(),
+            ..SeriesInfo::with_title(id, &id.to_string())
+        }
+    }
+
+    #[test]
+    fn every_franchise_relation_is_walked() {
+        use RelationKind::{Character, Prequel, Sequel, SideStory, SpinOff};
+
+        let mut searches = Vec::new();
+
+        let graph = RelationGraph::walk(info(2, &[(Prequel, 1), (Sequel, 3)]), |ids| {
+            searches.push(ids.to_vec());
+
+            Ok(ids
+                .iter()
+                .map(|&id| match id {
+                    1 => info(1, &[(Sequel, 2), (SideStory, 4), (Character, 10)]),
+                    3 => info(3, &[(Prequel, 2)]),
+                    4 => info(4, &[(SpinOff, 5)]),
+                    // Series that can't be found are left out
+                    _ => info(id, &[]),
+                })
+                .filter(|info| info.id != 5)
+                .collect())
+        })
+        .unwrap();
+
+        let ids = graph
+            .series()
+            .iter()
+            .map(|info| info.id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, [2, 1, 3, 4]);
+        assert_eq!(searches, [vec![1, 3], vec![4], vec![5]]);
+        assert_eq!(graph.root(), 2);
+        assert!(graph.reached_by(2).is_none());
+        assert_eq!(graph.reached_by(4), Some((1, SideStory)));
+        assert!(graph.get(10).is_none());
+    }
+
+    #[test]
+    fn walking_stops_at_max_series() {
+        let graph = RelationGraph::walk(info(0, &[(RelationKind::Sequel, 1)]), |ids| {
+            Ok(ids
+                .iter()
+                .map(|&id| info(id, &[(RelationKind::Sequel, id + 1)]))
+                .collect())
+        })
+        .unwrap();
+
+        assert_eq!(graph.len(), RelationGraph::MAX_SERIES);
+    }
+}
@@ -0,0 +1,422 @@
+//! Recording and replaying of AniList requests.
+//!
+//! A `Recorder` can be used as the transport for an `AniList` connection to save every GraphQL query it sends,
+//! along with its variables and response, to a `Cassette` file. The `Replay` remote can then answer the same
+//! requests from that file without needing an internet connection, which makes it useful for testing.
+
+#![allow(clippy::doc_markdown)]
+
+use super::anilist::{AniList, Auth};
+use super::transport::{Client, ClientConfig, Request, Response, Transport};
+use super::{
+    AccessToken, AiringEpisode, Recommendation, RemoteService, ScoreParser, SearchOptions,
+    SearchPage, Season, SeriesEntry, SeriesID, SeriesInfo, UserListEntry,
+};
+use crate::err::{Error, Result};
+use crate::SeriesKind;
+use serde_derive::{Deserialize, Serialize};
+use serde_json as json;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// A single GraphQL query and the response it received.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Interaction {
+    pub query: String,
+    pub variables: json::Value,
+    pub status: u16,
+    pub response: json::Value,
+}
+
+impl Interaction {
+    fn matches(&self, query: &GqlQuery) -> bool {
+        self.query == query.query && self.variables == query.variables
+    }
+}
+
+/// A set of recorded interactions with AniList, in the order they were made.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Load a cassette from the JSON file at `path`.
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(path)?;
+        json::from_reader(BufReader::new(file)).map_err(Into::into)
+    }
+
+    /// Save the cassette as JSON to the file at `path`.
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::create(path)?;
+        json::to_writer_pretty(BufWriter::new(file), self).map_err(Into::into)
+    }
+}
+
+#[derive(Deserialize)]
+struct GqlQuery {
+    query: String,
+    #[serde(default)]
+    variables: json::Value,
+}
+
+impl GqlQuery {
+    fn from_request(request: &Request) -> Option<Self> {
+        let body = request.body.as_ref()?;
+        json::from_slice(body).ok()
+    }
+}
+
+/// A `Transport` that saves every GraphQL query sent through it to a cassette file.
+///
+/// The file is rewritten after each query, so the recording is not lost if the program exits early.
+#[derive(Debug)]
+pub struct Recorder {
+    inner: Arc<dyn Transport>,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl Recorder {
+    /// Create a new `Recorder` that sends requests through `inner` and saves them to the file at `path`.
+    pub fn new<T, P>(inner: T, path: P) -> Self
+    where
+        T: Transport + 'static,
+        P: Into<PathBuf>,
+    {
+        Self {
+            inner: Arc::new(inner),
+            path: path.into(),
+            cassette: Mutex::new(Cassette::default()),
+        }
+    }
+}
+
+impl Transport for Recorder {
+    fn send(&self, request: Request) -> Result<Response> {
+        let query = GqlQuery::from_request(&request);
+        let response = self.inner.send(request)?;
+
+        if let (Some(query), Ok(body)) = (query, response.json()) {
+            let mut cassette = self.cassette.lock().unwrap();
+
+            cassette.interactions.push(Interaction {
+                query: query.query,
+                variables: query.variables,
+                status: response.status,
+                response: body,
+            });
+
+            cassette.save(&self.path)?;
+        }
+
+        Ok(response)
+    }
+}
+
+/// A `Transport` that answers GraphQL queries with the responses recorded in a `Cassette`.
+///
+/// Interactions are replayed in the order they were recorded. When a query is sent more times than it was recorded,
+/// the last recorded response to it is used again.
+#[derive(Debug)]
+pub struct Replayer {
+    cassette: Cassette,
+    used: Mutex<Vec<bool>>,
+}
+
+impl Replayer {
+    #[must_use]
+    pub fn new(cassette: Cassette) -> Self {
+        let used = vec![false; cassette.interactions.len()];
+
+        Self {
+            cassette,
+            used: Mutex::new(used),
+        }
+    }
+
+    fn find(&self, query: &GqlQuery) -> Option<&Interaction> {
+        let mut used = self.used.lock().unwrap();
+        let interactions = &self.cassette.interactions;
+
+        let unused = interactions
+            .iter()
+            .enumerate()
+            .find(|(i, interaction)| !used[*i] && interaction.matches(query));
+
+        if let Some((i, interaction)) = unused {
+            used[i] = true;
+            return Some(interaction);
+        }
+
+        interactions
+            .iter()
+            .rev()
+            .find(|interaction| interaction.matches(query))
+    }
+}
+
+impl Transport for Replayer {
+    fn send(&self, request: Request) -> Result<Response> {
+        let query = GqlQuery::from_request(&request)
+            .ok_or_else(|| Error::NoRecordedResponse(request.url.clone()))?;
+
+        let interaction = self
+            .find(&query)
+            .ok_or_else(|| Error::NoRecordedResponse(query.variables.to_string()))?;
+
+        Ok(Response {
+            status: interaction.status,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: json::to_vec(&interaction.response)?,
+        })
+    }
+}
+
+/// A remote service that answers every request from a `Cassette` recorded with AniList.
+#[derive(Debug)]
+pub struct Replay(AniList);
+
+impl Replay {
+    /// Create a new `Replay` remote without any authentication.
+    ///
+    /// Just like `AniList`, only series info can be searched for in this mode.
+    #[must_use]
+    pub fn new(cassette: Cassette) -> Self {
+        Self(AniList::unauthenticated().with_client(Self::client(cassette)))
+    }
+
+    /// Create a new `Replay` remote that is authenticated with the user recorded in `cassette`.
+    ///
+    /// This requires the cassette to contain the query that was made to retrieve the user.
+    pub fn authenticated(cassette: Cassette) -> Result<Self> {
+        let client = Self::client(cassette);
+        let auth = Auth::retrieve_with(&client, AccessToken::encode("replay"))?;
+
+        Ok(Self(AniList::authenticated(auth).with_client(client)))
+    }
+
+    fn client(cassette: Cassette) -> Client {
+        Client::with_transport(ClientConfig::default(), Replayer::new(cassette))
+    }
+}
+
+impl RemoteService for Replay {
+    fn search_info_by_name(&self, name: &str) -> Result<Vec<SeriesInfo>> {
+        self.0.search_info_by_name(name)
+    }
+
+    fn search_info(&self, name: &str, options: &SearchOptions) -> Result<SearchPage> {
+        self.0.search_info(name, options)
+    }
+
+    fn search_info_by_id(&self, id: SeriesID) -> Result<SeriesInfo> {
+        self.0.search_info_by_id(id)
+    }
+
+    fn search_info_by_ids(&self, ids: &[SeriesID]) -> Result<Vec<SeriesInfo>> {
+        self.0.search_info_by_ids(ids)
+    }
+
+    fn get_recommendations(&self, id: SeriesID) -> Result<Vec<Recommendation>> {
+        self.0.get_recommendations(id)
+    }
+
+    fn get_season(
+        &self,
+        season: Season,
+        year: u32,
+        kind: Option<SeriesKind>,
+    ) -> Result<Vec<SeriesInfo>> {
+        self.0.get_season(season, year, kind)
+    }
+
+    fn get_list_entry(&self, id: SeriesID) -> Result<Option<SeriesEntry>> {
+        self.0.get_list_entry(id)
+    }
+
+    fn get_list_entries(&self, ids: &[SeriesID]) -> Result<Vec<SeriesEntry>> {
+        self.0.get_list_entries(ids)
+    }
+
+    fn get_airing_schedule(
+        &self,
+        ids: &[SeriesID],
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<AiringEpisode>> {
+        self.0.get_airing_schedule(ids, from, to)
+    }
+
+    fn get_user_list(&self) -> Result<Vec<UserListEntry>> {
+        self.0.get_user_list()
+    }
+
+    fn get_mal_ids(&self, ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        self.0.get_mal_ids(ids)
+    }
+
+    fn find_by_mal_ids(&self, mal_ids: &[SeriesID]) -> Result<HashMap<SeriesID, SeriesID>> {
+        self.0.find_by_mal_ids(mal_ids)
+    }
+
+    fn update_list_entry(&self, entry: &SeriesEntry) -> Result<Option<i64>> {
+        self.0.update_list_entry(entry)
+    }
+
+    fn delete_list_entry(&self, id: SeriesID) -> Result<()> {
+        self.0.delete_list_entry(id)
+    }
+
+    fn set_favourite(&self, id: SeriesID, favourite: bool) -> Result<()> {
+        self.0.set_favourite(id, favourite)
+    }
+}
+
+impl ScoreParser for Replay {
+    fn parse_score(&self, score: &str) -> Option<u8> {
+        self.0.parse_score(score)
+    }
+
+    fn score_to_str(&self, score: u8) -> Cow<str> {
+        self.0.score_to_str(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::test_server::TestServer;
+    use crate::remote::transport::AttoTransport;
+    use crate::remote::Status;
+    use crate::SeriesKind;
+
+    const USER_JSON: &str = r#"{
+        "data": {
+            "Viewer": {
+                "id": 5,
+                "name": "test",
+                "mediaListOptions": { "scoreFormat": "POINT_10" }
+            }
+        }
+    }"#;
+
+    const SEASON_JSON: &str = r#"{
+        "data": {
+            "Media": {
+                "id": 1,
+                "title": { "romaji": "Season 1", "userPreferred": "Season 1" },
+                "episodes": 12,
+                "duration": 24,
+                "format": "TV",
+                "relations": {
+                    "edges": [{ "relationType": "SEQUEL", "node": { "id": 2, "format": "TV" } }]
+                }
+            }
+        }
+    }"#;
+
+    const ENTRY_WATCHING_JSON: &str = r#"{
+        "data": {
+            "MediaList": {
+                "progress": 11,
+                "status": "CURRENT",
+                "score": 0,
+                "repeat": 0,
+                "startedAt": { "year": 2021, "month": 1, "day": 2 },
+                "completedAt": { "year": null, "month": null, "day": null }
+            }
+        }
+    }"#;
+
+    const ENTRY_COMPLETED_JSON: &str = r#"{
+        "data": {
+            "MediaList": {
+                "progress": 12,
+                "status": "COMPLETED",
+                "score": 80,
+                "repeat": 0,
+                "startedAt": { "year": 2021, "month": 1, "day": 2 },
+                "completedAt": { "year": 2021, "month": 1, "day": 9 }
+            }
+        }
+    }"#;
+
+    const UPDATE_JSON: &str = r#"{ "data": { "SaveMediaListEntry": { "mediaId": 1 } } }"#;
+
+    #[test]
+    fn recorded_requests_are_replayed() {
+        let server = TestServer::spawn(vec![
+            USER_JSON.into(),
+            SEASON_JSON.into(),
+            ENTRY_WATCHING_JSON.into(),
+            UPDATE_JSON.into(),
+            ENTRY_COMPLETED_JSON.into(),
+        ]);
+
+        let path = std::env::temp_dir().join(format!("anime-cassette-{}.json", std::process::id()));
+
+        let config = ClientConfig {
+            base_url: Some(server.url()),
+            ..ClientConfig::default()
+        };
+
+        let client = Client::with_transport(config, Recorder::new(AttoTransport, &path));
+        let auth = Auth::retrieve_with(&client, AccessToken::encode("token")).unwrap();
+        let anilist = AniList::authenticated(auth).with_client(client);
+
+        anilist.search_info_by_id(1).unwrap();
+
+        let mut entry = anilist.get_list_entry(1).unwrap().unwrap();
+        entry.watched_eps = 12;
+        entry.score = Some(80);
+        entry.status = Status::Completed;
+
+        anilist.update_list_entry(&entry).unwrap();
+        anilist.get_list_entry(1).unwrap();
+
+        let cassette = Cassette::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(cassette.interactions.len(), 5);
+
+        let replay = Replay::authenticated(cassette).unwrap();
+
+        let info = replay.search_info_by_id(1).unwrap();
+        assert_eq!(info.episodes, 12);
+        assert_eq!(info.direct_sequel().map(|sequel| sequel.id), Some(2));
+        assert_eq!(info.kind, SeriesKind::Season);
+
+        let entry = replay.get_list_entry(1).unwrap().unwrap();
+        assert_eq!(entry.status, Status::Watching);
+        assert_eq!(replay.score_to_str(80), "8");
+
+        replay.update_list_entry(&entry).unwrap_err();
+
+        let mut entry = entry;
+        entry.watched_eps = 12;
+        entry.score = Some(80);
+        entry.status = Status::Completed;
+        replay.update_list_entry(&entry).unwrap();
+
+        let entry = replay.get_list_entry(1).unwrap().unwrap();
+        assert_eq!(entry.status, Status::Completed);
+        assert_eq!(entry.watched_eps, 12);
+
+        match replay.search_info_by_id(2) {
+            Err(Error::NoRecordedResponse(_)) => (),
+            other => panic!("expected a missing response error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! A minimal HTTP server to stand in for remote services in tests.
+
+use super::transport::{Client, ClientConfig};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A canned response for the server to reply with.
+pub struct TestResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl TestResponse {
+    pub fn with_status<S>(status: u16, body: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl From<&str> for TestResponse {
+    fn from(body: &str) -> Self {
+        Self::with_status(200, body)
+    }
+}
+
+/// A server that replies to each incoming request with the next queued response.
+///
+/// Every request received is recorded with its request line, headers, and body so tests can inspect them.
+pub struct TestServer {
+    port: u16,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServer {
+    pub fn spawn(responses: Vec<TestResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind test server");
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let thread_requests = Arc::clone(&requests);
+
+        thread::spawn(move || {
+            for response in responses {
+                let (stream, _) = match listener.accept() {
+                    Ok(conn) => conn,
+                    Err(_) => return,
+                };
+
+                let mut reader = BufReader::new(stream);
+                let request = Self::read_request(&mut reader);
+                thread_requests.lock().unwrap().push(request);
+
+                let mut stream = reader.into_inner();
+                Self::write_response(&mut stream, &response).ok();
+            }
+        });
+
+        Self { port, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// Returns a `Client` that sends all requests to the server.
+    pub fn client(&self) -> Client {
+        Client::new(ClientConfig {
+            base_url: Some(self.url()),
+            ..ClientConfig::default()
+        })
+    }
+
+    /// Returns every request the server has fully received so far.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn read_request<R>(reader: &mut R) -> String
+    where
+        R: BufRead,
+    {
+        let mut request = String::new();
+        let mut content_len = 0;
+
+        loop {
+            let mut line = String::new();
+
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                break;
+            }
+
+            if line == "\r\n" {
+                request.push_str(&line);
+                break;
+            }
+
+            // Header names are case insensitive, so normalize them to make matching on them easier
+            match line.find(':') {
+                Some(idx) if !request.is_empty() => {
+                    let name = line[..idx].to_ascii_lowercase();
+                    let value = &line[idx + 1..];
+
+                    if name == "content-length" {
+                        content_len = value.trim().parse().unwrap_or(0);
+                    }
+
+                    request.push_str(&name);
+                    request.push(':');
+                    request.push_str(value);
+                }
+                _ => request.push_str(&line),
+            }
+        }
+
+        let mut body = vec![0; content_len];
+        reader.read_exact(&mut body).ok();
+        request.push_str(&String::from_utf8_lossy(&body));
+
+        request
+    }
+
+    fn write_response<W>(stream: &mut W, response: &TestResponse) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        write!(stream, "HTTP/1.1 {} Test\r\n", response.status)?;
+        write!(stream, "Content-Type: application/json\r\n")?;
+        write!(stream, "Content-Length: {}\r\n", response.body.len())?;
+        write!(stream, "Connection: close\r\n")?;
+
+        for (name, value) in &response.headers {
+            write!(stream, "{}: {}\r\n", name, value)?;
+        }
+
+        write!(stream, "\r\n{}", response.body)?;
+        stream.flush()
+    }
+}
@@ -0,0 +1,436 @@
+//! The HTTP layer that every remote service sends its requests through.
+//!
+//! By default, requests are sent with `attohttpc`, but any HTTP client can be used
+//! by implementing the `Transport` trait and passing it to a `Client`.
+
+use crate::err::{Error, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use url::form_urlencoded;
+
+/// The HTTP methods used by remote services.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl Method {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Patch => "PATCH",
+            Self::Delete => "DELETE",
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An HTTP request ready to be sent by a `Transport`.
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: Method,
+    /// The full URL of the request, including any query parameters.
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    /// The maximum amount of time the entire request is allowed to take.
+    pub timeout: Duration,
+    /// The URL of the proxy to send the request through.
+    pub proxy: Option<String>,
+}
+
+impl Request {
+    /// Create a new `Request` with no headers, body, or proxy, and a default timeout.
+    #[must_use]
+    pub fn new<S>(method: Method, url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            method,
+            url: url.into(),
+            headers: Vec::new(),
+            body: None,
+            timeout: ClientConfig::DEFAULT_TIMEOUT,
+            proxy: None,
+        }
+    }
+
+    /// Add a query parameter to the request's URL.
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn param<V>(mut self, key: &str, value: V) -> Self
+    where
+        V: ToString,
+    {
+        let separator = if self.url.contains('?') { '&' } else { '?' };
+        let encoded = form_urlencoded::Serializer::new(String::new())
+            .append_pair(key, &value.to_string())
+            .finish();
+
+        self.url.push(separator);
+        self.url.push_str(&encoded);
+        self
+    }
+
+    /// Set a header, replacing any existing header with the same name.
+    #[must_use]
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let name = name.into();
+
+        self.headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// Set the `Authorization` header to use the specified bearer `token`.
+    #[inline]
+    #[must_use]
+    pub fn bearer_auth<S>(self, token: S) -> Self
+    where
+        S: AsRef<str>,
+    {
+        self.header("Authorization", format!("Bearer {}", token.as_ref()))
+    }
+
+    /// Set the body of the request to `value` serialized as JSON.
+    pub fn json<T>(self, value: &T) -> Result<Self>
+    where
+        T: Serialize,
+    {
+        let body = serde_json::to_vec(value)?;
+        Ok(self.body("application/json", body))
+    }
+
+    /// Set the body of the request to `value` serialized as a URL encoded form.
+    pub fn form<T>(self, value: &T) -> Result<Self>
+    where
+        T: Serialize,
+    {
+        let body = serde_urlencoded::to_string(value)
+            .map_err(|err| Error::Transport(err.into()))?
+            .into_bytes();
+
+        Ok(self.body("application/x-www-form-urlencoded", body))
+    }
+
+    /// Set the body of the request to `body` with the specified `content_type`.
+    #[must_use]
+    pub fn body<S>(mut self, content_type: S, body: Vec<u8>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.body = Some(body);
+        self.header("Content-Type", content_type)
+    }
+
+    /// Returns the value of the header with the specified `name`.
+    #[must_use]
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// A response to a `Request`.
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Returns true if the response has a 2xx status code.
+    #[inline]
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Returns the value of the header with the specified `name`.
+    ///
+    /// Header names are case insensitive.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Deserialize the body of the response from JSON.
+    pub fn json<T>(&self) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(&self.body).map_err(Into::into)
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// An HTTP client that can send requests for a remote service.
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// Send the `request` and return its response.
+    ///
+    /// Responses with a non-2xx status code should still be returned as a `Response`, as remote
+    /// services typically include more information about the error in the response body.
+    fn send(&self, request: Request) -> Result<Response>;
+}
+
+/// A `Transport` that sends requests with `attohttpc`.
+#[derive(Debug, Default)]
+pub struct AttoTransport;
+
+impl Transport for AttoTransport {
+    fn send(&self, request: Request) -> Result<Response> {
+        use attohttpc::header::HeaderName;
+        use attohttpc::{ProxySettings, RequestBuilder};
+
+        let method = match request.method {
+            Method::Get => attohttpc::Method::GET,
+            Method::Post => attohttpc::Method::POST,
+            Method::Put => attohttpc::Method::PUT,
+            Method::Patch => attohttpc::Method::PATCH,
+            Method::Delete => attohttpc::Method::DELETE,
+        };
+
+        let mut builder = RequestBuilder::try_new(method, &request.url)?.timeout(request.timeout);
+
+        for (name, value) in &request.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|err| Error::Transport(err.into()))?;
+
+            builder = builder.try_header_append(name, value.as_str())?;
+        }
+
+        if let Some(proxy) = &request.proxy {
+            let proxy = url::Url::parse(proxy)?;
+
+            let settings = ProxySettings::builder()
+                .http_proxy(proxy.clone())
+                .https_proxy(proxy)
+                .build();
+
+            builder = builder.proxy_settings(settings);
+        }
+
+        let response = builder.bytes(request.body.unwrap_or_default()).send()?;
+        let status = response.status().as_u16();
+
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                Some((name.as_str().to_string(), value.to_string()))
+            })
+            .collect();
+
+        let body = response.bytes()?;
+
+        Ok(Response {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Settings that apply to every request a `Client` makes.
+#[derive(Clone, Debug)]
+pub struct ClientConfig {
+    /// The URL to use instead of a remote service's official API endpoint.
+    ///
+    /// This is mainly useful for pointing a remote service at a local server for testing.
+    pub base_url: Option<String>,
+    /// The maximum amount of time a request is allowed to take.
+    pub timeout: Duration,
+    /// The value of the `User-Agent` header to send with each request.
+    pub user_agent: Option<String>,
+    /// The URL of the proxy to send each request through.
+    pub proxy: Option<String>,
+}
+
+impl ClientConfig {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            base_url: None,
+            timeout: Self::DEFAULT_TIMEOUT,
+            user_agent: None,
+            proxy: None,
+        }
+    }
+}
+
+/// A `Transport` along with the settings to use for each request sent through it.
+///
+/// Cloning a `Client` is cheap, as the transport is shared between clones.
+#[derive(Clone, Debug)]
+pub struct Client {
+    config: ClientConfig,
+    transport: Arc<dyn Transport>,
+}
+
+impl Client {
+    /// Create a new `Client` that uses `attohttpc` to send requests.
+    #[inline]
+    #[must_use]
+    pub fn new(config: ClientConfig) -> Self {
+        Self::with_transport(config, AttoTransport)
+    }
+
+    /// Create a new `Client` that sends requests through the specified `transport`.
+    #[must_use]
+    pub fn with_transport<T>(config: ClientConfig, transport: T) -> Self
+    where
+        T: Transport + 'static,
+    {
+        Self {
+            config,
+            transport: Arc::new(transport),
+        }
+    }
+
+    /// Returns the settings used for each request.
+    #[inline(always)]
+    #[must_use]
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// Returns the base URL set in the client's config, or `default` if it isn't set.
+    #[inline]
+    #[must_use]
+    pub fn base_url<'a>(&'a self, default: &'a str) -> &'a str {
+        self.config.base_url.as_deref().unwrap_or(default)
+    }
+
+    /// Create a new request to `url` with the client's timeout, proxy, and user agent applied.
+    #[must_use]
+    pub fn request<S>(&self, method: Method, url: S) -> Request
+    where
+        S: Into<String>,
+    {
+        let mut request = Request::new(method, url);
+        request.timeout = self.config.timeout;
+        request.proxy.clone_from(&self.config.proxy);
+
+        match &self.config.user_agent {
+            Some(user_agent) => request.header("User-Agent", user_agent.as_str()),
+            None => request,
+        }
+    }
+
+    /// Send the `request` through the client's transport.
+    #[inline]
+    pub fn send(&self, request: Request) -> Result<Response> {
+        self.transport.send(request)
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new(ClientConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct MockTransport {
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl Transport for MockTransport {
+        fn send(&self, request: Request) -> Result<Response> {
+            self.requests.lock().unwrap().push(request);
+
+            Ok(Response {
+                status: 200,
+                headers: vec![("X-Test".into(), "value".into())],
+                body: br#"{"ok":true}"#.to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn client_applies_config() {
+        let config = ClientConfig {
+            base_url: Some("http://localhost".into()),
+            timeout: Duration::from_secs(3),
+            user_agent: Some("anime-test".into()),
+            proxy: Some("http://proxy:8080".into()),
+        };
+
+        let transport = Arc::new(MockTransport::default());
+        let client = Client {
+            config,
+            transport: Arc::clone(&transport) as Arc<dyn Transport>,
+        };
+
+        let url = format!("{}/search", client.base_url("https://example.com"));
+
+        let request = client
+            .request(Method::Get, url)
+            .param("q", "a b&c")
+            .param("limit", 5);
+
+        let response = client.send(request).unwrap();
+
+        assert!(response.is_success());
+        assert_eq!(response.header("x-test"), Some("value"));
+        assert_eq!(response.json::<serde_json::Value>().unwrap()["ok"], true);
+
+        let requests = transport.requests.lock().unwrap();
+        let sent = &requests[0];
+
+        assert_eq!(sent.url, "http://localhost/search?q=a+b%26c&limit=5");
+        assert_eq!(sent.timeout, Duration::from_secs(3));
+        assert_eq!(sent.proxy.as_deref(), Some("http://proxy:8080"));
+        assert_eq!(sent.get_header("user-agent"), Some("anime-test"));
+    }
+
+    #[test]
+    fn headers_are_replaced() {
+        let request = Request::new(Method::Post, "http://localhost")
+            .json(&serde_json::json!({ "a": 1 }))
+            .unwrap()
+            .header("content-type", "application/vnd.api+json");
+
+        assert_eq!(request.headers.len(), 1);
+        assert_eq!(
+            request.get_header("Content-Type"),
+            Some("application/vnd.api+json")
+        );
+    }
+}
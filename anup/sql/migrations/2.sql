@@ -0,0 +1,10 @@
+ALTER TABLE series_info ADD COLUMN title_english TEXT;
+ALTER TABLE series_info ADD COLUMN title_native TEXT;
+ALTER TABLE series_info ADD COLUMN synonyms TEXT;
+ALTER TABLE series_info ADD COLUMN season SMALLINT;
+ALTER TABLE series_info ADD COLUMN season_year SMALLINT;
+ALTER TABLE series_info ADD COLUMN airing_status SMALLINT;
+ALTER TABLE series_info ADD COLUMN genres TEXT;
+ALTER TABLE series_info ADD COLUMN studios TEXT;
+ALTER TABLE series_info ADD COLUMN average_score SMALLINT;
+ALTER TABLE series_info ADD COLUMN is_adult BIT NOT NULL DEFAULT 0;
\ No newline at end of file
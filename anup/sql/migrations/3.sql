@@ -0,0 +1,2 @@
+ALTER TABLE series_info ADD COLUMN next_episode SMALLINT;
+ALTER TABLE series_info ADD COLUMN next_episode_airs_at BIGINT;
\ No newline at end of file
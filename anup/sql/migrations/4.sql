@@ -0,0 +1,4 @@
+ALTER TABLE series_entries ADD COLUMN notes TEXT;
+ALTER TABLE series_entries ADD COLUMN private BIT NOT NULL DEFAULT 0;
+ALTER TABLE series_entries ADD COLUMN hidden BIT NOT NULL DEFAULT 0;
+ALTER TABLE series_entries ADD COLUMN custom_lists TEXT;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE series_entries ADD COLUMN remote_updated_at BIGINT;
+ALTER TABLE series_entries ADD COLUMN modified_at BIGINT;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE series_entries ADD COLUMN favourite BIT NOT NULL DEFAULT 0;
\ No newline at end of file
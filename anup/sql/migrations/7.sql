@@ -0,0 +1 @@
+ALTER TABLE series_entries ADD COLUMN extended_synced BIT NOT NULL DEFAULT 0;
\ No newline at end of file
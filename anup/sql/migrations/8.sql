@@ -0,0 +1,6 @@
+ALTER TABLE series_entries ADD COLUMN base_status SMALLINT;
+ALTER TABLE series_entries ADD COLUMN base_score SMALLINT;
+ALTER TABLE series_entries ADD COLUMN base_notes TEXT;
+ALTER TABLE series_entries ADD COLUMN base_private BIT;
+ALTER TABLE series_entries ADD COLUMN base_hidden BIT;
+ALTER TABLE series_entries ADD COLUMN base_custom_lists TEXT;
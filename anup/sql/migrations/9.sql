@@ -0,0 +1,20 @@
+-- The table used to be created by the schema with only an id column, so older databases may not have it at all
+CREATE TABLE IF NOT EXISTS pending_deletions (
+    id INTEGER NOT NULL,
+    service TEXT NOT NULL DEFAULT '',
+    username TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY(id, service, username)
+);
+
+-- Existing deletions were made before their user was tracked, so they're kept without one
+CREATE TABLE pending_deletions_new (
+    id INTEGER NOT NULL,
+    service TEXT NOT NULL DEFAULT '',
+    username TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY(id, service, username)
+);
+
+INSERT INTO pending_deletions_new (id) SELECT id FROM pending_deletions;
+
+DROP TABLE pending_deletions;
+ALTER TABLE pending_deletions_new RENAME TO pending_deletions;
@@ -1,4 +1,4 @@
-PRAGMA user_version = 1;
+PRAGMA user_version = 9;
 
 CREATE TABLE IF NOT EXISTS series_configs (
     id INTEGER NOT NULL PRIMARY KEY,
@@ -14,6 +14,18 @@ CREATE TABLE IF NOT EXISTS series_info (
     title_romaji TEXT NOT NULL,
     episodes SMALLINT NOT NULL,
     episode_length_mins SMALLINT NOT NULL,
+    title_english TEXT,
+    title_native TEXT,
+    synonyms TEXT,
+    season SMALLINT,
+    season_year SMALLINT,
+    airing_status SMALLINT,
+    genres TEXT,
+    studios TEXT,
+    average_score SMALLINT,
+    is_adult BIT NOT NULL DEFAULT 0,
+    next_episode SMALLINT,
+    next_episode_airs_at BIGINT,
     FOREIGN KEY(id) REFERENCES series_configs(id) ON DELETE CASCADE
 );
 
@@ -26,5 +38,31 @@ CREATE TABLE IF NOT EXISTS series_entries (
     start_date DATE,
     end_date DATE,
     needs_sync BIT NOT NULL,
+    notes TEXT,
+    private BIT NOT NULL DEFAULT 0,
+    hidden BIT NOT NULL DEFAULT 0,
+    custom_lists TEXT,
+    remote_updated_at BIGINT,
+    modified_at BIGINT,
+    favourite BIT NOT NULL DEFAULT 0,
+    extended_synced BIT NOT NULL DEFAULT 0,
+    base_status SMALLINT,
+    base_score SMALLINT,
+    base_notes TEXT,
+    base_private BIT,
+    base_hidden BIT,
+    base_custom_lists TEXT,
     FOREIGN KEY(id) REFERENCES series_configs(id) ON DELETE CASCADE
-);
\ No newline at end of file
+);
+
+CREATE TABLE IF NOT EXISTS pending_deletions (
+    id INTEGER NOT NULL,
+    service TEXT NOT NULL DEFAULT '',
+    username TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY(id, service, username)
+);
+
+CREATE TABLE IF NOT EXISTS unmatched_imports (
+    id INTEGER NOT NULL PRIMARY KEY,
+    list_entry BLOB NOT NULL
+);
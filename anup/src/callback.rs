@@ -0,0 +1,365 @@
+//! A short-lived HTTP listener on localhost that captures the credentials a remote service redirects the user's browser
+//! to after they login.
+
+use crate::util::ScopedTask;
+use anyhow::{anyhow, Context, Error, Result};
+use rand::Rng;
+use std::net::{Ipv4Addr, TcpListener as StdTcpListener};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::{task, time};
+
+/// The path the remote service should redirect to after the user logs in.
+pub const CALLBACK_PATH: &str = "/callback";
+
+/// The path the relay page sends access tokens from URL fragments to.
+const TOKEN_PATH: &str = "/token";
+
+/// How long to wait for the user to login before giving up.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How long to wait for a connection to send its request.
+///
+/// Browsers can open connections ahead of time that never send anything, so they have to be given up on eventually.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The most bytes of a request that will be read.
+const MAX_REQUEST_LEN: usize = 8192;
+
+/// The number of characters to use for the OAuth state.
+const STATE_LEN: usize = 32;
+
+// Access tokens are sent in the URL fragment, which browsers never send to the server.
+// This page sends the fragment back as a query string instead.
+const RELAY_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<body>
+<p>Finishing login..</p>
+<script>window.location.replace("/token?" + window.location.hash.substring(1));</script>
+</body>
+</html>"#;
+
+const SUCCESS_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<body>
+<p>Login complete. You can close this window and return to anup.</p>
+</body>
+</html>"#;
+
+const FAILED_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<body>
+<p>Login failed. You can close this window and return to anup.</p>
+</body>
+</html>"#;
+
+/// A credential captured from a login redirect.
+pub enum Credential {
+    /// An access token, as provided by services using the implicit grant flow.
+    Token(String),
+    /// An authorization code that still needs to be exchanged for an access token.
+    Code(String),
+}
+
+/// The outcome of a request made to the listener.
+enum Redirect {
+    Credential(Credential),
+    /// The user didn't authorize the login, with the reason given by the remote service.
+    Denied(String),
+    /// The request doesn't contain anything we need, such as a favicon request or a connection that was opened
+    /// ahead of time and never used.
+    Ignored,
+}
+
+pub struct CallbackListener {
+    listener: TcpListener,
+    state: Arc<str>,
+}
+
+impl CallbackListener {
+    /// Start listening for the login redirect on `port`.
+    ///
+    /// This must be called from within the async runtime.
+    pub fn bind(port: u16) -> Result<Self> {
+        const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+        let listener = StdTcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .with_context(|| anyhow!("failed to listen for login on port {}", port))?;
+
+        listener.set_nonblocking(true)?;
+
+        let mut rng = rand::thread_rng();
+
+        let state = (0..STATE_LEN)
+            .map(|_| CHARS[rng.gen_range(0..CHARS.len())] as char)
+            .collect::<String>();
+
+        Ok(Self {
+            listener: TcpListener::from_std(listener)?,
+            state: state.into(),
+        })
+    }
+
+    /// Returns `auth_url` with the OAuth state the redirect needs to be made with added to it.
+    ///
+    /// Redirects without the state are rejected, so other sites can't log the user in to an account of their choosing.
+    pub fn auth_url(&self, auth_url: &str) -> String {
+        format!("{}&state={}", auth_url, self.state)
+    }
+
+    /// Wait for the remote service to redirect the user's browser back with their credential.
+    ///
+    /// Requests that can't be handled are passed to `on_error` and skipped.
+    pub async fn wait<F>(self, on_error: F) -> Result<Credential>
+    where
+        F: FnMut(Error),
+    {
+        time::timeout(LOGIN_TIMEOUT, self.accept_credential(on_error))
+            .await
+            .map_err(|_| anyhow!("timed out waiting for login in browser"))?
+    }
+
+    async fn accept_credential<F>(self, mut on_error: F) -> Result<Credential>
+    where
+        F: FnMut(Error),
+    {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let listener = self.listener;
+        let state = self.state;
+
+        // Each connection is handled separately so one that never sends a request can't hold up the rest
+        let mut accept_task: ScopedTask<Result<()>> = task::spawn(async move {
+            loop {
+                let mut stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(err) => {
+                        break Err(Error::new(err).context("failed to accept login connection"))
+                    }
+                };
+
+                let sender = sender.clone();
+                let state = Arc::clone(&state);
+
+                task::spawn(async move {
+                    let result =
+                        time::timeout(REQUEST_TIMEOUT, Self::handle_request(&mut stream, &state))
+                            .await
+                            .unwrap_or(Ok(Redirect::Ignored));
+
+                    sender.send(result).ok();
+                });
+            }
+        })
+        .into();
+
+        while let Some(result) = receiver.recv().await {
+            match result {
+                Ok(Redirect::Credential(credential)) => return Ok(credential),
+                Ok(Redirect::Denied(error)) => {
+                    return Err(anyhow!("login was not authorized: {}", error))
+                }
+                Ok(Redirect::Ignored) => (),
+                Err(err) => on_error(err),
+            }
+        }
+
+        // The channel only closes once the listener stops accepting connections
+        match (&mut *accept_task).await {
+            Ok(Err(err)) => Err(err),
+            _ => Err(anyhow!("stopped listening for login")),
+        }
+    }
+
+    async fn handle_request(stream: &mut TcpStream, state: &str) -> Result<Redirect> {
+        let target = match read_request_target(stream).await? {
+            Some(target) => target,
+            None => return Ok(Redirect::Ignored),
+        };
+
+        let mut target = target.splitn(2, '?');
+        let path = target.next().unwrap_or_default();
+        let query = target.next().unwrap_or_default();
+
+        // Tokens and authorization codes only contain URL-safe characters, so they don't need to be decoded
+        let param = |name: &str| {
+            query
+                .split('&')
+                .find_map(|param| param.strip_prefix(name)?.strip_prefix('='))
+        };
+
+        let is_redirect = matches!(path, CALLBACK_PATH | TOKEN_PATH)
+            && (param("error").is_some()
+                || param("code").is_some()
+                || param("access_token").is_some());
+
+        if is_redirect && param("state") != Some(state) {
+            respond(stream, "400 Bad Request", FAILED_PAGE).await?;
+            return Err(anyhow!("received login redirect with the wrong state"));
+        }
+
+        if let Some(error) = param("error") {
+            respond(stream, "200 OK", FAILED_PAGE).await?;
+            return Ok(Redirect::Denied(error.into()));
+        }
+
+        let credential = match (path, param("code"), param("access_token")) {
+            (CALLBACK_PATH, Some(code), _) => Credential::Code(code.into()),
+            (CALLBACK_PATH, None, _) => {
+                respond(stream, "200 OK", RELAY_PAGE).await?;
+                return Ok(Redirect::Ignored);
+            }
+            (TOKEN_PATH, _, Some(token)) => Credential::Token(token.into()),
+            _ => {
+                respond(stream, "404 Not Found", "").await?;
+                return Ok(Redirect::Ignored);
+            }
+        };
+
+        respond(stream, "200 OK", SUCCESS_PAGE).await?;
+        Ok(Redirect::Credential(credential))
+    }
+}
+
+/// Read the request line from `stream` and return the path and query it's for.
+///
+/// Returns `None` if the connection is closed without sending anything.
+async fn read_request_target(stream: &mut TcpStream) -> Result<Option<String>> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+
+    // Only the request line is needed, which is everything before the first line break
+    while !request.contains(&b'\n') {
+        let read = stream
+            .read(&mut buffer)
+            .await
+            .context("failed to read login request")?;
+
+        if read == 0 && request.is_empty() {
+            return Ok(None);
+        }
+
+        if read == 0 || request.len() + read > MAX_REQUEST_LEN {
+            return Err(anyhow!("received malformed login request"));
+        }
+
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+
+    // The request line looks like: GET /callback?code=abc HTTP/1.1
+    request
+        .split_whitespace()
+        .nth(1)
+        .map(|target| Some(target.into()))
+        .ok_or_else(|| anyhow!("received malformed login request"))
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .context("failed to respond to login request")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream as StdTcpStream;
+
+    fn request(port: u16, target: &str) {
+        let mut stream = StdTcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", target).unwrap();
+
+        // Wait for the listener to respond and close the connection before making the next request
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+    }
+
+    /// Make a request to each target, where `{state}` is replaced with the listener's OAuth state, and return the
+    /// credential captured along with the number of requests that were skipped because of an error.
+    fn capture(targets: &'static [&'static str]) -> (Result<Credential>, usize) {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let listener = CallbackListener::bind(0).unwrap();
+            let port = listener.listener.local_addr().unwrap().port();
+            let state = listener.state.to_string();
+
+            // An idle connection, like the ones browsers open ahead of time, shouldn't hold up the real request
+            let idle = StdTcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+
+            let requests = tokio::task::spawn_blocking(move || {
+                for target in targets {
+                    request(port, &target.replace("{state}", &state));
+                }
+            });
+
+            let mut errors = 0;
+            let credential = listener.wait(|_| errors += 1).await;
+
+            requests.await.unwrap();
+            drop(idle);
+
+            (credential, errors)
+        })
+    }
+
+    #[test]
+    fn token_is_relayed_from_fragment() {
+        let targets = &[
+            "/favicon.ico",
+            "/callback",
+            "/token?access_token=abc.def&token_type=Bearer&state={state}",
+        ];
+
+        match capture(targets) {
+            (Ok(Credential::Token(token)), 0) => assert_eq!(token, "abc.def"),
+            _ => panic!("expected token"),
+        }
+    }
+
+    #[test]
+    fn code_is_captured() {
+        match capture(&["/callback?code=xyz&state={state}"]) {
+            (Ok(Credential::Code(code)), 0) => assert_eq!(code, "xyz"),
+            _ => panic!("expected code"),
+        }
+    }
+
+    #[test]
+    fn redirects_with_the_wrong_state_are_skipped() {
+        let targets = &[
+            "/callback?code=forged&state=1",
+            "/callback?error=access_denied",
+            "/callback?code=xyz&state={state}",
+        ];
+
+        match capture(targets) {
+            (Ok(Credential::Code(code)), 2) => assert_eq!(code, "xyz"),
+            _ => panic!("expected code"),
+        }
+    }
+
+    #[test]
+    fn denied_logins_are_reported() {
+        let (credential, _) = capture(&["/callback?error=access_denied&state={state}"]);
+        assert!(credential.is_err());
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     file::{FileFormat, SaveDir, SerializedFile},
     key::Key,
 };
+use anyhow::{anyhow, Result};
 use crossterm::event::KeyCode;
 use serde::ser::Serializer;
 use serde::{
@@ -12,12 +13,22 @@ use std::ops::Mul;
 use std::path::PathBuf;
 use std::result;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Config {
     pub series_dir: PathBuf,
     pub reset_dates_on_rewatch: bool,
     pub episode: EpisodeConfig,
     pub tui: TuiConfig,
+    /// The client ID of the application to use with the `MyAnimeList` API.
+    #[serde(default)]
+    pub mal_client_id: Option<String>,
+    #[serde(default)]
+    pub login_callback: LoginCallbackConfig,
+    /// The file to record every AniList query and its response to.
+    ///
+    /// This is set with the `--record` flag rather than in the config file.
+    #[serde(skip)]
+    pub record_requests: Option<PathBuf>,
 }
 
 impl Config {
@@ -30,6 +41,52 @@ impl Config {
             ..Self::default()
         }
     }
+
+    /// Returns the `MyAnimeList` client ID if it has been set.
+    pub fn mal_client_id(&self) -> Result<&str> {
+        self.mal_client_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("mal_client_id must be set in your config to use MyAnimeList"))
+    }
+}
+
+/// Settings for logging in through the browser without having to paste a token or code.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct LoginCallbackConfig {
+    /// The port on localhost to listen for the login redirect on.
+    pub port: u16,
+    /// The client ID of an AniList application whose redirect URL is `http://localhost:<port>/callback`.
+    ///
+    /// The default application redirects to a page showing the token instead, so it can't be used.
+    #[serde(default)]
+    pub anilist_client_id: Option<u32>,
+}
+
+impl LoginCallbackConfig {
+    /// Returns the URL the remote service needs to redirect to after logging in.
+    pub fn redirect_url(&self) -> String {
+        format!(
+            "http://localhost:{}{}",
+            self.port,
+            crate::callback::CALLBACK_PATH
+        )
+    }
+
+    /// Returns the AniList client ID to use when logging in through the browser if it has been set.
+    pub fn anilist_client_id(&self) -> Result<u32> {
+        self.anilist_client_id.ok_or_else(|| {
+            anyhow!("login_callback.anilist_client_id must be set in your config to login to AniList through the browser")
+        })
+    }
+}
+
+impl Default for LoginCallbackConfig {
+    fn default() -> Self {
+        Self {
+            port: 7421,
+            anilist_client_id: None,
+        }
+    }
 }
 
 impl Default for Config {
@@ -43,6 +100,9 @@ impl Default for Config {
             reset_dates_on_rewatch: false,
             episode: EpisodeConfig::default(),
             tui: TuiConfig::default(),
+            mal_client_id: None,
+            login_callback: LoginCallbackConfig::default(),
+            record_requests: None,
         }
     }
 }
@@ -61,7 +121,7 @@ impl SerializedFile for Config {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct EpisodeConfig {
     #[serde(rename = "percent_watched_to_progress")]
     pub pcnt_must_watch: Percentage,
@@ -156,12 +216,12 @@ impl Mul<Percentage> for f32 {
     }
 }
 
-#[derive(Default, Deserialize, Serialize)]
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct TuiConfig {
     pub keys: TuiKeys,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct TuiKeys {
     pub play_next_episode: Key,
 }
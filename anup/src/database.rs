@@ -4,7 +4,7 @@ use diesel::connection::SimpleConnection;
 use diesel::deserialize::{self, FromSql};
 use diesel::prelude::*;
 use diesel::serialize::{self, Output, ToSql};
-use diesel::sql_types::{Nullable, Text};
+use diesel::sql_types::{Integer, Nullable, Text};
 use smallvec::SmallVec;
 use std::io::Write;
 use std::ops::Deref;
@@ -28,6 +28,18 @@ pub mod schema {
             title_romaji -> Text,
             episodes -> SmallInt,
             episode_length_mins -> SmallInt,
+            title_english -> Nullable<Text>,
+            title_native -> Nullable<Text>,
+            synonyms -> Nullable<Text>,
+            season -> Nullable<SmallInt>,
+            season_year -> Nullable<SmallInt>,
+            airing_status -> Nullable<SmallInt>,
+            genres -> Nullable<Text>,
+            studios -> Nullable<Text>,
+            average_score -> Nullable<SmallInt>,
+            is_adult -> Bool,
+            next_episode -> Nullable<SmallInt>,
+            next_episode_airs_at -> Nullable<BigInt>,
         }
     }
 
@@ -41,10 +53,54 @@ pub mod schema {
             start_date -> Nullable<Date>,
             end_date -> Nullable<Date>,
             needs_sync -> Bool,
+            notes -> Nullable<Text>,
+            private -> Bool,
+            hidden -> Bool,
+            custom_lists -> Nullable<Text>,
+            remote_updated_at -> Nullable<BigInt>,
+            modified_at -> Nullable<BigInt>,
+            favourite -> Bool,
+            extended_synced -> Bool,
+            base_status -> Nullable<SmallInt>,
+            base_score -> Nullable<SmallInt>,
+            base_notes -> Nullable<Text>,
+            base_private -> Nullable<Bool>,
+            base_hidden -> Nullable<Bool>,
+            base_custom_lists -> Nullable<Text>,
+        }
+    }
+
+    table! {
+        pending_deletions (id, service, username) {
+            id -> Integer,
+            service -> Text,
+            username -> Text,
+        }
+    }
+
+    table! {
+        unmatched_imports {
+            id -> Integer,
+            list_entry -> Binary,
         }
     }
 }
 
+/// Statements to upgrade an existing database to each schema version, in ascending order.
+///
+/// The schema file always creates tables at the latest version, so these only need to be ran on databases
+/// created before it.
+const MIGRATIONS: [(i32, &str); 8] = [
+    (2, include_str!("../sql/migrations/2.sql")),
+    (3, include_str!("../sql/migrations/3.sql")),
+    (4, include_str!("../sql/migrations/4.sql")),
+    (5, include_str!("../sql/migrations/5.sql")),
+    (6, include_str!("../sql/migrations/6.sql")),
+    (7, include_str!("../sql/migrations/7.sql")),
+    (8, include_str!("../sql/migrations/8.sql")),
+    (9, include_str!("../sql/migrations/9.sql")),
+];
+
 pub struct Database(SqliteConnection);
 
 impl Database {
@@ -55,12 +111,33 @@ impl Database {
         conn.batch_execute(include_str!("../sql/pragmas.sql"))
             .context("executing pragmas")?;
 
+        Self::migrate(&conn).context("migrating schema")?;
+
         conn.batch_execute(include_str!("../sql/schema.sql"))
             .context("executing schema")?;
 
         Ok(Self(conn))
     }
 
+    fn migrate(conn: &SqliteConnection) -> Result<()> {
+        let version: i32 = diesel::dsl::sql::<Integer>("PRAGMA user_version").get_result(conn)?;
+
+        // A version of 0 means that the database is new and will be created by the schema file
+        if version == 0 {
+            return Ok(());
+        }
+
+        conn.transaction(|| {
+            for (migration_version, migration) in &MIGRATIONS {
+                if version < *migration_version {
+                    conn.batch_execute(migration)?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
     pub fn validated_path() -> Result<PathBuf> {
         let mut path = SaveDir::LocalData.validated_dir_path()?.to_path_buf();
         path.push("data.sqlite");
@@ -140,3 +217,54 @@ impl Deref for PlayerArgs {
         &self.0
     }
 }
+
+/// A list of strings stored in a single column.
+#[derive(Clone, Debug, Default, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub struct TextList(Vec<String>);
+
+impl<DB> FromSql<Nullable<Text>, DB> for TextList
+where
+    DB: diesel::backend::Backend,
+    String: FromSql<Text, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        match bytes {
+            Some(_) => {
+                let items = String::from_sql(bytes)?
+                    .split(";;")
+                    .filter(|item| !item.is_empty())
+                    .map(Into::into)
+                    .collect();
+
+                Ok(Self(items))
+            }
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+impl<DB> ToSql<Text, DB> for TextList
+where
+    DB: diesel::backend::Backend,
+    String: ToSql<Text, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        let value = self.0.join(";;");
+        value.to_sql(out)
+    }
+}
+
+impl From<Vec<String>> for TextList {
+    fn from(value: Vec<String>) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for TextList {
+    type Target = Vec<String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
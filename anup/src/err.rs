@@ -4,3 +4,11 @@ use std::io;
 pub fn is_file_nonexistant(err: &Error) -> bool {
     matches!(err.downcast_ref::<io::Error>(), Some(err) if err.kind() == io::ErrorKind::NotFound)
 }
+
+/// Returns true if `err` was caused by the remote service rejecting the user's access token.
+pub fn is_invalid_token(err: &Error) -> bool {
+    matches!(
+        err.downcast_ref::<anime::Error>(),
+        Some(anime::Error::InvalidToken)
+    )
+}
@@ -12,6 +12,7 @@
 #[macro_use]
 extern crate diesel;
 
+mod callback;
 mod config;
 mod database;
 mod err;
@@ -26,18 +27,26 @@ mod util;
 use crate::config::Config;
 use crate::database::Database;
 use crate::file::SerializedFile;
+use crate::remote::RemoteLogin;
 use crate::series::config::SeriesConfig;
-use crate::series::entry::SeriesEntry;
+use crate::series::entry::{EntryConflict, PendingDeletion, SeriesEntry};
 use crate::series::info::SeriesInfo;
+use crate::series::mal_xml::{self, MalEntry};
 use crate::series::{LastWatched, LoadedSeries, Series};
-use crate::user::Users;
-use anime::remote::Remote;
+use crate::user::{RemoteType, Users};
+use anime::remote::{Remote, RemoteService, SeriesID};
 use anyhow::{anyhow, Context, Result};
 use argh::FromArgs;
 use chrono::Utc;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 const ANILIST_CLIENT_ID: u32 = 427;
 
+/// The number of list entries to retrieve from the remote service at once when syncing.
+const SYNC_CHUNK_SIZE: usize = 50;
+
 #[derive(FromArgs)]
 /// Play, manage, and sync anime from the terminal.
 pub struct Args {
@@ -56,6 +65,26 @@ pub struct Args {
     /// syncronize changes made while offline
     #[argh(switch)]
     pub sync: bool,
+
+    /// copy the list of the last used account to your account on another service (anilist, kitsu, or myanimelist)
+    #[argh(option)]
+    pub migrate_to: Option<RemoteType>,
+
+    /// export the series in your library to a MyAnimeList XML file
+    #[argh(option)]
+    pub export_mal: Option<PathBuf>,
+
+    /// import list entries for the series in your library from a MyAnimeList XML file
+    #[argh(option)]
+    pub import_mal: Option<PathBuf>,
+
+    /// record every AniList query and its response to a file that can be used with --replay
+    #[argh(option)]
+    pub record: Option<PathBuf>,
+
+    /// answer every AniList query with the responses recorded to a file with --record instead of logging in
+    #[argh(option)]
+    pub replay: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
@@ -75,6 +104,12 @@ async fn run() -> Result<()> {
         play_episode(&args).await
     } else if args.sync {
         sync(&args)
+    } else if let Some(service) = args.migrate_to {
+        migrate(&args, service)
+    } else if let Some(path) = &args.export_mal {
+        export_mal(&args, path)
+    } else if let Some(path) = &args.import_mal {
+        import_mal(&args, path)
     } else {
         tui::run(&args).await
     }
@@ -83,19 +118,50 @@ async fn run() -> Result<()> {
 /// Initialize a new remote service specified by `args`.
 ///
 /// If there are no users, returns Ok(None).
-fn init_remote(args: &Args) -> Result<Option<Remote>> {
-    use anime::remote::anilist::{AniList, Auth};
-
-    if args.offline {
-        Ok(Some(Remote::offline()))
+fn init_remote(args: &Args, config: &Config) -> Result<Option<Remote>> {
+    if let Some(path) = &args.replay {
+        remote::replay(path).map(Some)
+    } else if args.offline {
+        let service = Users::load_or_create()?.last_used_service();
+        Ok(Some(remote::offline(service)))
     } else {
-        let token = match Users::load_or_create()?.take_last_used_token() {
-            Some(token) => token,
+        let config = &Config {
+            record_requests: args.record.clone(),
+            ..config.clone()
+        };
+
+        let mut users = Users::load_or_create()?;
+
+        let (user, mut token) = match users.last_used_user() {
+            Some((user, token)) => (user.clone(), token.clone()),
             None => return Ok(None),
         };
 
-        let auth = Auth::retrieve(token)?;
-        Ok(Some(AniList::Authenticated(auth).into()))
+        if let Some(refresh_token) = users.due_refresh_token(&user) {
+            match remote::refresh_token(&user, refresh_token, config) {
+                Ok(grant) => {
+                    token = grant.token.clone();
+                    users.set_grant(user.clone(), grant);
+                    users.save()?;
+                }
+                // The current token may still be valid, so this shouldn't prevent logging in
+                Err(err) => eprintln!("warning: {:#}", err),
+            }
+        }
+
+        if let Some(warning) = users.token_status(&user).warning(&user) {
+            eprintln!("warning: {}\nadd the user again in the TUI", warning);
+        }
+
+        match RemoteLogin::from_user(user.clone(), token).login(config) {
+            Ok(remote) => Ok(Some(remote)),
+            Err(err) if err::is_invalid_token(&err) => {
+                users.set_token_rejected(&user, true);
+                users.save()?;
+                Err(err)
+            }
+            Err(err) => Err(err),
+        }
     }
 }
 
@@ -104,28 +170,323 @@ fn sync(args: &Args) -> Result<()> {
         return Err(anyhow!("must be online to run this command"));
     }
 
+    let config = Config::load_or_create()?;
     let db = Database::open().context("failed to open database")?;
     let mut list_entries = SeriesEntry::entries_that_need_sync(&db)?;
+    let deletions = PendingDeletion::load_all(&db)?;
 
-    if list_entries.is_empty() {
+    if list_entries.is_empty() && deletions.is_empty() {
         return Ok(());
     }
 
-    let remote =
-        init_remote(&args)?.ok_or_else(|| anyhow!("no users found\nadd one in the TUI"))?;
+    let remote = init_remote(&args, &config)?
+        .ok_or_else(|| anyhow!("no users found\nadd one in the TUI"))?;
+
+    // Failures are reported as they happen so the rest of the series can still be synced
+    let mut failed = 0;
+
+    for chunk in list_entries.chunks_mut(SYNC_CHUNK_SIZE) {
+        let ids = chunk
+            .iter()
+            .map(|entry| entry.id() as SeriesID)
+            .collect::<Vec<_>>();
+
+        let mut remote_entries = match remote.get_list_entries(&ids) {
+            Ok(remote_entries) => remote_entries,
+            Err(err) => {
+                eprintln!(
+                    "warning: failed to get the list entries of {} anime: {}",
+                    chunk.len(),
+                    err
+                );
+
+                failed += chunk.len();
+                continue;
+            }
+        };
+
+        for entry in chunk {
+            match SeriesInfo::load(&db, entry.id()) {
+                Ok(info) => println!("{} is being synced..", info.title_preferred),
+                Err(err) => eprintln!(
+                    "warning: failed to get info for anime with ID {}: {}",
+                    entry.id(),
+                    err
+                ),
+            }
+
+            let remote_entry = remote_entries
+                .iter()
+                .position(|remote_entry| remote_entry.id == entry.id() as SeriesID)
+                .map(|index| remote_entries.swap_remove(index));
+
+            match entry.sync_to_remote_with(&remote, remote_entry) {
+                Ok(()) => (),
+                Err(err) if err.is::<EntryConflict>() => {
+                    eprintln!(
+                        "warning: anime with ID {} has conflicting changes on the remote service\nuse the synctoremote or syncfromremote command in the TUI to keep one side",
+                        entry.id()
+                    );
+                    failed += 1;
+                    continue;
+                }
+                Err(err) => {
+                    eprintln!(
+                        "warning: failed to sync anime with ID {}: {:#}",
+                        entry.id(),
+                        err
+                    );
+
+                    failed += 1;
+                    continue;
+                }
+            }
+
+            if let Err(err) = entry.save(&db) {
+                eprintln!(
+                    "warning: failed to save anime with ID {}: {}",
+                    entry.id(),
+                    err
+                );
+
+                failed += 1;
+            }
+        }
+    }
+
+    for deletion in &deletions {
+        if !deletion.belongs_to(&remote) {
+            continue;
+        }
+
+        println!("anime with ID {} is being deleted..", deletion.id());
 
-    for entry in &mut list_entries {
-        match SeriesInfo::load(&db, entry.id()) {
-            Ok(info) => println!("{} is being synced..", info.title_preferred),
-            Err(err) => eprintln!(
-                "warning: failed to get info for anime with ID {}: {}",
-                entry.id(),
+        if let Err(err) = deletion.sync_to_remote(&remote, &db) {
+            eprintln!(
+                "warning: failed to delete anime with ID {}: {:#}",
+                deletion.id(),
                 err
-            ),
+            );
+
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow!("{} anime failed to sync", failed));
+    }
+
+    Ok(())
+}
+
+fn migrate(args: &Args, service: RemoteType) -> Result<()> {
+    if args.offline {
+        return Err(anyhow!("must be online to run this command"));
+    }
+
+    let config = Config::load_or_create()?;
+    let users = Users::load_or_create()?;
+
+    let mut targets = users
+        .get()
+        .iter()
+        .filter(|(user, _)| user.service == service);
+
+    let (user, token) = match (targets.next(), targets.next()) {
+        (Some(target), None) => target,
+        (None, _) => {
+            return Err(anyhow!(
+                "no {} account found\nadd one in the TUI",
+                service.as_str()
+            ))
+        }
+        (Some(_), Some(_)) => {
+            return Err(anyhow!(
+                "more than one {} account found\nremove the ones that shouldn't be migrated to in the TUI",
+                service.as_str()
+            ))
+        }
+    };
+
+    let from = init_remote(&args, &config)?
+        .ok_or_else(|| anyhow!("no users found\nadd one in the TUI"))?;
+
+    if user.is_logged_in(&from) {
+        return Err(anyhow!("can't migrate a list to the same account"));
+    }
+
+    let to = RemoteLogin::from_user(user.clone(), token.clone()).login(&config)?;
+
+    println!(
+        "copying list to {} on {}..",
+        user.username,
+        service.as_str()
+    );
+
+    let migration = anime::remote::migrate_list(&from, &to)?;
+
+    println!("{} entries copied", migration.migrated.len());
+
+    if !migration.unmapped.is_empty() {
+        eprintln!(
+            "warning: {} entries could not be found on {}:",
+            migration.unmapped.len(),
+            service.as_str()
+        );
+
+        for list_entry in &migration.unmapped {
+            eprintln!(
+                "{} (ID {})",
+                list_entry.info.title.preferred, list_entry.entry.id
+            );
         }
+    }
+
+    if !migration.failed.is_empty() {
+        eprintln!(
+            "warning: {} entries failed to be copied:",
+            migration.failed.len()
+        );
+
+        for (entry, err) in &migration.failed {
+            eprintln!("{} ID {}: {}", service.as_str(), entry.id, err);
+        }
+
+        return Err(anyhow!(
+            "{} entries failed to be copied
+run the migration again to retry them",
+            migration.failed.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Write every list entry in the library to a MyAnimeList XML file at `path`.
+///
+/// Series are written with their MyAnimeList ID, so the current service must be able to map its IDs to them.
+fn export_mal(args: &Args, path: &Path) -> Result<()> {
+    if args.offline {
+        return Err(anyhow!("must be online to run this command"));
+    }
+
+    let config = Config::load_or_create()?;
+    let db = Database::open().context("failed to open database")?;
+    let entries = SeriesEntry::load_all(&db)?;
+
+    let remote = init_remote(&args, &config)?
+        .ok_or_else(|| anyhow!("no users found\nadd one in the TUI"))?;
+
+    let ids = entries
+        .iter()
+        .map(|entry| entry.id() as SeriesID)
+        .collect::<Vec<_>>();
+
+    let mal_ids = remote
+        .get_mal_ids(&ids)
+        .context("failed to get MyAnimeList IDs from the current service")?;
+
+    let mut exported = Vec::with_capacity(entries.len());
+
+    for mut entry in entries {
+        let info = SeriesInfo::load(&db, entry.id()).ok();
+        let title = info.as_ref().map_or_else(
+            || format!("anime with ID {}", entry.id()),
+            |info| info.title_preferred.clone(),
+        );
+
+        let mal_id = match mal_ids.get(&(entry.id() as SeriesID)) {
+            Some(&mal_id) => mal_id,
+            None => {
+                eprintln!("warning: {} has no MyAnimeList ID and was skipped", title);
+                continue;
+            }
+        };
+
+        let mut mal_entry: anime::remote::SeriesEntry = (&mut entry).into();
+        mal_entry.id = mal_id;
+
+        exported.push(MalEntry {
+            entry: mal_entry,
+            episodes: info.map_or(0, |info| info.episodes.max(0) as u32),
+            title,
+        });
+    }
+
+    fs::write(path, mal_xml::export(&exported))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+
+    println!("{} entries exported to {}", exported.len(), path.display());
+    Ok(())
+}
+
+/// Read a MyAnimeList XML file at `path` and fill in the list entries of the series in the library with it.
+///
+/// The imported entries are marked as needing to be synced, so they're uploaded with the next sync.
+/// Anime that haven't been added to the library are skipped.
+///
+/// In offline mode, the series are found with the MyAnimeList IDs cached while online.
+#[allow(clippy::cast_possible_wrap)]
+fn import_mal(args: &Args, path: &Path) -> Result<()> {
+    let xml =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    let imported =
+        mal_xml::parse(&xml).with_context(|| format!("failed to parse {}", path.display()))?;
+
+    let config = Config::load_or_create()?;
+    let db = Database::open().context("failed to open database")?;
+
+    let remote = init_remote(&args, &config)?
+        .ok_or_else(|| anyhow!("no users found\nadd one in the TUI"))?;
+
+    let mal_ids = imported.iter().map(MalEntry::id).collect::<Vec<_>>();
+
+    let ids = remote
+        .find_by_mal_ids(&mal_ids)
+        .context("failed to find MyAnimeList IDs on the current service")?;
+
+    let library = SeriesConfig::load_all(&db)?
+        .into_iter()
+        .map(|config| config.id)
+        .collect::<HashSet<_>>();
 
-        entry.sync_to_remote(&remote)?;
+    let mut count = 0;
+    let mut skipped = Vec::new();
+
+    for mal_entry in &imported {
+        let id = match ids.get(&mal_entry.id()) {
+            Some(&id) if library.contains(&(id as i32)) => id as i32,
+            _ => {
+                skipped.push(mal_entry);
+                continue;
+            }
+        };
+
+        let mut entry = match SeriesEntry::load(&db, id) {
+            Ok(entry) => entry,
+            // The series may have been added without an entry
+            Err(diesel::result::Error::NotFound) => SeriesEntry::from(id),
+            Err(err) => return Err(err).context("failed to load series entry"),
+        };
+
+        entry.import(&mal_entry.entry);
         entry.save(&db)?;
+
+        count += 1;
+    }
+
+    println!("{} entries imported\nrun with --sync to upload them", count);
+
+    if !skipped.is_empty() {
+        eprintln!(
+            "warning: {} entries are not in your library and were skipped:",
+            skipped.len()
+        );
+
+        for mal_entry in skipped {
+            eprintln!("{} (MyAnimeList ID {})", mal_entry.title, mal_entry.id());
+        }
     }
 
     Ok(())
@@ -138,8 +499,8 @@ async fn play_episode(args: &Args) -> Result<()> {
     let db = Database::open().context("failed to open database")?;
     let mut last_watched = LastWatched::load()?;
 
-    let remote =
-        init_remote(&args)?.ok_or_else(|| anyhow!("no users found\nadd one in the TUI"))?;
+    let remote = init_remote(&args, &config)?
+        .ok_or_else(|| anyhow!("no users found\nadd one in the TUI"))?;
 
     let desired_series = args
         .series
@@ -183,10 +544,16 @@ async fn play_episode(args: &Args) -> Result<()> {
         if series.data.entry.status() == Status::Completed {
             println!("{} completed!", series.data.info.title_preferred);
         } else {
+            let episodes = series
+                .data
+                .info
+                .episode_count()
+                .map_or_else(|| "??".into(), |episodes| episodes.to_string());
+
             println!(
                 "{}/{} of {} completed",
                 series.data.entry.watched_episodes(),
-                series.data.info.episodes,
+                episodes,
                 series.data.info.title_preferred
             );
         }
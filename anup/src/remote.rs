@@ -1,19 +1,200 @@
-use anime::remote::{AccessToken, Remote};
-use anyhow::{anyhow, Result};
+use crate::config::Config;
+use crate::file::SaveDir;
+use crate::user::{RemoteType, UserInfo};
+use anime::remote::cache::{Cached, InfoCache};
+use anime::remote::replay::{Cassette, Recorder, Replay};
+use anime::remote::transport::{AttoTransport, Client, ClientConfig};
+use anime::remote::{AccessToken, AsyncRemote, Remote, TokenGrant};
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+use tokio::task;
 
 pub type Username = String;
 
 pub enum RemoteLogin {
     AniList(Username, AccessToken),
+    Kitsu(Username, AccessToken),
+    MyAnimeList(Username, AccessToken),
+}
+
+impl RemoteLogin {
+    pub fn from_user(user: UserInfo, token: AccessToken) -> Self {
+        match user.service {
+            RemoteType::AniList => Self::AniList(user.username, token),
+            RemoteType::Kitsu => Self::Kitsu(user.username, token),
+            RemoteType::MyAnimeList => Self::MyAnimeList(user.username, token),
+        }
+    }
+
+    pub fn username(&self) -> &Username {
+        match self {
+            Self::AniList(username, _)
+            | Self::Kitsu(username, _)
+            | Self::MyAnimeList(username, _) => username,
+        }
+    }
+
+    /// Returns the user being logged in as.
+    pub fn user(&self) -> UserInfo {
+        let service = match self {
+            Self::AniList(_, _) => RemoteType::AniList,
+            Self::Kitsu(_, _) => RemoteType::Kitsu,
+            Self::MyAnimeList(_, _) => RemoteType::MyAnimeList,
+        };
+
+        UserInfo::new(service, self.username())
+    }
+
+    /// Authenticate with the remote service and return it.
+    ///
+    /// The returned remote caches the series info it retrieves for use in offline mode.
+    /// Note that this will block until the remote service responds.
+    pub fn login(self, config: &Config) -> Result<Remote> {
+        let service = self.user().service;
+        self.login_uncached(config)
+            .map(|remote| with_cache(remote, service))
+    }
+
+    fn login_uncached(self, config: &Config) -> Result<Remote> {
+        match self {
+            Self::AniList(_, token) => {
+                use anime::remote::anilist::{AniList, Auth};
+
+                let client = anilist_client(config);
+                let auth =
+                    Auth::retrieve_with(&client, token).context("failed to login to AniList")?;
+
+                Ok(AniList::authenticated(auth).with_client(client).into())
+            }
+            Self::Kitsu(_, token) => {
+                use anime::remote::kitsu::{Auth, Kitsu};
+
+                let auth = Auth::retrieve(token).context("failed to login to Kitsu")?;
+                Ok(Kitsu::authenticated(auth).into())
+            }
+            Self::MyAnimeList(_, token) => {
+                use anime::remote::myanimelist::{Auth, MyAnimeList};
+
+                let client_id = config.mal_client_id()?;
+                let auth =
+                    Auth::retrieve(client_id, token).context("failed to login to MyAnimeList")?;
+
+                Ok(MyAnimeList::authenticated(client_id, auth).into())
+            }
+        }
+    }
+
+    /// Authenticate with the remote service without blocking the async runtime.
+    pub async fn login_async(self, config: Config) -> Result<AsyncRemote> {
+        let remote = task::spawn_blocking(move || self.login(&config)).await??;
+        Ok(remote.into())
+    }
+}
+
+/// Returns the client to send AniList requests through, which records them when the `--record` flag is used.
+fn anilist_client(config: &Config) -> Client {
+    match &config.record_requests {
+        Some(path) => Client::with_transport(
+            ClientConfig::default(),
+            Recorder::new(AttoTransport, path.clone()),
+        ),
+        None => Client::default(),
+    }
+}
+
+/// Get a new access token for `user` with the `refresh_token` their service granted along with the last one.
+///
+/// Note that this will block until the remote service responds.
+pub fn refresh_token(
+    user: &UserInfo,
+    refresh_token: &AccessToken,
+    config: &Config,
+) -> Result<TokenGrant> {
+    match user.service {
+        RemoteType::AniList => Err(anyhow!("AniList logins can't be refreshed")),
+        RemoteType::Kitsu => {
+            use anime::remote::kitsu::Auth;
+
+            Auth::refresh(refresh_token).context("failed to refresh Kitsu login")
+        }
+        RemoteType::MyAnimeList => {
+            use anime::remote::myanimelist::Auth;
+
+            let client_id = config.mal_client_id()?;
+
+            Auth::refresh(client_id, refresh_token).context("failed to refresh MyAnimeList login")
+        }
+    }
+}
+
+/// Returns the offline remote, which answers series info requests from the info cached while logged in to `service`.
+///
+/// Series IDs are only unique to each service, so nothing is cached when `service` is None.
+pub fn offline(service: Option<RemoteType>) -> Remote {
+    match service {
+        Some(service) => with_cache(Remote::offline(), service),
+        None => Remote::offline(),
+    }
+}
+
+/// Returns a remote that answers every request from the AniList queries recorded to `path` with `--record`.
+///
+/// Recordings made without a user logged in only contain series info, so an unauthenticated remote is used for them.
+pub fn replay(path: &Path) -> Result<Remote> {
+    let cassette = Cassette::load(path)
+        .with_context(|| format!("failed to load recorded requests from {}", path.display()))?;
+
+    let replay = Replay::authenticated(cassette.clone()).unwrap_or_else(|_| Replay::new(cassette));
+    Ok(replay.into())
+}
+
+/// Wrap `remote` so the series info it retrieves from `service` is cached for use in offline mode.
+fn with_cache(remote: Remote, service: RemoteType) -> Remote {
+    let filename = format!("info_cache_{}.json", service.as_str().to_ascii_lowercase());
+
+    let path = match SaveDir::LocalData.validated_dir_path() {
+        Ok(dir) => dir.join(filename),
+        Err(_) => return remote,
+    };
+
+    // The cache is only an aid for offline mode, so a missing or invalid one can simply be started over
+    let cache = InfoCache::load(&path).unwrap_or_default();
+
+    Cached::new(remote, cache).save_to(path).into()
+}
+
+/// Save the series info `remote` has cached if it has changed.
+///
+/// The cache is saved automatically as well, but this is the only way to find out if saving it failed.
+pub fn save_cache(remote: &Remote) -> Result<()> {
+    match remote {
+        Remote::Cached(cached) => cached
+            .save()
+            .context("failed to save the series info cache"),
+        _ => Ok(()),
+    }
 }
 
 pub enum RemoteStatus {
     LoggingIn(Username),
-    LoggedIn(Remote),
+    LoggedIn(AsyncRemote),
 }
 
 impl RemoteStatus {
+    #[inline]
+    pub fn logged_in<R>(remote: R) -> Self
+    where
+        R: Into<Remote>,
+    {
+        Self::LoggedIn(AsyncRemote::new(remote.into()))
+    }
+
     pub fn get_logged_in(&self) -> Result<&Remote> {
+        self.get_logged_in_async().map(AsyncRemote::get)
+    }
+
+    /// Returns the logged in remote in a form that can be moved into async tasks.
+    pub fn get_logged_in_async(&self) -> Result<&AsyncRemote> {
         match self {
             Self::LoggingIn(name) => Err(anyhow!("currently logging in as {}", name)),
             Self::LoggedIn(remote) => Ok(remote),
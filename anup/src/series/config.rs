@@ -2,7 +2,6 @@ use super::{SeriesParams, SeriesPath, UpdateParams};
 use crate::database::schema::series_configs;
 use crate::database::{self, Database};
 use anime::local::EpisodeParser;
-use anime::remote::{Remote, RemoteService};
 use anyhow::{anyhow, Result};
 use diesel::prelude::*;
 use std::borrow::Cow;
@@ -35,13 +34,9 @@ impl SeriesConfig {
     ///
     /// Returns true if the series ID has changed.
     #[allow(clippy::cast_possible_wrap)]
-    pub fn update(&mut self, params: UpdateParams, db: &Database, remote: &Remote) -> Result<bool> {
+    pub fn update(&mut self, params: UpdateParams, db: &Database) -> Result<bool> {
         let id_changed = match params.id {
             Some(id) if id as i32 != self.id => {
-                if remote.is_offline() {
-                    return Err(anyhow!("must be online to set a new series id"));
-                }
-
                 if let Some(existing) = Self::id_exists(db, id as i32) {
                     return Err(anyhow!("series already exists as {}", existing));
                 }
@@ -1,13 +1,17 @@
 use super::info::SeriesInfo;
 use crate::config::Config;
-use crate::database::schema::series_entries;
-use crate::database::Database;
-use anime::remote::{Remote, RemoteService, SeriesDate, Status};
+use crate::database::schema::{pending_deletions, series_entries};
+use crate::database::{Database, TextList};
+use crate::user::UserInfo;
+use anime::remote::{AsyncRemote, Remote, RemoteService, SeriesDate, Status};
 use anyhow::Result;
-use chrono::Local;
+use chrono::{Local, Utc};
 use diesel::prelude::*;
+use std::cmp::Ordering;
+use std::result;
+use thiserror::Error;
 
-#[derive(Queryable, Insertable)]
+#[derive(Clone, Debug, Queryable, Insertable)]
 #[table_name = "series_entries"]
 pub struct SeriesEntry {
     id: i32,
@@ -18,6 +22,29 @@ pub struct SeriesEntry {
     start_date: Option<SeriesDate>,
     end_date: Option<SeriesDate>,
     needs_sync: bool,
+    notes: Option<String>,
+    private: bool,
+    hidden: bool,
+    custom_lists: TextList,
+    remote_updated_at: Option<i64>,
+    modified_at: Option<i64>,
+    favourite: bool,
+    /// Indicates whether or not the notes, privacy, status list visibility, and custom lists have been retrieved
+    /// from the remote service.
+    ///
+    /// Entries saved before those fields were tracked only have their default values, which shouldn't replace the
+    /// ones on the remote service when they're uploaded.
+    extended_synced: bool,
+    /// The values of the fields that can conflict as of the last time the entry was synced with the remote service.
+    ///
+    /// They're used to tell which side changed a field when merging. Entries that were last synced before they were
+    /// tracked have no `base_status`, in which case any difference is treated as a conflict.
+    base_status: Option<Status>,
+    base_score: Option<i16>,
+    base_notes: Option<String>,
+    base_private: Option<bool>,
+    base_hidden: Option<bool>,
+    base_custom_lists: TextList,
 }
 
 impl SeriesEntry {
@@ -41,6 +68,12 @@ impl SeriesEntry {
         series_entries.filter(needs_sync.eq(true)).load(db.conn())
     }
 
+    pub fn load_all(db: &Database) -> diesel::QueryResult<Vec<Self>> {
+        use crate::database::schema::series_entries::dsl::series_entries;
+
+        series_entries.load(db.conn())
+    }
+
     pub fn from_remote(remote: &Remote, info: &SeriesInfo) -> Result<Self> {
         match remote.get_list_entry(info.id as u32)? {
             Some(entry) => Ok(Self::from(entry)),
@@ -48,21 +81,57 @@ impl SeriesEntry {
         }
     }
 
+    pub async fn from_remote_async(remote: &AsyncRemote, info: &SeriesInfo) -> Result<Self> {
+        match remote.get_list_entry(info.id as u32).await? {
+            Some(entry) => Ok(Self::from(entry)),
+            None => Ok(Self::from(info.id)),
+        }
+    }
+
     pub fn force_sync_to_remote(&mut self, remote: &Remote) -> Result<()> {
         if remote.is_offline() {
             return Ok(());
         }
 
-        remote.update_list_entry(&self.into())?;
-        self.needs_sync = false;
+        if !self.extended_synced {
+            let remote_entry = remote.get_list_entry(self.id() as u32)?;
+            self.fill_extended_fields(remote_entry.as_ref());
+        }
+
+        let updated_at = remote.update_list_entry(&self.into())?;
+        self.synced(updated_at);
         Ok(())
     }
 
+    /// Upload the entry to `remote` if it has unsynced changes.
+    ///
+    /// Changes made to the entry on `remote` since it was last synced are merged in first. If they can't be
+    /// merged automatically, an `EntryConflict` error is returned and nothing is uploaded.
     pub fn sync_to_remote(&mut self, remote: &Remote) -> Result<()> {
-        if !self.needs_sync {
+        if !self.needs_sync || remote.is_offline() {
+            return Ok(());
+        }
+
+        let remote_entry = remote.get_list_entry(self.id() as u32)?;
+        self.sync_to_remote_with(remote, remote_entry)
+    }
+
+    /// Upload the entry to `remote` if it has unsynced changes, where `remote_entry` is the entry as it currently is
+    /// on `remote`.
+    ///
+    /// This allows the entries of many series to be retrieved at once before syncing each of them. See `sync_to_remote`
+    /// for how changes made on `remote` are handled.
+    pub fn sync_to_remote_with(
+        &mut self,
+        remote: &Remote,
+        remote_entry: Option<anime::remote::SeriesEntry>,
+    ) -> Result<()> {
+        if !self.needs_sync || remote.is_offline() {
             return Ok(());
         }
 
+        self.fill_extended_fields(remote_entry.as_ref());
+        self.merge_remote_changes(remote_entry)?;
         self.force_sync_to_remote(remote)
     }
 
@@ -79,12 +148,325 @@ impl SeriesEntry {
         Ok(())
     }
 
+    pub async fn force_sync_to_remote_async(&mut self, remote: &AsyncRemote) -> Result<()> {
+        if remote.get().is_offline() {
+            return Ok(());
+        }
+
+        if !self.extended_synced {
+            let remote_entry = remote.get_list_entry(self.id() as u32).await?;
+            self.fill_extended_fields(remote_entry.as_ref());
+        }
+
+        let updated_at = remote.update_list_entry((&mut *self).into()).await?;
+        self.synced(updated_at);
+        Ok(())
+    }
+
+    /// Upload the entry to `remote` if it has unsynced changes, without blocking the async runtime.
+    ///
+    /// See `sync_to_remote` for how changes made on `remote` are handled.
+    pub async fn sync_to_remote_async(&mut self, remote: &AsyncRemote) -> Result<()> {
+        if !self.needs_sync || remote.get().is_offline() {
+            return Ok(());
+        }
+
+        let remote_entry = remote.get_list_entry(self.id() as u32).await?;
+        self.fill_extended_fields(remote_entry.as_ref());
+        self.merge_remote_changes(remote_entry)?;
+        self.force_sync_to_remote_async(remote).await
+    }
+
+    pub async fn force_sync_from_remote_async(&mut self, remote: &AsyncRemote) -> Result<()> {
+        if remote.get().is_offline() {
+            return Ok(());
+        }
+
+        *self = match remote.get_list_entry(self.id() as u32).await? {
+            Some(entry) => Self::from(entry),
+            None => Self::from(self.id()),
+        };
+
+        Ok(())
+    }
+
+    /// Retrieve the entry from `remote`.
+    ///
+    /// If the entry has unsynced changes, only the changes made on `remote` since it was last synced are merged in.
+    /// An `EntryConflict` error is returned if they can't be merged automatically.
     pub fn sync_from_remote(&mut self, remote: &Remote) -> Result<()> {
-        if self.needs_sync {
+        if !self.needs_sync {
+            return self.force_sync_from_remote(remote);
+        }
+
+        if remote.is_offline() {
+            return Ok(());
+        }
+
+        let remote_entry = remote.get_list_entry(self.id() as u32)?;
+        self.merge_remote_changes(remote_entry)
+    }
+
+    /// Take the notes, privacy, status list visibility, and custom lists from `remote_entry` if they've never been
+    /// retrieved before.
+    ///
+    /// Only fields that still have their default value are replaced, so changes made locally since they started
+    /// being tracked are kept.
+    fn fill_extended_fields(&mut self, remote_entry: Option<&anime::remote::SeriesEntry>) {
+        if self.extended_synced {
+            return;
+        }
+
+        self.extended_synced = true;
+
+        let remote_entry = match remote_entry {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        if self.notes.is_none() {
+            self.notes = remote_entry.notes.clone();
+        }
+
+        self.private |= remote_entry.private;
+        self.hidden |= remote_entry.hidden;
+
+        if self.custom_lists.is_empty() {
+            self.custom_lists = remote_entry.custom_lists.clone().into();
+        }
+    }
+
+    fn merge_remote_changes(
+        &mut self,
+        remote_entry: Option<anime::remote::SeriesEntry>,
+    ) -> Result<()> {
+        let remote_entry = match remote_entry {
+            Some(entry) => Self::from(entry),
+            None => return Ok(()),
+        };
+
+        if !self.remote_changed(&remote_entry) {
             return Ok(());
         }
 
-        self.force_sync_from_remote(remote)
+        *self = self.merge(remote_entry)?;
+        Ok(())
+    }
+
+    /// Returns true if `remote_entry` has been updated since the entry was last synced.
+    ///
+    /// Entries that were last synced before update times were tracked, or that come from a remote service
+    /// that doesn't report them, are never considered to have changed.
+    fn remote_changed(&self, remote_entry: &Self) -> bool {
+        match (self.remote_updated_at, remote_entry.remote_updated_at) {
+            (Some(last_synced), Some(updated)) => updated > last_synced,
+            _ => false,
+        }
+    }
+
+    /// Merge the changes made to `remote_entry` with the ones made to the entry.
+    ///
+    /// The higher progress (along with the status that goes with it) and rewatch count are kept, as well as the later
+    /// start and finish dates. Any other field is compared against its value as of the last sync, and is taken from
+    /// whichever side changed it. Fields that were changed on both sides in different ways are conflicts, and are
+    /// returned as an `EntryConflict` for the user to resolve.
+    pub fn merge(&self, remote_entry: Self) -> result::Result<Self, EntryConflict> {
+        let mut merged = self.clone();
+        let mut fields = Vec::new();
+
+        merged.remote_updated_at = remote_entry.remote_updated_at;
+        // Favourites are set on the remote service directly, so it always has the latest state
+        merged.favourite = remote_entry.favourite;
+
+        let progress = remote_entry.watched_episodes.cmp(&self.watched_episodes);
+
+        if progress == Ordering::Greater {
+            merged.watched_episodes = remote_entry.watched_episodes;
+            merged.status = remote_entry.status;
+        }
+
+        merged.times_rewatched = self.times_rewatched.max(remote_entry.times_rewatched);
+        merged.start_date = later_date(self.start_date, remote_entry.start_date);
+        merged.end_date = later_date(self.end_date, remote_entry.end_date);
+
+        let has_base = self.base_status.is_some();
+
+        let mut resolve = |field: ConflictField, merge: FieldMerge| match merge {
+            FieldMerge::Local => (),
+            FieldMerge::Remote => field.copy(&remote_entry, &mut merged),
+            FieldMerge::Conflict => fields.push(field),
+        };
+
+        if progress == Ordering::Equal {
+            resolve(
+                ConflictField::Status,
+                merge_field(
+                    &self.status,
+                    &remote_entry.status,
+                    self.base_status.as_ref(),
+                ),
+            );
+        }
+
+        resolve(
+            ConflictField::Score,
+            merge_field(
+                &self.score,
+                &remote_entry.score,
+                has_base.then(|| &self.base_score),
+            ),
+        );
+
+        resolve(
+            ConflictField::Notes,
+            merge_field(
+                &self.notes,
+                &remote_entry.notes,
+                has_base.then(|| &self.base_notes),
+            ),
+        );
+
+        resolve(
+            ConflictField::Private,
+            merge_field(
+                &self.private,
+                &remote_entry.private,
+                self.base_private.as_ref(),
+            ),
+        );
+
+        resolve(
+            ConflictField::Hidden,
+            merge_field(
+                &self.hidden,
+                &remote_entry.hidden,
+                self.base_hidden.as_ref(),
+            ),
+        );
+
+        resolve(
+            ConflictField::CustomLists,
+            merge_field(
+                &*self.custom_lists,
+                &*remote_entry.custom_lists,
+                has_base.then(|| &*self.base_custom_lists),
+            ),
+        );
+
+        // Everything on the remote service has now been seen, so any further changes are measured against it
+        merged.set_base(&remote_entry);
+
+        if fields.is_empty() {
+            return Ok(merged);
+        }
+
+        let mut theirs = merged.clone();
+
+        for field in &fields {
+            field.copy(&remote_entry, &mut theirs);
+        }
+
+        Err(EntryConflict {
+            local: merged,
+            remote: theirs,
+            fields,
+        })
+    }
+
+    /// Mark the entry as being in sync with the remote service, which reported the update at `updated_at`.
+    fn synced(&mut self, updated_at: Option<i64>) {
+        self.needs_sync = false;
+        self.remote_updated_at = updated_at.or(self.remote_updated_at);
+
+        let current = self.clone();
+        self.set_base(&current);
+    }
+
+    /// Apply the result of syncing `original`, a copy of the entry taken before the sync started, to the entry.
+    ///
+    /// The entry may have been changed while the sync was in progress, so only the fields that still have their
+    /// original value are taken from `synced`. Any changes made in the meantime are kept and still need to be synced,
+    /// but will be merged against what `synced` left on the remote service.
+    pub fn apply_sync(&mut self, original: &Self, synced: Self) {
+        let mut changed = false;
+
+        macro_rules! take_unchanged {
+            ($($field:ident),+) => {
+                $(
+                if self.$field == original.$field {
+                    self.$field = synced.$field;
+                } else {
+                    changed = true;
+                }
+                )+
+            };
+        }
+
+        take_unchanged!(
+            watched_episodes,
+            score,
+            status,
+            times_rewatched,
+            notes,
+            private,
+            hidden,
+            favourite
+        );
+
+        if same_date(self.start_date, original.start_date) {
+            self.start_date = synced.start_date;
+        } else {
+            changed = true;
+        }
+
+        if same_date(self.end_date, original.end_date) {
+            self.end_date = synced.end_date;
+        } else {
+            changed = true;
+        }
+
+        if *self.custom_lists == *original.custom_lists {
+            self.custom_lists = synced.custom_lists;
+        } else {
+            changed = true;
+        }
+
+        self.needs_sync = changed || synced.needs_sync;
+        self.remote_updated_at = synced.remote_updated_at;
+        self.extended_synced = synced.extended_synced;
+        self.base_status = synced.base_status;
+        self.base_score = synced.base_score;
+        self.base_notes = synced.base_notes;
+        self.base_private = synced.base_private;
+        self.base_hidden = synced.base_hidden;
+        self.base_custom_lists = synced.base_custom_lists;
+    }
+
+    /// Record the values of the fields in `remote_entry` that can conflict as the last ones synced.
+    fn set_base(&mut self, remote_entry: &Self) {
+        self.base_status = Some(remote_entry.status);
+        self.base_score = remote_entry.score;
+        self.base_notes = remote_entry.notes.clone();
+        self.base_private = Some(remote_entry.private);
+        self.base_hidden = Some(remote_entry.hidden);
+        self.base_custom_lists = remote_entry.custom_lists.clone();
+    }
+
+    fn modified(&mut self) {
+        self.needs_sync = true;
+        self.modified_at = Some(Utc::now().timestamp());
+    }
+
+    /// Returns the time the entry was last modified locally, in seconds since the Unix epoch.
+    #[inline(always)]
+    pub fn modified_at(&self) -> Option<i64> {
+        self.modified_at
+    }
+
+    /// Returns the time the entry was last updated on the remote service as of the last sync, in seconds since the Unix epoch.
+    #[inline(always)]
+    pub fn remote_updated_at(&self) -> Option<i64> {
+        self.remote_updated_at
     }
 
     #[inline(always)]
@@ -92,6 +474,39 @@ impl SeriesEntry {
         self.needs_sync
     }
 
+    #[inline(always)]
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    /// Set the user's notes on the series, or clear them if `notes` is empty.
+    pub fn set_notes<S>(&mut self, notes: S)
+    where
+        S: Into<String>,
+    {
+        let notes = notes.into();
+
+        self.notes = if notes.is_empty() { None } else { Some(notes) };
+        self.modified();
+    }
+
+    #[inline(always)]
+    pub fn custom_lists(&self) -> &[String] {
+        &self.custom_lists
+    }
+
+    pub fn set_custom_lists(&mut self, lists: Vec<String>) {
+        self.custom_lists = lists.into();
+        self.modified();
+    }
+
+    /// Set whether or not the series is one of the user's favourites.
+    ///
+    /// This doesn't mark the entry as needing to be synced, as favourites are changed on the remote service directly.
+    pub fn set_favourite(&mut self, favourite: bool) {
+        self.favourite = favourite;
+    }
+
     pub fn set_status(&mut self, status: Status, config: &Config) {
         match status {
             Status::Watching if self.start_date().is_none() => {
@@ -116,7 +531,31 @@ impl SeriesEntry {
         }
 
         self.status = status;
-        self.needs_sync = true;
+        self.modified();
+    }
+
+    /// Replace the progress, score, status, rewatch count, and dates with the ones from an `imported` entry.
+    ///
+    /// The notes are only replaced if the imported entry has some, and fields it can't carry (such as custom lists)
+    /// are kept. The entry is marked as needing to be synced so the imported changes reach the remote service, where
+    /// they replace the entry like a forced sync instead of being merged with it.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn import(&mut self, imported: &anime::remote::SeriesEntry) {
+        self.watched_episodes = imported.watched_eps as i16;
+        self.score = imported.score.map(Into::into);
+        self.status = imported.status;
+        self.times_rewatched = imported.times_rewatched as i16;
+        self.start_date = imported.start_date;
+        self.end_date = imported.end_date;
+
+        if imported.notes.is_some() {
+            self.notes = imported.notes.clone();
+        }
+
+        // Changes made on the remote service are only merged in when it has been updated since the entry was last
+        // synced, which can't be known without a sync time
+        self.remote_updated_at = None;
+        self.modified();
     }
 }
 
@@ -140,7 +579,7 @@ macro_rules! impl_series_entry_getters_setters {
         #[inline(always)]
         pub fn $setter(&mut self, value: $field_ty) {
             self.$field = value;
-            self.needs_sync = true;
+            self.modified();
         }
     }
 }
@@ -153,6 +592,9 @@ impl_series_entry_getters_setters!(
     times_rewatched: i16 => set_times_rewatched,
     start_date: Option<SeriesDate> => !,
     end_date: Option<SeriesDate> => !,
+    private: bool => set_private,
+    hidden: bool => set_hidden,
+    favourite: bool => !,
 );
 
 impl Into<anime::remote::SeriesEntry> for &mut SeriesEntry {
@@ -165,6 +607,12 @@ impl Into<anime::remote::SeriesEntry> for &mut SeriesEntry {
             times_rewatched: self.times_rewatched as u32,
             start_date: self.start_date,
             end_date: self.end_date,
+            notes: self.notes.clone(),
+            private: self.private,
+            hidden: self.hidden,
+            custom_lists: self.custom_lists.to_vec(),
+            updated_at: self.remote_updated_at,
+            favourite: self.favourite,
         }
     }
 }
@@ -173,6 +621,12 @@ impl From<anime::remote::SeriesEntry> for SeriesEntry {
     #[allow(clippy::cast_possible_wrap)]
     fn from(entry: anime::remote::SeriesEntry) -> Self {
         Self {
+            base_status: Some(entry.status),
+            base_score: entry.score.map(Into::into),
+            base_notes: entry.notes.clone(),
+            base_private: Some(entry.private),
+            base_hidden: Some(entry.hidden),
+            base_custom_lists: entry.custom_lists.clone().into(),
             id: entry.id as i32,
             watched_episodes: entry.watched_eps as i16,
             score: entry.score.map(Into::into),
@@ -181,6 +635,14 @@ impl From<anime::remote::SeriesEntry> for SeriesEntry {
             start_date: entry.start_date,
             end_date: entry.end_date,
             needs_sync: false,
+            notes: entry.notes,
+            private: entry.private,
+            hidden: entry.hidden,
+            custom_lists: entry.custom_lists.into(),
+            remote_updated_at: entry.updated_at,
+            modified_at: None,
+            favourite: entry.favourite,
+            extended_synced: true,
         }
     }
 }
@@ -191,3 +653,409 @@ impl From<i32> for SeriesEntry {
         Self::from(remote_entry)
     }
 }
+
+/// Returns true if both dates are the same day, or are both unknown.
+fn same_date(first: Option<SeriesDate>, second: Option<SeriesDate>) -> bool {
+    let ymd = |date: Option<SeriesDate>| date.map(|date| (date.year, date.month, date.day));
+    ymd(first) == ymd(second)
+}
+
+/// Returns the later of two dates, or whichever one is known.
+fn later_date(first: Option<SeriesDate>, second: Option<SeriesDate>) -> Option<SeriesDate> {
+    match (first, second) {
+        (Some(first), Some(second)) => {
+            let ymd = |date: SeriesDate| (date.year, date.month, date.day);
+
+            if ymd(second) > ymd(first) {
+                Some(second)
+            } else {
+                Some(first)
+            }
+        }
+        (first, second) => first.or(second),
+    }
+}
+
+/// How a field of a list entry should be merged.
+enum FieldMerge {
+    /// The field was only changed locally, or is the same on both sides.
+    Local,
+    /// The field was only changed on the remote service.
+    Remote,
+    /// The field was changed on both sides in different ways.
+    Conflict,
+}
+
+/// Decide how to merge a field based on its `local` and `remote` values and its value as of the last sync.
+///
+/// Without a `base` value it isn't possible to tell which side changed the field, so any difference is a conflict.
+fn merge_field<T>(local: &T, remote: &T, base: Option<&T>) -> FieldMerge
+where
+    T: PartialEq,
+{
+    if local == remote {
+        return FieldMerge::Local;
+    }
+
+    match base {
+        Some(base) if base == local => FieldMerge::Remote,
+        Some(base) if base == remote => FieldMerge::Local,
+        _ => FieldMerge::Conflict,
+    }
+}
+
+/// Changes made to a list entry both locally and on the remote service since it was last synced that can't be
+/// merged automatically.
+#[derive(Debug, Error)]
+#[error("list entry has conflicting changes on the remote service")]
+pub struct EntryConflict {
+    /// The merged entry with every conflicting field kept from the local entry.
+    pub local: SeriesEntry,
+    /// The merged entry with every conflicting field taken from the remote entry.
+    pub remote: SeriesEntry,
+    /// The fields that were changed in different ways.
+    pub fields: Vec<ConflictField>,
+}
+
+impl EntryConflict {
+    #[inline(always)]
+    pub fn id(&self) -> i32 {
+        self.local.id
+    }
+}
+
+/// A field of a list entry that can conflict when merging.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConflictField {
+    Status,
+    Score,
+    Notes,
+    Private,
+    Hidden,
+    CustomLists,
+}
+
+impl ConflictField {
+    /// Copy the value of the field in `from` to `to`.
+    fn copy(self, from: &SeriesEntry, to: &mut SeriesEntry) {
+        match self {
+            Self::Status => to.status = from.status,
+            Self::Score => to.score = from.score,
+            Self::Notes => to.notes = from.notes.clone(),
+            Self::Private => to.private = from.private,
+            Self::Hidden => to.hidden = from.hidden,
+            Self::CustomLists => to.custom_lists = from.custom_lists.clone(),
+        }
+    }
+}
+
+impl Into<&'static str> for ConflictField {
+    fn into(self) -> &'static str {
+        match self {
+            Self::Status => "Status",
+            Self::Score => "Score",
+            Self::Notes => "Notes",
+            Self::Private => "Private",
+            Self::Hidden => "Hidden",
+            Self::CustomLists => "Custom Lists",
+        }
+    }
+}
+
+/// A list entry that was removed locally and still needs to be deleted from the remote service.
+///
+/// Deletions are queued so they can be performed the next time the remote service is synced with if they
+/// can't be done immediately. Each user has their own queue, so the same series can be queued for several users.
+#[derive(Queryable, Insertable)]
+#[table_name = "pending_deletions"]
+pub struct PendingDeletion {
+    id: i32,
+    /// The name of the remote service the list entry should be deleted from.
+    ///
+    /// Deletions queued before the user was tracked have an empty service and username, and are performed with
+    /// whichever user is logged in.
+    service: String,
+    username: String,
+}
+
+impl PendingDeletion {
+    /// Create a deletion of the list entry with the specified `id` from `user`'s list.
+    pub fn new(id: i32, user: Option<&UserInfo>) -> Self {
+        Self {
+            id,
+            service: user.map_or_else(String::new, |user| user.service.as_str().into()),
+            username: user.map_or_else(String::new, |user| user.username.clone()),
+        }
+    }
+
+    pub fn load_all(db: &Database) -> diesel::QueryResult<Vec<Self>> {
+        use crate::database::schema::pending_deletions::dsl::pending_deletions;
+
+        pending_deletions.load(db.conn())
+    }
+
+    pub fn save(&self, db: &Database) -> diesel::QueryResult<usize> {
+        use crate::database::schema::pending_deletions::dsl::pending_deletions;
+
+        diesel::replace_into(pending_deletions)
+            .values(self)
+            .execute(db.conn())
+    }
+
+    /// Remove the deletion from the queue, either because it has been performed or is no longer wanted.
+    pub fn remove(&self, db: &Database) -> diesel::QueryResult<usize> {
+        use crate::database::schema::pending_deletions::dsl::{
+            id, pending_deletions, service, username,
+        };
+
+        let deletion = pending_deletions
+            .filter(id.eq(self.id))
+            .filter(service.eq(&self.service))
+            .filter(username.eq(&self.username));
+
+        diesel::delete(deletion).execute(db.conn())
+    }
+
+    /// Returns the user whose list the entry should be deleted from, if it's known.
+    pub fn user(&self) -> Option<UserInfo> {
+        if self.username.is_empty() {
+            return None;
+        }
+
+        let service = self.service.parse().ok()?;
+        Some(UserInfo::new(service, self.username.clone()))
+    }
+
+    /// Returns true if the deletion should be performed with the user logged in to `remote`.
+    pub fn belongs_to(&self, remote: &Remote) -> bool {
+        match self.user() {
+            Some(user) => user.is_logged_in(remote),
+            None => !remote.is_offline(),
+        }
+    }
+
+    /// Delete the list entry from `remote` and remove it from the queue.
+    ///
+    /// The deletion will stay queued if `remote` is offline or is logged in to a different user than the one it
+    /// was queued for.
+    pub fn sync_to_remote(&self, remote: &Remote, db: &Database) -> Result<()> {
+        if !self.belongs_to(remote) {
+            return Ok(());
+        }
+
+        remote.delete_list_entry(self.id as u32)?;
+        self.remove(db)?;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(watched_episodes: i16, status: Status, updated_at: i64) -> SeriesEntry {
+        let mut entry = SeriesEntry::from(1);
+        entry.watched_episodes = watched_episodes;
+        entry.status = status;
+        entry.remote_updated_at = Some(updated_at);
+        entry
+    }
+
+    #[test]
+    fn merge_keeps_higher_progress() {
+        let mut local = entry(5, Status::Watching, 100);
+        local.times_rewatched = 1;
+        local.start_date = Some(SeriesDate::from_ymd(2021, 1, 5));
+
+        let mut remote = entry(12, Status::Completed, 200);
+        remote.start_date = Some(SeriesDate::from_ymd(2021, 1, 2));
+        remote.end_date = Some(SeriesDate::from_ymd(2021, 3, 1));
+
+        let merged = local.merge(remote).expect("entries should merge");
+
+        assert_eq!(merged.watched_episodes, 12);
+        assert_eq!(merged.status, Status::Completed);
+        assert_eq!(merged.times_rewatched, 1);
+        assert_eq!(merged.start_date.map(|date| date.day), Some(5));
+        assert_eq!(merged.end_date.map(|date| date.month), Some(3));
+        assert_eq!(merged.remote_updated_at, Some(200));
+    }
+
+    #[test]
+    fn merge_reports_conflicting_fields() {
+        let mut local = entry(5, Status::OnHold, 100);
+        local.score = Some(80);
+        local.notes = Some("local".into());
+
+        let mut remote = entry(5, Status::Dropped, 200);
+        remote.score = Some(60);
+
+        let conflict = local.merge(remote).expect_err("entries should conflict");
+
+        // The notes were only changed locally, so they aren't a conflict
+        assert_eq!(
+            conflict.fields,
+            [ConflictField::Status, ConflictField::Score]
+        );
+
+        assert_eq!(conflict.local.status, Status::OnHold);
+        assert_eq!(conflict.local.score, Some(80));
+        assert_eq!(conflict.remote.status, Status::Dropped);
+        assert_eq!(conflict.remote.score, Some(60));
+        assert_eq!(conflict.remote.notes.as_deref(), Some("local"));
+        assert_eq!(conflict.remote.remote_updated_at, Some(200));
+    }
+
+    #[test]
+    fn merge_takes_fields_changed_on_one_side() {
+        let mut local = entry(5, Status::PlanToWatch, 100);
+        local.notes = Some("local".into());
+
+        let mut remote = entry(5, Status::OnHold, 200);
+        remote.score = Some(70);
+        remote.private = true;
+
+        let merged = local.merge(remote).expect("entries should merge");
+
+        assert_eq!(merged.status, Status::OnHold);
+        assert_eq!(merged.score, Some(70));
+        assert!(merged.private);
+        assert_eq!(merged.notes(), Some("local"));
+        assert_eq!(merged.base_score, Some(70));
+        assert_eq!(merged.base_notes, None);
+    }
+
+    #[test]
+    fn merge_without_base_reports_any_difference() {
+        let mut local = entry(5, Status::Watching, 100);
+        local.base_status = None;
+
+        let mut remote = entry(5, Status::Watching, 200);
+        remote.score = Some(70);
+
+        let conflict = local.merge(remote).expect_err("entries should conflict");
+
+        assert_eq!(conflict.fields, [ConflictField::Score]);
+    }
+
+    #[test]
+    fn synced_entries_use_the_remote_update_time() {
+        let mut local = entry(5, Status::Watching, 100);
+        local.set_score(Some(90));
+
+        local.synced(Some(300));
+
+        assert!(!local.needs_sync());
+        assert_eq!(local.remote_updated_at(), Some(300));
+        assert_eq!(local.base_score, Some(90));
+
+        local.synced(None);
+
+        assert_eq!(local.remote_updated_at(), Some(300));
+    }
+
+    #[test]
+    fn unchanged_remote_entries_are_not_merged() {
+        let local = entry(5, Status::Watching, 200);
+
+        assert!(!local.remote_changed(&entry(3, Status::Watching, 200)));
+        assert!(local.remote_changed(&entry(3, Status::Watching, 300)));
+
+        let mut legacy = entry(5, Status::Watching, 0);
+        legacy.remote_updated_at = None;
+
+        assert!(!legacy.remote_changed(&entry(3, Status::Watching, 300)));
+    }
+
+    #[test]
+    fn sync_result_keeps_changes_made_while_syncing() {
+        let mut original = entry(5, Status::Watching, 100);
+        original.set_score(Some(60));
+
+        let mut synced = original.clone();
+        synced.notes = Some("remote".into());
+        synced.synced(Some(200));
+
+        let mut current = original.clone();
+        current.set_watched_episodes(6);
+        current.apply_sync(&original, synced.clone());
+
+        assert_eq!(current.watched_episodes, 6);
+        assert_eq!(current.notes(), Some("remote"));
+        assert_eq!(current.remote_updated_at(), Some(200));
+        assert_eq!(current.base_score, Some(60));
+        assert!(current.needs_sync());
+
+        let mut unchanged = original.clone();
+        unchanged.apply_sync(&original, synced);
+
+        assert!(!unchanged.needs_sync());
+    }
+
+    #[test]
+    fn extended_fields_are_only_filled_in_once() {
+        let mut local = entry(5, Status::Watching, 100);
+        local.extended_synced = false;
+        local.notes = Some("local".into());
+
+        let remote = anime::remote::SeriesEntry {
+            notes: Some("remote".into()),
+            private: true,
+            custom_lists: vec!["favourites".into()],
+            ..anime::remote::SeriesEntry::new(1)
+        };
+
+        local.fill_extended_fields(Some(&remote));
+
+        assert_eq!(local.notes(), Some("local"));
+        assert!(local.private());
+        assert_eq!(local.custom_lists(), ["favourites"]);
+
+        local.set_custom_lists(Vec::new());
+        local.fill_extended_fields(Some(&remote));
+
+        assert!(local.custom_lists().is_empty());
+    }
+
+    #[test]
+    fn import_keeps_unimported_fields() {
+        let mut local = entry(2, Status::Watching, 100);
+        local.notes = Some("local".into());
+        local.custom_lists = vec!["favourites".to_string()].into();
+
+        let imported = anime::remote::SeriesEntry {
+            watched_eps: 12,
+            score: Some(90),
+            status: Status::Completed,
+            ..anime::remote::SeriesEntry::new(1)
+        };
+
+        local.import(&imported);
+
+        assert_eq!(local.watched_episodes, 12);
+        assert_eq!(local.score, Some(90));
+        assert_eq!(local.status, Status::Completed);
+        assert_eq!(local.notes(), Some("local"));
+        assert_eq!(local.custom_lists(), ["favourites"]);
+        assert!(local.needs_sync());
+
+        // The imported entry should replace the remote one, even if it changed after the last sync
+        let remote = entry(5, Status::Dropped, 200);
+        assert!(!local.remote_changed(&remote));
+    }
+
+    #[test]
+    fn deletions_keep_their_user() {
+        use crate::user::RemoteType;
+
+        let user = UserInfo::new(RemoteType::MyAnimeList, "test");
+
+        assert!(PendingDeletion::new(1, Some(&user)).user() == Some(user));
+        assert!(PendingDeletion::new(1, None).user().is_none());
+    }
+}
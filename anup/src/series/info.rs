@@ -1,8 +1,13 @@
 use super::SeriesPath;
 use crate::database::schema::series_info;
-use crate::database::Database;
-use anime::remote::{Remote, RemoteService, SeriesID, SeriesInfo as RemoteInfo};
+use crate::database::{Database, TextList};
+use anime::local::SortedEpisodes;
+use anime::remote::matcher::{SeriesMatcher, CONFIDENT_MATCH};
+use anime::remote::{
+    AiringStatus, AsyncRemote, Remote, RemoteService, SearchOptions, SearchPage, Season, SeriesID,
+};
 use anyhow::Result;
+use chrono::Utc;
 use diesel::prelude::*;
 use std::borrow::Cow;
 
@@ -14,6 +19,18 @@ pub struct SeriesInfo {
     pub title_romaji: String,
     pub episodes: i16,
     pub episode_length_mins: i16,
+    pub title_english: Option<String>,
+    pub title_native: Option<String>,
+    pub synonyms: TextList,
+    pub season: Option<Season>,
+    pub season_year: Option<i16>,
+    pub airing_status: Option<AiringStatus>,
+    pub genres: TextList,
+    pub studios: TextList,
+    pub average_score: Option<i16>,
+    pub is_adult: bool,
+    pub next_episode: Option<i16>,
+    pub next_episode_airs_at: Option<i64>,
 }
 
 impl SeriesInfo {
@@ -31,13 +48,87 @@ impl SeriesInfo {
             .execute(db.conn())
     }
 
-    pub fn from_remote(sel: InfoSelector, remote: &Remote) -> Result<InfoResult> {
-        match sel {
-            InfoSelector::ID(id) => Self::from_remote_by_id(id, remote).map(InfoResult::Confident),
-            InfoSelector::Name(name) => Self::from_remote_by_name(name, remote),
+    /// Returns the number of episodes of the series, or None if it isn't known yet.
+    pub fn episode_count(&self) -> Option<i16> {
+        Some(self.episodes).filter(|&episodes| episodes > 0)
+    }
+
+    /// Returns true if the series is still airing or hasn't started airing yet.
+    pub fn is_airing(&self) -> bool {
+        self.next_episode.is_some()
+            || matches!(
+                self.airing_status,
+                Some(AiringStatus::Airing) | Some(AiringStatus::NotYetAired)
+            )
+    }
+
+    /// Returns the number of episodes that have aired so far, if it's known.
+    pub fn aired_episodes(&self) -> Option<i16> {
+        match (self.next_episode, self.airing_status) {
+            (Some(next), _) => {
+                // The info may have been saved before the next episode aired
+                let next_has_aired = self
+                    .next_episode_airs_at
+                    .map_or(false, |airs_at| airs_at <= Utc::now().timestamp());
+
+                if next_has_aired {
+                    Some(next)
+                } else {
+                    Some(next - 1)
+                }
+            }
+            (None, Some(AiringStatus::Finished)) => self.episode_count(),
+            (None, Some(AiringStatus::NotYetAired)) => Some(0),
+            (None, _) => None,
         }
     }
 
+    /// Returns true if having `watched` episodes means the series has been completed.
+    ///
+    /// This is never the case for series that are still airing, or that have an unknown number of episodes.
+    pub fn is_completed_by(&self, watched: i16) -> bool {
+        match self.episode_count() {
+            Some(episodes) if !self.is_airing() => watched >= episodes,
+            Some(_) | None => false,
+        }
+    }
+
+    /// Returns the season and year the series started airing in, such as "Spring 2013".
+    pub fn airing_season(&self) -> Option<String> {
+        match (self.season, self.season_year) {
+            (Some(season), Some(year)) => {
+                let season: &'static str = season.into();
+                Some(format!("{} {}", season, year))
+            }
+            (None, Some(year)) => Some(year.to_string()),
+            (_, None) => None,
+        }
+    }
+
+    /// Returns a short summary of when the series aired, its airing status, studios, and average score.
+    pub fn details(&self) -> String {
+        let mut details = Vec::with_capacity(4);
+
+        if let Some(season) = self.airing_season() {
+            details.push(season);
+        }
+
+        if let Some(status) = self.airing_status {
+            let status: &'static str = status.into();
+            details.push(status.into());
+        }
+
+        if !self.studios.is_empty() {
+            details.push(self.studios.join(", "));
+        }
+
+        if let Some(score) = self.average_score {
+            details.push(format!("{}%", score));
+        }
+
+        details.join(" | ")
+    }
+
     pub fn from_remote_by_id(id: SeriesID, remote: &Remote) -> Result<Self> {
         remote
             .search_info_by_id(id)
@@ -45,25 +136,44 @@ impl SeriesInfo {
             .map_err(Into::into)
     }
 
-    pub fn from_remote_by_name<S>(name: S, remote: &Remote) -> Result<InfoResult>
-    where
-        S: Into<String>,
-    {
-        const MIN_CONFIDENCE: f32 = 0.85;
+    /// Find the series info specified by `sel`.
+    ///
+    /// When searching by name, `local_episodes` is used to prefer series with the same number of episodes.
+    pub async fn from_remote_async(
+        sel: InfoSelector,
+        local_episodes: Option<&SortedEpisodes>,
+        remote: &AsyncRemote,
+    ) -> Result<InfoResult> {
+        match sel {
+            InfoSelector::ID(id) => {
+                let info = remote.search_info_by_id(id).await?;
+                Ok(InfoResult::Confident(info.into()))
+            }
+            InfoSelector::Name(name) => {
+                let options = SearchOptions::new();
+                let page = remote.search_info(name.as_str(), options.clone()).await?;
 
-        let name = name.into();
-        let mut results = remote.search_info_by_name(&name)?;
-        let found =
-            RemoteInfo::closest_match(name, MIN_CONFIDENCE, results.iter().map(Cow::Borrowed));
+                let matcher = SeriesMatcher::new(&name)
+                    .episodes(local_episodes.map(|episodes| episodes.len() as u32));
+
+                Ok(Self::best_match(
+                    &matcher,
+                    InfoSearch::new(name, options),
+                    page,
+                ))
+            }
+        }
+    }
+
+    fn best_match(matcher: &SeriesMatcher, search: InfoSearch, mut page: SearchPage) -> InfoResult {
+        let found = matcher.best_match(page.results.iter().map(Cow::Borrowed), CONFIDENT_MATCH);
 
         match found {
             Some((best_match, _)) => {
-                let info = results.swap_remove(best_match).into();
-                Ok(InfoResult::Confident(info))
+                let info = page.results.swap_remove(best_match).into();
+                InfoResult::Confident(info)
             }
-            None => Ok(InfoResult::Unconfident(
-                results.into_iter().map(Into::into).collect(),
-            )),
+            None => InfoResult::Unconfident(SearchResults::new(search, page)),
         }
     }
 }
@@ -77,6 +187,18 @@ impl From<anime::remote::SeriesInfo> for SeriesInfo {
             title_romaji: value.title.romaji,
             episodes: value.episodes as i16,
             episode_length_mins: value.episode_length as i16,
+            title_english: value.title.english,
+            title_native: value.title.native,
+            synonyms: value.synonyms.into(),
+            season: value.season,
+            season_year: value.year.map(|year| year as i16),
+            airing_status: value.status,
+            genres: value.genres.into(),
+            studios: value.studios.into(),
+            average_score: value.average_score.map(i16::from),
+            is_adult: value.is_adult,
+            next_episode: value.next_episode.map(|next| next.number as i16),
+            next_episode_airs_at: value.next_episode.map(|next| next.airs_at),
         }
     }
 }
@@ -101,5 +223,45 @@ impl InfoSelector {
 
 pub enum InfoResult {
     Confident(SeriesInfo),
-    Unconfident(Vec<SeriesInfo>),
+    Unconfident(SearchResults),
+}
+
+/// A search for series info by name that can be continued to retrieve more results.
+#[derive(Clone)]
+pub struct InfoSearch {
+    pub name: String,
+    pub options: SearchOptions,
+}
+
+impl InfoSearch {
+    #[inline(always)]
+    pub fn new(name: String, options: SearchOptions) -> Self {
+        Self { name, options }
+    }
+
+    /// Retrieve the page of results specified by the search options.
+    pub async fn run(&self, remote: &AsyncRemote) -> Result<SearchResults> {
+        let page = remote
+            .search_info(self.name.as_str(), self.options.clone())
+            .await?;
+
+        Ok(SearchResults::new(self.clone(), page))
+    }
+}
+
+/// The results found on a single page of an `InfoSearch`.
+pub struct SearchResults {
+    pub search: InfoSearch,
+    pub results: Vec<SeriesInfo>,
+    pub has_next_page: bool,
+}
+
+impl SearchResults {
+    fn new(search: InfoSearch, page: SearchPage) -> Self {
+        Self {
+            search,
+            results: page.results.into_iter().map(Into::into).collect(),
+            has_next_page: page.has_next_page,
+        }
+    }
 }
@@ -0,0 +1,348 @@
+//! Reading and writing lists in the XML format MyAnimeList exports them in.
+//!
+//! Most trackers can import and export this format, so it serves as a backup of the library that isn't tied to any
+//! one service.
+
+use anime::remote::{SeriesDate, SeriesEntry, SeriesID, Status};
+use anyhow::{anyhow, Context, Result};
+use std::fmt::Write;
+
+/// An anime in a MyAnimeList export.
+#[derive(Clone, Debug)]
+pub struct MalEntry {
+    /// The user's list entry for the anime, with its MyAnimeList ID.
+    pub entry: SeriesEntry,
+    pub title: String,
+    /// The number of episodes of the anime, or 0 if it isn't known.
+    pub episodes: u32,
+}
+
+impl MalEntry {
+    #[inline(always)]
+    pub fn id(&self) -> SeriesID {
+        self.entry.id
+    }
+}
+
+/// Write `entries` as a MyAnimeList anime list export.
+pub fn export(entries: &[MalEntry]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" ?>\n<myanimelist>\n\t<myinfo>\n\t\t<user_export_type>1</user_export_type>\n\t</myinfo>\n",
+    );
+
+    for mal_entry in entries {
+        let entry = &mal_entry.entry;
+
+        xml.push_str("\t<anime>\n");
+
+        let mut field = |name: &str, value: &dyn std::fmt::Display| {
+            // Writing to a string can't fail
+            writeln!(xml, "\t\t<{0}>{1}</{0}>", name, value).ok();
+        };
+
+        field("series_animedb_id", &entry.id);
+        field("series_title", &cdata(&mal_entry.title));
+        field("series_episodes", &mal_entry.episodes);
+        field("my_watched_episodes", &entry.watched_eps);
+        field("my_start_date", &date_to_str(entry.start_date));
+        field("my_finish_date", &date_to_str(entry.end_date));
+        field(
+            "my_score",
+            &entry.score.map_or(0, |score| score.saturating_add(5) / 10),
+        );
+        field("my_status", &status_to_str(entry.status));
+        field(
+            "my_comments",
+            &cdata(entry.notes.as_deref().unwrap_or_default()),
+        );
+        field("my_times_watched", &entry.times_rewatched);
+        field(
+            "my_rewatching",
+            &u8::from(entry.status == Status::Rewatching),
+        );
+        field("update_on_import", &1);
+
+        xml.push_str("\t</anime>\n");
+    }
+
+    xml.push_str("</myanimelist>\n");
+    xml
+}
+
+/// Parse the anime in a MyAnimeList anime list export.
+///
+/// Only the fields that are needed to fill in a list entry are read, so exports from other trackers that leave the
+/// rest out can be parsed as well.
+pub fn parse(xml: &str) -> Result<Vec<MalEntry>> {
+    let mut entries = Vec::new();
+    let mut rest = xml;
+
+    while let Some((block, remaining)) = element(rest, "anime") {
+        let entry = parse_anime(block)
+            .with_context(|| format!("failed to parse anime #{}", entries.len() + 1))?;
+
+        entries.push(entry);
+        rest = remaining;
+    }
+
+    if entries.is_empty() && element(xml, "myanimelist").is_none() {
+        return Err(anyhow!("not a MyAnimeList export"));
+    }
+
+    Ok(entries)
+}
+
+fn parse_anime(block: &str) -> Result<MalEntry> {
+    let field = |name: &str| element(block, name).map(|(value, _)| unescape(value));
+
+    let number = |name: &str| -> Result<u32> {
+        match field(name) {
+            Some(value) if !value.is_empty() => value
+                .parse()
+                .map_err(|_| anyhow!("{} is not a number: {}", name, value)),
+            _ => Ok(0),
+        }
+    };
+
+    let id = match field("series_animedb_id") {
+        Some(id) => id.parse().map_err(|_| anyhow!("invalid ID: {}", id))?,
+        None => return Err(anyhow!("no series_animedb_id found")),
+    };
+
+    let rewatching = number("my_rewatching")? > 0;
+
+    let status = match field("my_status") {
+        Some(status) => parse_status(&status)?,
+        None => return Err(anyhow!("no my_status found")),
+    };
+
+    let status = match status {
+        Status::Completed | Status::Watching if rewatching => Status::Rewatching,
+        status => status,
+    };
+
+    let score = number("my_score")?;
+    let notes = field("my_comments").filter(|notes| !notes.is_empty());
+
+    let entry = SeriesEntry {
+        watched_eps: number("my_watched_episodes")?,
+        score: (score > 0).then(|| score.min(10) as u8 * 10),
+        status,
+        times_rewatched: number("my_times_watched")?,
+        start_date: field("my_start_date").and_then(|date| parse_date(&date)),
+        end_date: field("my_finish_date").and_then(|date| parse_date(&date)),
+        notes,
+        ..SeriesEntry::new(id)
+    };
+
+    Ok(MalEntry {
+        entry,
+        title: field("series_title").unwrap_or_default(),
+        episodes: number("series_episodes")?,
+    })
+}
+
+/// Find the first element named `name` in `xml`, and return its raw contents along with everything after it.
+fn element<'a>(xml: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+    let open = format!("<{}>", name);
+    let empty = format!("<{}/>", name);
+    let close = format!("</{}>", name);
+
+    let open_pos = xml.find(&open);
+
+    match xml.find(&empty) {
+        Some(pos) if open_pos.map_or(true, |open_pos| pos < open_pos) => {
+            return Some(("", &xml[pos + empty.len()..]));
+        }
+        _ => (),
+    }
+
+    let start = open_pos? + open.len();
+    let end = start + xml[start..].find(&close)?;
+
+    Some((&xml[start..end], &xml[end + close.len()..]))
+}
+
+fn cdata(value: &str) -> String {
+    // A CDATA section can't contain its own terminator, so it has to be split across two sections
+    format!("<![CDATA[{}]]>", value.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        if let Some(section) = rest.strip_prefix("<![CDATA[") {
+            let end = section.find("]]>").unwrap_or_else(|| section.len());
+            result.push_str(&section[..end]);
+            rest = section.get(end + 3..).unwrap_or_default();
+            continue;
+        }
+
+        let next = rest
+            .find(|ch| ch == '&' || ch == '<')
+            .unwrap_or_else(|| rest.len());
+
+        if next > 0 {
+            result.push_str(&rest[..next]);
+            rest = &rest[next..];
+            continue;
+        }
+
+        let entity = ["&amp;", "&lt;", "&gt;", "&quot;", "&apos;"]
+            .iter()
+            .zip(&['&', '<', '>', '"', '\''])
+            .find(|(entity, _)| rest.starts_with(*entity));
+
+        match entity {
+            Some((entity, &ch)) => {
+                result.push(ch);
+                rest = &rest[entity.len()..];
+            }
+            None => {
+                let ch = rest.chars().next().unwrap_or_default();
+                result.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+
+    result
+}
+
+fn status_to_str(status: Status) -> &'static str {
+    match status {
+        Status::Watching => "Watching",
+        // MyAnimeList represents a rewatch as a completed entry with a rewatching flag
+        Status::Completed | Status::Rewatching => "Completed",
+        Status::OnHold => "On-Hold",
+        Status::Dropped => "Dropped",
+        Status::PlanToWatch => "Plan to Watch",
+    }
+}
+
+fn parse_status(status: &str) -> Result<Status> {
+    // Some trackers export the numeric status MyAnimeList uses internally instead
+    match status.to_ascii_lowercase().as_str() {
+        "watching" | "1" => Ok(Status::Watching),
+        "completed" | "2" => Ok(Status::Completed),
+        "on-hold" | "3" => Ok(Status::OnHold),
+        "dropped" | "4" => Ok(Status::Dropped),
+        "plan to watch" | "6" => Ok(Status::PlanToWatch),
+        _ => Err(anyhow!("unknown status: {}", status)),
+    }
+}
+
+fn date_to_str(date: Option<SeriesDate>) -> String {
+    match date {
+        Some(date) => format!("{:04}-{:02}-{:02}", date.year, date.month, date.day),
+        None => "0000-00-00".into(),
+    }
+}
+
+/// Parse a date in the YYYY-MM-DD format, where unknown dates are written as 0000-00-00.
+fn parse_date(date: &str) -> Option<SeriesDate> {
+    let mut parts = date.splitn(3, '-');
+
+    match (
+        parts.next()?.parse(),
+        parts.next()?.parse(),
+        parts.next()?.parse(),
+    ) {
+        (Ok(year), Ok(month), Ok(day)) if year > 0 && month > 0 && day > 0 => {
+            Some(SeriesDate::from_ymd(year, month, day))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mal_entry(id: SeriesID, status: Status) -> MalEntry {
+        MalEntry {
+            entry: SeriesEntry {
+                watched_eps: 3,
+                score: Some(75),
+                status,
+                times_rewatched: 1,
+                start_date: Some(SeriesDate::from_ymd(2020, 4, 9)),
+                notes: Some("Tom & Jerry <3 ]]>".into()),
+                ..SeriesEntry::new(id)
+            },
+            title: "Kaguya-sama: Love is War?".into(),
+            episodes: 12,
+        }
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let entries = vec![
+            mal_entry(1, Status::Rewatching),
+            mal_entry(2, Status::PlanToWatch),
+        ];
+
+        let parsed = parse(&export(&entries)).unwrap();
+
+        assert_eq!(parsed.len(), 2);
+
+        let first = &parsed[0];
+        assert_eq!(first.id(), 1);
+        assert_eq!(first.title, entries[0].title);
+        assert_eq!(first.episodes, 12);
+        assert_eq!(first.entry.status, Status::Rewatching);
+        assert_eq!(first.entry.watched_eps, 3);
+        // Scores are rounded to the nearest point out of 10
+        assert_eq!(first.entry.score, Some(80));
+        assert_eq!(first.entry.times_rewatched, 1);
+        assert_eq!(
+            first
+                .entry
+                .start_date
+                .map(|date| (date.year, date.month, date.day)),
+            Some((2020, 4, 9))
+        );
+        assert!(first.entry.end_date.is_none());
+        assert_eq!(first.entry.notes, entries[0].entry.notes);
+
+        assert_eq!(parsed[1].entry.status, Status::PlanToWatch);
+    }
+
+    #[test]
+    fn minimal_entries_are_parsed() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <myanimelist>
+                <anime>
+                    <series_animedb_id>5114</series_animedb_id>
+                    <series_title>Fullmetal Alchemist: Brotherhood &amp; More</series_title>
+                    <my_watched_episodes>64</my_watched_episodes>
+                    <my_score>0</my_score>
+                    <my_status>2</my_status>
+                    <my_comments/>
+                </anime>
+            </myanimelist>"#;
+
+        let parsed = parse(xml).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].id(), 5114);
+        assert_eq!(parsed[0].title, "Fullmetal Alchemist: Brotherhood & More");
+        assert_eq!(parsed[0].entry.status, Status::Completed);
+        assert_eq!(parsed[0].entry.watched_eps, 64);
+        assert_eq!(parsed[0].entry.score, None);
+        assert_eq!(parsed[0].entry.notes, None);
+    }
+
+    #[test]
+    fn invalid_exports_are_rejected() {
+        assert!(parse("{}").is_err());
+        assert!(parse("<myanimelist></myanimelist>").unwrap().is_empty());
+
+        let unknown_status =
+            "<myanimelist><anime><series_animedb_id>1</series_animedb_id><my_status>Paused</my_status></anime></myanimelist>";
+
+        assert!(parse(unknown_status).is_err());
+    }
+}
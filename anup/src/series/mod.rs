@@ -1,6 +1,8 @@
 pub mod config;
 pub mod entry;
 pub mod info;
+pub mod mal_xml;
+pub mod unmatched;
 
 use crate::config::Config;
 use crate::database::Database;
@@ -8,7 +10,7 @@ use crate::file;
 use crate::file::SaveDir;
 use crate::try_opt_r;
 use anime::local::{CategorizedEpisodes, EpisodeParser, SortedEpisodes};
-use anime::remote::{Remote, SeriesID, Status};
+use anime::remote::{AsyncRemote, Remote, SeriesID, Status};
 use anyhow::{anyhow, Context, Error, Result};
 use chrono::{DateTime, Duration, Utc};
 use config::SeriesConfig;
@@ -48,8 +50,12 @@ pub struct SeriesData {
 }
 
 impl SeriesData {
-    pub fn from_remote(config: SeriesConfig, info: SeriesInfo, remote: &Remote) -> Result<Self> {
-        let entry = SeriesEntry::from_remote(remote, &info)?;
+    pub async fn from_remote(
+        config: SeriesConfig,
+        info: SeriesInfo,
+        remote: &AsyncRemote,
+    ) -> Result<Self> {
+        let entry = SeriesEntry::from_remote_async(remote, &info).await?;
 
         Ok(Self {
             config,
@@ -73,15 +79,25 @@ impl SeriesData {
         })
     }
 
+    #[allow(clippy::cast_possible_wrap)]
     pub fn update(&mut self, params: UpdateParams, db: &Database, remote: &Remote) -> Result<()> {
-        let id_changed = self.config.update(params, db, remote)?;
+        // The series info is retrieved first so the config is left as it is if that fails
+        let remote_data = match params.id {
+            Some(id) if id as i32 != self.config.id => {
+                let info =
+                    SeriesInfo::from_remote_by_id(id, remote).context("getting series info")?;
 
-        if id_changed {
-            let info = SeriesInfo::from_remote_by_id(self.config.id as SeriesID, remote)
-                .context("getting series info")?;
+                let entry =
+                    SeriesEntry::from_remote(remote, &info).context("getting series entry")?;
 
-            let entry = SeriesEntry::from_remote(remote, &info).context("getting series entry")?;
+                Some((info, entry))
+            }
+            Some(_) | None => None,
+        };
+
+        self.config.update(params, db)?;
 
+        if let Some((info, entry)) = remote_data {
             self.info = info;
             self.entry = entry;
         }
@@ -89,16 +105,6 @@ impl SeriesData {
         Ok(())
     }
 
-    pub fn force_sync_from_remote(&mut self, remote: &Remote) -> Result<()> {
-        // We don't want to set the new info now in case the entry sync fails
-        let info = SeriesInfo::from_remote_by_id(self.info.id as SeriesID, remote)?;
-
-        self.entry.force_sync_from_remote(remote)?;
-        self.info = info;
-
-        Ok(())
-    }
-
     pub fn save(&self, db: &Database) -> diesel::QueryResult<()> {
         db.conn()
             .transaction(|| {
@@ -237,7 +243,7 @@ impl Series {
             Status::Watching | Status::Rewatching => {
                 // There is an edge case where all episodes have been watched, but the status
                 // is still set to watching / rewatching. Here we just start a rewatch
-                if entry.watched_episodes() >= self.data.info.episodes {
+                if self.data.info.is_completed_by(entry.watched_episodes()) {
                     entry.set_status(Status::Rewatching, config);
                     entry.set_watched_episodes(0);
 
@@ -257,10 +263,7 @@ impl Series {
             }
         }
 
-        self.data.entry.sync_to_remote(remote)?;
-        self.save(db)?;
-
-        Ok(())
+        self.sync_and_save(remote, db)
     }
 
     pub fn episode_completed(
@@ -271,7 +274,7 @@ impl Series {
     ) -> Result<()> {
         let new_progress = self.data.entry.watched_episodes() + 1;
 
-        if new_progress >= self.data.info.episodes {
+        if self.data.info.is_completed_by(new_progress) {
             // The watched episode range is inclusive, so it's fine to bump the watched count
             // if we're at exactly at the last episode
             if new_progress == self.data.info.episodes {
@@ -282,10 +285,7 @@ impl Series {
         }
 
         self.data.entry.set_watched_episodes(new_progress);
-        self.data.entry.sync_to_remote(remote)?;
-        self.save(db)?;
-
-        Ok(())
+        self.sync_and_save(remote, db)
     }
 
     pub fn episode_regressed(
@@ -304,10 +304,7 @@ impl Series {
         };
 
         entry.set_status(new_status, config);
-        entry.sync_to_remote(remote)?;
-        self.save(db)?;
-
-        Ok(())
+        self.sync_and_save(remote, db)
     }
 
     pub fn series_complete(
@@ -324,10 +321,16 @@ impl Series {
         }
 
         entry.set_status(Status::Completed, config);
-        entry.sync_to_remote(remote)?;
-        self.save(db)?;
+        self.sync_and_save(remote, db)
+    }
 
-        Ok(())
+    /// Sync the list entry to `remote` and save the series.
+    ///
+    /// The series is saved even if the sync fails, so the changes can be synced later.
+    fn sync_and_save(&mut self, remote: &Remote, db: &Database) -> Result<()> {
+        let result = self.data.entry.sync_to_remote(remote);
+        self.save(db)?;
+        result
     }
 }
 
@@ -370,6 +373,14 @@ impl LoadedSeries {
         }
     }
 
+    pub fn entry(&self) -> Option<&SeriesEntry> {
+        match self {
+            Self::Complete(series) => Some(&series.data.entry),
+            Self::Partial(data, _) => Some(&data.entry),
+            Self::None(_, _) => None,
+        }
+    }
+
     pub fn complete_mut(&mut self) -> Option<&mut Series> {
         match self {
             Self::Complete(series) => Some(series),
@@ -414,7 +425,7 @@ impl LoadedSeries {
                 data.save(db)?;
             }
             Self::None(cfg, _) => {
-                cfg.update(params, db, remote)?;
+                cfg.update(params, db)?;
                 cfg.save(db)?;
             }
         }
@@ -599,19 +610,64 @@ impl SeriesPath {
         }
     }
 
+    const MIN_MATCH_CONFIDENCE: f32 = 0.6;
+
     pub fn closest_matching(name: &str, config: &Config) -> Result<Self> {
         use anime::local::detect::dir;
 
-        const MIN_CONFIDENCE: f32 = 0.6;
-
         let dirs = file::subdirectories(&config.series_dir)?;
 
-        dir::closest_match(name, MIN_CONFIDENCE, dirs.into_iter()).map_or_else(
+        dir::closest_match(name, Self::MIN_MATCH_CONFIDENCE, dirs.into_iter()).map_or_else(
             || Err(anyhow!("no series found on disk matching {}", name)),
             |dir| Ok(Self::new(dir.path(), config)),
         )
     }
 
+    /// Find the directory in `dirs` that best matches each series in `infos`.
+    ///
+    /// Every directory is only given to the series it matches best, so the directory of a sequel won't also be used
+    /// for the series before it. The returned paths are in the same order as `infos`.
+    pub fn closest_matching_each(
+        infos: &[&anime::remote::SeriesInfo],
+        dirs: &[fs::DirEntry],
+        config: &Config,
+    ) -> Vec<Option<Self>> {
+        use anime::local::detect::dir;
+        use anime::remote::matcher::SeriesMatcher;
+
+        let mut best: Vec<Option<(PathBuf, f32)>> = vec![None; infos.len()];
+
+        for entry in dirs {
+            let path = entry.path();
+
+            let title = match dir::parse_title(&path) {
+                Some(title) => title,
+                None => continue,
+            };
+
+            let matcher = SeriesMatcher::new(&title);
+            let items = infos.iter().map(|&info| Cow::Borrowed(info));
+
+            let (index, info) = match matcher.best_match(items, Self::MIN_MATCH_CONFIDENCE) {
+                Some(found) => found,
+                None => continue,
+            };
+
+            let score = matcher.score(&info);
+
+            if best[index]
+                .as_ref()
+                .map_or(true, |(_, best_score)| score > *best_score)
+            {
+                best[index] = Some((path, score));
+            }
+        }
+
+        best.into_iter()
+            .map(|found| found.map(|(path, _)| Self::new(path, config)))
+            .collect()
+    }
+
     #[inline(always)]
     pub fn inner(&self) -> &PathBuf {
         &self.0
@@ -0,0 +1,62 @@
+use crate::database::schema::unmatched_imports;
+use crate::database::Database;
+use anime::remote::UserListEntry;
+use anyhow::{Context, Result};
+use diesel::prelude::*;
+
+/// A series from the user's remote list that was imported without a directory.
+///
+/// The series info and entry tables only hold series in the library, so the list entry is staged here until the
+/// series is given a directory.
+#[derive(Queryable, Insertable)]
+#[table_name = "unmatched_imports"]
+pub struct UnmatchedImport {
+    pub id: i32,
+    list_entry: Vec<u8>,
+}
+
+impl UnmatchedImport {
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn new(list_entry: &UserListEntry) -> Result<Self> {
+        let encoded = bincode::serialize(list_entry).context("encoding list entry")?;
+
+        Ok(Self {
+            id: list_entry.info.id as i32,
+            list_entry: encoded,
+        })
+    }
+
+    pub fn load(db: &Database, import_id: i32) -> diesel::QueryResult<Self> {
+        use crate::database::schema::unmatched_imports::dsl::{id, unmatched_imports};
+
+        unmatched_imports
+            .filter(id.eq(import_id))
+            .get_result(db.conn())
+    }
+
+    pub fn save(&self, db: &Database) -> diesel::QueryResult<usize> {
+        use crate::database::schema::unmatched_imports::dsl::unmatched_imports;
+
+        diesel::replace_into(unmatched_imports)
+            .values(self)
+            .execute(db.conn())
+    }
+
+    /// Remove the import from the staging table once it has been added to the library.
+    pub fn remove(&self, db: &Database) -> diesel::QueryResult<usize> {
+        use crate::database::schema::unmatched_imports::dsl::{id, unmatched_imports};
+
+        diesel::delete(unmatched_imports.filter(id.eq(self.id))).execute(db.conn())
+    }
+
+    /// Remove every staged import, so only the ones from the latest import are kept.
+    pub fn clear(db: &Database) -> diesel::QueryResult<usize> {
+        use crate::database::schema::unmatched_imports::dsl::unmatched_imports;
+
+        diesel::delete(unmatched_imports).execute(db.conn())
+    }
+
+    pub fn list_entry(&self) -> Result<UserListEntry> {
+        bincode::deserialize(&self.list_entry).context("decoding list entry")
+    }
+}
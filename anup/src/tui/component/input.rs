@@ -30,6 +30,8 @@ bitflags! {
         const IGNORE_PLACEHOLDER = 0b0100;
         /// Indicates that the input is disabled and will not accept input, even if selected.
         const DISABLED = 0b1000;
+        /// Indicates that the input's text should not be shown, such as for passwords.
+        const MASKED = 0b1_0000;
     }
 }
 
@@ -151,7 +153,13 @@ impl Input {
             }
             _ => {
                 let visible_offset = self.get_visible_offset(content_area.width);
-                self.caret.buffer[visible_offset..].into()
+                let visible = &self.caret.buffer[visible_offset..];
+
+                if self.flags.contains(InputFlags::MASKED) {
+                    "*".repeat(visible.width()).into()
+                } else {
+                    visible.into()
+                }
             }
         };
 
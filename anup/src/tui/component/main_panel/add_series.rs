@@ -1,4 +1,4 @@
-use super::PartialSeries;
+use super::SeriesSearch;
 use crate::tui::component::input::{
     DrawInput, IDInput, Input, InputFlags, NameInput, ParsedValue, ParserInput, PathInput,
     ValidatedInput,
@@ -7,10 +7,7 @@ use crate::tui::component::Component;
 use crate::tui::UIState;
 use crate::{config::Config, key::Key};
 use crate::{file, tui::state::SharedState};
-use crate::{
-    series::info::{InfoSelector, SeriesInfo},
-    util::ArcMutex,
-};
+use crate::{series::info::InfoSelector, util::ArcMutex};
 use crate::{
     series::{self, LoadedSeries, SeriesParams, SeriesPath, UpdateParams},
     util::arc_mutex,
@@ -408,7 +405,7 @@ impl Component for AddSeriesPanel {
 pub enum AddSeriesResult {
     Ok,
     Reset,
-    AddSeries(Box<PartialSeries>),
+    AddSeries(Box<SeriesSearch>),
     UpdateSeries(Box<UpdateParams>),
 }
 
@@ -484,21 +481,15 @@ impl SeriesBuilder {
 
         match mode {
             Mode::AddSeries => {
-                let remote = state.remote.get_logged_in()?;
-
-                let info = {
-                    let id = inputs.id.parsed_value();
-                    let sel = id.map_or_else(
-                        || InfoSelector::from_path_or_name(&params.path, &params.name),
-                        InfoSelector::ID,
-                    );
-
-                    SeriesInfo::from_remote(sel, remote)?
-                };
+                let id = inputs.id.parsed_value();
+                let sel = id.map_or_else(
+                    || InfoSelector::from_path_or_name(&params.path, &params.name),
+                    InfoSelector::ID,
+                );
 
-                let partial = PartialSeries::new(info, params, episodes);
+                let search = SeriesSearch::new(sel, params, episodes);
 
-                Ok(AddSeriesResult::AddSeries(partial.into()))
+                Ok(AddSeriesResult::AddSeries(search.into()))
             }
             Mode::UpdateSeries => {
                 let params = UpdateParams {
@@ -0,0 +1,236 @@
+use super::load_state::{self, LoadState};
+use super::ShouldReset;
+use crate::key::Key;
+use crate::tui::component::Component;
+use crate::tui::state::{SharedState, UIState};
+use crate::util::{arc_mutex, ArcMutex, ScopedTask};
+use anime::remote::{AiringEpisode, SeriesID, Status};
+use chrono::{Date, Duration, Local, TimeZone};
+use crossterm::event::KeyCode;
+use std::collections::HashMap;
+use tokio::task;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Rect};
+use tui::style::Color;
+use tui::terminal::Frame;
+use tui::text::Span;
+use tui_utils::{
+    helpers::{block, text},
+    layout::RectExt,
+    list::WrappedSelection,
+    widgets::{SimpleList, SimpleText},
+};
+
+/// The number of days shown on the calendar, including today.
+const CALENDAR_DAYS: i64 = 7;
+
+/// A panel that shows when the next episodes of every series being watched or planned to be watched air over the next week.
+pub struct CalendarPanel {
+    state: ArcMutex<PanelState>,
+    #[allow(dead_code)]
+    schedule_task: ScopedTask<()>,
+}
+
+impl CalendarPanel {
+    pub fn new(state: &SharedState) -> Self {
+        let panel_state = arc_mutex(PanelState::Loading);
+        let schedule_task = Self::spawn_schedule_task(&panel_state, state).into();
+
+        Self {
+            state: panel_state,
+            schedule_task,
+        }
+    }
+
+    fn spawn_schedule_task(
+        panel_state: &ArcMutex<PanelState>,
+        state: &SharedState,
+    ) -> task::JoinHandle<()> {
+        let shared_state = state.clone();
+
+        let load = async move {
+            let (remote, nicknames) = {
+                let state = shared_state.lock();
+                let state = state.get();
+                let remote = state.remote.get_logged_in_async()?.clone();
+
+                (remote, Calendar::tracked_series(state))
+            };
+
+            let today = Local::today();
+            let from = Local::now().timestamp();
+            let to = (today + Duration::days(CALENDAR_DAYS))
+                .and_hms(0, 0, 0)
+                .timestamp();
+
+            let ids = nicknames.keys().copied().collect();
+            let episodes = remote.get_airing_schedule(ids, from, to).await?;
+
+            Ok::<_, anyhow::Error>((today, episodes, nicknames))
+        };
+
+        PanelState::spawn_load(
+            panel_state,
+            state,
+            load,
+            |(today, episodes, nicknames), _| Calendar::new(today, &episodes, &nicknames),
+        )
+    }
+
+    fn draw_calendar<B>(calendar: &Calendar, rect: Rect, frame: &mut Frame<B>)
+    where
+        B: Backend,
+    {
+        let hint = SimpleText::new(text::hint("Esc - Close")).alignment(Alignment::Center);
+        frame.render_widget(hint, rect.lines_from_bottom(1));
+
+        // Leave room for the hint, along with a spacer between it and the calendar
+        let list_rect = Rect {
+            height: rect.height.saturating_sub(2),
+            ..rect
+        };
+
+        let lines = calendar.lines.iter().map(|line| match line {
+            CalendarLine::Day(day) => text::bold(day.as_str()),
+            CalendarLine::Episode(episode) => Span::raw(episode.as_str()),
+            CalendarLine::Empty => text::italic("  Nothing airing"),
+        });
+
+        let list = SimpleList::new(lines)
+            .highlight_symbol(text::italic_with(">", |s| s.fg(Color::Green)))
+            .select(Some(calendar.lines.index() as u16));
+
+        frame.render_widget(list, list_rect);
+    }
+
+    pub fn draw<B: Backend>(&mut self, rect: Rect, frame: &mut Frame<B>) {
+        let block = block::with_borders("Airing Calendar");
+        let block_area = block.inner(rect);
+
+        frame.render_widget(block, rect);
+
+        self.state.lock().draw(
+            "Failed to get the airing schedule",
+            block_area,
+            frame,
+            |calendar, rect, frame| {
+                if calendar.num_episodes == 0 {
+                    load_state::draw_status("Nothing being watched airs this week", rect, frame);
+                } else {
+                    Self::draw_calendar(calendar, rect, frame);
+                }
+            },
+        );
+    }
+}
+
+impl Component for CalendarPanel {
+    type State = UIState;
+    type KeyResult = ShouldReset;
+
+    fn process_key(&mut self, key: Key, _: &mut Self::State) -> Self::KeyResult {
+        let mut state = self.state.lock();
+
+        match (*key, &mut *state) {
+            (KeyCode::Esc, _) => ShouldReset::Yes,
+            (KeyCode::Up, PanelState::Loaded(calendar)) => {
+                calendar.lines.dec_selected();
+                ShouldReset::No
+            }
+            (KeyCode::Down, PanelState::Loaded(calendar)) => {
+                calendar.lines.inc_selected();
+                ShouldReset::No
+            }
+            _ => ShouldReset::No,
+        }
+    }
+}
+
+type PanelState = LoadState<Calendar>;
+
+enum CalendarLine {
+    Day(String),
+    Episode(String),
+    Empty,
+}
+
+struct Calendar {
+    lines: WrappedSelection<Vec<CalendarLine>, CalendarLine>,
+    num_episodes: usize,
+}
+
+impl Calendar {
+    /// Group `episodes` by the local day they air on, starting from `today`.
+    fn new(
+        today: Date<Local>,
+        episodes: &[AiringEpisode],
+        nicknames: &HashMap<SeriesID, String>,
+    ) -> Self {
+        let mut lines = Vec::new();
+        let mut num_episodes = 0;
+
+        for offset in 0..CALENDAR_DAYS {
+            let day = today + Duration::days(offset);
+
+            let day_name = match offset {
+                0 => "Today".into(),
+                1 => "Tomorrow".into(),
+                _ => day.format("%A").to_string(),
+            };
+
+            lines.push(CalendarLine::Day(format!(
+                "{} ({})",
+                day_name,
+                day.format("%b %-d")
+            )));
+
+            let airing = episodes
+                .iter()
+                .map(|episode| (episode, Local.timestamp(episode.airs_at, 0)))
+                .filter(|(_, airs_at)| airs_at.date() == day)
+                .collect::<Vec<_>>();
+
+            if airing.is_empty() {
+                lines.push(CalendarLine::Empty);
+                continue;
+            }
+
+            for (episode, airs_at) in airing {
+                let nickname = nicknames.get(&episode.id).map_or("Unknown", String::as_str);
+
+                lines.push(CalendarLine::Episode(format!(
+                    "  {}  {} - episode {}",
+                    airs_at.format("%H:%M"),
+                    nickname,
+                    episode.episode
+                )));
+
+                num_episodes += 1;
+            }
+        }
+
+        Self {
+            lines: WrappedSelection::new(lines),
+            num_episodes,
+        }
+    }
+
+    /// Returns the nickname of every series that is being watched or planned to be watched, keyed by its ID.
+    #[allow(clippy::cast_sign_loss)]
+    fn tracked_series(state: &UIState) -> HashMap<SeriesID, String> {
+        state
+            .series
+            .iter()
+            .filter_map(|series| {
+                let status = series.entry()?.status();
+
+                if !matches!(status, Status::Watching | Status::PlanToWatch) {
+                    return None;
+                }
+
+                let id = series.id()? as SeriesID;
+                Some((id, series.nickname().to_string()))
+            })
+            .collect()
+    }
+}
@@ -1,5 +1,6 @@
 use super::ShouldReset;
-use crate::tui::state::UIState;
+use crate::series::entry::PendingDeletion;
+use crate::tui::state::{SharedState, UIState};
 use crate::{key::Key, tui::component::Component};
 use anyhow::{anyhow, Context, Result};
 use crossterm::event::KeyCode;
@@ -16,13 +17,15 @@ use tui_utils::{
 };
 
 pub struct DeleteSeriesPanel {
-    remove_files: RemoveFiles,
+    remove_files: Remove,
+    remove_remote_entry: Remove,
     removal_warning_text: String,
     series_path_text: String,
+    state: SharedState,
 }
 
 impl DeleteSeriesPanel {
-    pub fn init(state: &UIState) -> Result<Self> {
+    pub fn init(state: &UIState, shared_state: &SharedState) -> Result<Self> {
         let series = match state.series.selected() {
             Some(series) => series,
             None => return Err(anyhow!("must select a series to delete")),
@@ -32,16 +35,26 @@ impl DeleteSeriesPanel {
         let series_path_text = series.path().inner().to_string_lossy().into_owned();
 
         Ok(Self {
-            remove_files: RemoveFiles::default(),
+            remove_files: Remove::default(),
+            remove_remote_entry: Remove::default(),
             removal_warning_text,
             series_path_text,
+            state: shared_state.clone(),
         })
     }
 
     fn delete_selected_series(&self, state: &mut UIState) -> Result<()> {
         let series = state.delete_selected_series()?;
 
-        if let RemoveFiles::Yes = self.remove_files {
+        if let Remove::Yes = self.remove_remote_entry {
+            let deletion = PendingDeletion::new(series.config().id, state.users.last_used.as_ref());
+
+            // The deletion is queued first so it can be retried on the next sync if it fails
+            deletion.save(&state.db)?;
+            self.state.delete_remote_entry_async(deletion);
+        }
+
+        if let Remove::Yes = self.remove_files {
             let path = series.config().path.absolute(&state.config);
             fs::remove_dir_all(path).context("failed to remove directory")?;
         }
@@ -70,16 +83,17 @@ impl DeleteSeriesPanel {
 
         frame.render_widget(path_widget, path_rect);
 
-        let delete_status_text = match self.remove_files {
-            RemoveFiles::Yes => text::bold_with("will be deleted.", |s| s.fg(Color::Red)),
-            RemoveFiles::No => text::bold("will not be deleted."),
-        };
-
         let full_status_frags = {
             let frags = [
                 Fragment::span(Span::raw("The series path on disk ")),
                 Fragment::Span(
-                    delete_status_text,
+                    self.remove_files.status_text(),
+                    SpanOptions::new().overflow(OverflowMode::Truncate),
+                ),
+                Fragment::Line,
+                Fragment::span(Span::raw("The remote list entry ")),
+                Fragment::Span(
+                    self.remove_remote_entry.status_text(),
                     SpanOptions::new().overflow(OverflowMode::Truncate),
                 ),
             ];
@@ -93,16 +107,26 @@ impl DeleteSeriesPanel {
     }
 
     fn draw_hints<B: Backend>(rect: Rect, frame: &mut Frame<B>) {
-        let horiz_layout =
-            SimpleLayout::new(Direction::Horizontal).split_evenly(rect.lines_from_bottom(1));
+        let confirm_pos = rect.lines_from_bottom(1);
+
+        let toggle_pos = Rect {
+            y: confirm_pos.y.saturating_sub(1).max(rect.y),
+            ..confirm_pos
+        };
+
+        let horiz_layout = SimpleLayout::new(Direction::Horizontal).split_evenly(toggle_pos);
 
         let hint_text = text::hint("D - Toggle path deletion");
         let hint_widget = SimpleText::new(hint_text).alignment(Alignment::Center);
         frame.render_widget(hint_widget, horiz_layout.left);
 
-        let hint_text = text::hint("Enter - Confirm");
+        let hint_text = text::hint("R - Toggle remote deletion");
         let hint_widget = SimpleText::new(hint_text).alignment(Alignment::Center);
         frame.render_widget(hint_widget, horiz_layout.right);
+
+        let hint_text = text::hint("Enter - Confirm");
+        let hint_widget = SimpleText::new(hint_text).alignment(Alignment::Center);
+        frame.render_widget(hint_widget, confirm_pos);
     }
 
     pub fn draw<B: Backend>(&mut self, rect: Rect, frame: &mut Frame<B>) {
@@ -144,6 +168,10 @@ impl Component for DeleteSeriesPanel {
                 self.remove_files.toggle();
                 Ok(ShouldReset::No)
             }
+            KeyCode::Char('r') => {
+                self.remove_remote_entry.toggle();
+                Ok(ShouldReset::No)
+            }
             KeyCode::Enter => {
                 self.delete_selected_series(state)?;
                 Ok(ShouldReset::Yes)
@@ -154,12 +182,12 @@ impl Component for DeleteSeriesPanel {
 }
 
 #[derive(Copy, Clone)]
-enum RemoveFiles {
+enum Remove {
     Yes,
     No,
 }
 
-impl RemoveFiles {
+impl Remove {
     fn next(self) -> Self {
         match self {
             Self::Yes => Self::No,
@@ -167,13 +195,20 @@ impl RemoveFiles {
         }
     }
 
+    fn status_text(self) -> Span<'static> {
+        match self {
+            Self::Yes => text::bold_with("will be deleted.", |s| s.fg(Color::Red)),
+            Self::No => text::bold("will not be deleted."),
+        }
+    }
+
     #[inline(always)]
     fn toggle(&mut self) {
         *self = self.next();
     }
 }
 
-impl Default for RemoveFiles {
+impl Default for Remove {
     fn default() -> Self {
         Self::No
     }
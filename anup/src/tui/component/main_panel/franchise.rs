@@ -0,0 +1,383 @@
+use super::load_state::LoadState;
+use super::ShouldReset;
+use crate::config::Config;
+use crate::file;
+use crate::key::Key;
+use crate::series::config::SeriesConfig;
+use crate::series::{self, LoadedSeries, SeriesParams, SeriesPath};
+use crate::tui::component::Component;
+use crate::tui::state::{SharedState, UIState};
+use crate::util::{arc_mutex, ArcMutex, ScopedTask};
+use anime::local::EpisodeParser;
+use anime::remote::{AsyncRemote, RelationGraph, SeriesEntry, SeriesID, SeriesInfo, Status};
+use anyhow::{anyhow, Result};
+use crossterm::event::KeyCode;
+use std::collections::HashMap;
+use tokio::task;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Direction, Rect};
+use tui::style::Color;
+use tui::terminal::Frame;
+use tui::text::Span;
+use tui_utils::{
+    helpers::{block, style, text},
+    layout::{BasicConstraint, RectExt, SimpleLayout},
+    list::WrappedSelection,
+    widgets::{SimpleTable, SimpleText},
+};
+
+/// A panel that lists every series in the same franchise as the selected series, and lets the ones that
+/// haven't been added yet be added.
+pub struct FranchisePanel {
+    title: String,
+    state: ArcMutex<PanelState>,
+    shared_state: SharedState,
+    #[allow(dead_code)]
+    walk_task: ScopedTask<()>,
+}
+
+impl FranchisePanel {
+    #[allow(clippy::cast_sign_loss)]
+    pub fn init(state: &UIState, shared_state: &SharedState) -> Result<Self> {
+        let series = match state.series.selected() {
+            Some(series) => series,
+            None => return Err(anyhow!("must select a series to browse its franchise")),
+        };
+
+        let remote = state.remote.get_logged_in_async()?.clone();
+        let id = series.config().id as SeriesID;
+
+        let panel_state = arc_mutex(PanelState::Loading);
+        let walk_task = Self::spawn_walk_task(id, remote, &panel_state, shared_state).into();
+
+        Ok(Self {
+            title: format!("{} Franchise", series.nickname()),
+            state: panel_state,
+            shared_state: shared_state.clone(),
+            walk_task,
+        })
+    }
+
+    fn spawn_walk_task(
+        id: SeriesID,
+        remote: AsyncRemote,
+        panel_state: &ArcMutex<PanelState>,
+        state: &SharedState,
+    ) -> task::JoinHandle<()> {
+        let load = Self::get_franchise(id, remote, state.clone());
+        PanelState::spawn_load(panel_state, state, load, |franchise, _| franchise)
+    }
+
+    async fn get_franchise(
+        id: SeriesID,
+        remote: AsyncRemote,
+        state: SharedState,
+    ) -> Result<Franchise> {
+        let graph = remote.get_relation_graph(id).await?;
+
+        // The list entries are only used to tell which series have been watched, so they aren't needed offline
+        let entries = if remote.get().is_offline() {
+            Vec::new()
+        } else {
+            let ids = graph.series().iter().map(|info| info.id).collect();
+            remote.get_list_entries(ids).await?
+        };
+
+        let config = state.lock().config.clone();
+
+        // Scanning the series directory can take a while, so it shouldn't be done while the state is locked
+        let franchise =
+            task::spawn_blocking(move || Franchise::new(graph, entries, &config)).await?;
+
+        Ok(franchise)
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn add_selected(&self, state: &mut UIState) -> Result<()> {
+        let panel_state = self.state.lock();
+
+        let entry = match &*panel_state {
+            PanelState::Loaded(franchise) => match franchise.entries.selected() {
+                Some(entry) => entry,
+                None => return Ok(()),
+            },
+            PanelState::Loading | PanelState::Failed => return Ok(()),
+        };
+
+        let info = &entry.info;
+        let id = info.id as i32;
+
+        if let Some(existing) = state.series.iter().find(|series| series.config().id == id) {
+            return Err(anyhow!("{} has already been added", existing.nickname()));
+        }
+
+        let path = match &entry.path {
+            Some(path) => path.clone(),
+            None => {
+                return Err(anyhow!(
+                    "no series found on disk matching {}",
+                    info.title.preferred
+                ))
+            }
+        };
+
+        let existing = state
+            .series
+            .iter()
+            .find(|series| series.path().inner() == path.inner());
+
+        if let Some(existing) = existing {
+            return Err(anyhow!(
+                "{} already uses {}",
+                existing.nickname(),
+                path.inner().display()
+            ));
+        }
+
+        let nickname = series::generate_nickname(info.title.preferred.as_str())
+            .unwrap_or_else(|| info.title.preferred.clone());
+
+        let params = SeriesParams::new(nickname, path, EpisodeParser::Default);
+        let config = SeriesConfig::new(id, params, &state.db)?;
+
+        self.shared_state
+            .add_series_async(config, info.clone().into(), None);
+
+        Ok(())
+    }
+
+    fn draw_franchise<B>(franchise: &Franchise, state: &UIState, rect: Rect, frame: &mut Frame<B>)
+    where
+        B: Backend,
+    {
+        let hints_rect = rect.lines_from_bottom(1);
+
+        let hints = SimpleLayout::new(Direction::Horizontal).split_evenly(hints_rect);
+
+        let add_hint = SimpleText::new(text::hint("A - Add series")).alignment(Alignment::Center);
+        frame.render_widget(add_hint, hints.left);
+
+        let close_hint = SimpleText::new(text::hint("Esc - Close")).alignment(Alignment::Center);
+        frame.render_widget(close_hint, hints.right);
+
+        // Leave room for the hints, along with a spacer between them and the table
+        let table_rect = Rect {
+            height: rect.height.saturating_sub(2),
+            ..rect
+        };
+
+        let marker = |marked: bool, color: Color| {
+            if marked {
+                Span::styled("Yes", style::fg(color))
+            } else {
+                Span::raw("")
+            }
+        };
+
+        let rows = franchise.entries.iter().map(|entry| {
+            let status = entry.status(state);
+            let kind: &'static str = entry.info.kind.into();
+
+            [
+                text::italic(entry.relation),
+                Span::raw(kind),
+                Span::raw(entry.aired.as_str()),
+                marker(status.added, Color::Blue),
+                marker(status.on_disk, Color::Green),
+                marker(!status.watched, Color::Yellow),
+                Span::raw(entry.info.title.preferred.as_str()),
+            ]
+        });
+
+        let header = [
+            Span::raw("Relation"),
+            Span::raw("Type"),
+            Span::raw("Aired"),
+            Span::raw("Added"),
+            Span::raw("On Disk"),
+            Span::raw("Unwatched"),
+            Span::raw("Title"),
+        ];
+
+        let layout = [
+            BasicConstraint::Length(12),
+            BasicConstraint::Length(8),
+            BasicConstraint::Length(12),
+            BasicConstraint::Length(6),
+            BasicConstraint::Length(8),
+            BasicConstraint::Length(10),
+            BasicConstraint::Percentage(100),
+        ];
+
+        let table = SimpleTable::new(rows, &layout)
+            .header(&header)
+            .highlight_symbol(Span::styled(">", style::list_selector(true)))
+            .select(Some(franchise.entries.index() as u16));
+
+        frame.render_widget(table, table_rect);
+    }
+
+    pub fn draw<B: Backend>(&mut self, state: &UIState, rect: Rect, frame: &mut Frame<B>) {
+        let block = block::with_borders(self.title.as_str());
+        let block_area = block.inner(rect);
+
+        frame.render_widget(block, rect);
+
+        self.state.lock().draw(
+            "Failed to get the franchise",
+            block_area,
+            frame,
+            |franchise, rect, frame| Self::draw_franchise(franchise, state, rect, frame),
+        );
+    }
+}
+
+impl Component for FranchisePanel {
+    type State = UIState;
+    type KeyResult = Result<ShouldReset>;
+
+    fn process_key(&mut self, key: Key, state: &mut Self::State) -> Self::KeyResult {
+        match *key {
+            KeyCode::Esc => return Ok(ShouldReset::Yes),
+            KeyCode::Char('a') => self.add_selected(state)?,
+            KeyCode::Up => {
+                if let PanelState::Loaded(franchise) = &mut *self.state.lock() {
+                    franchise.entries.dec_selected();
+                }
+            }
+            KeyCode::Down => {
+                if let PanelState::Loaded(franchise) = &mut *self.state.lock() {
+                    franchise.entries.inc_selected();
+                }
+            }
+            _ => (),
+        }
+
+        Ok(ShouldReset::No)
+    }
+}
+
+type PanelState = LoadState<Franchise>;
+
+struct Franchise {
+    entries: WrappedSelection<Vec<FranchiseEntry>, FranchiseEntry>,
+}
+
+impl Franchise {
+    fn new(graph: RelationGraph, list_entries: Vec<SeriesEntry>, config: &Config) -> Self {
+        let root = graph.root();
+
+        let list_entries = list_entries
+            .into_iter()
+            .map(|entry| (entry.id, entry))
+            .collect::<HashMap<_, _>>();
+
+        // The franchise can still be browsed without a series directory, so failing to read it isn't an error
+        let dirs = file::subdirectories(&config.series_dir).unwrap_or_default();
+        let infos = graph.series().iter().collect::<Vec<_>>();
+        let paths = SeriesPath::closest_matching_each(&infos, &dirs, config);
+
+        let mut entries = graph
+            .series()
+            .iter()
+            .zip(paths)
+            .map(|(info, path)| {
+                let relation: &'static str = match graph.reached_by(info.id) {
+                    Some((_, kind)) => kind.into(),
+                    None => "Selected",
+                };
+
+                FranchiseEntry::new(info.clone(), relation, list_entries.get(&info.id), path)
+            })
+            .collect::<Vec<_>>();
+
+        // Series without a known start date are most likely announcements, so they go last
+        entries.sort_by_key(|entry| {
+            (
+                entry.info.year.is_none(),
+                entry.info.year,
+                entry.info.season,
+            )
+        });
+
+        let selected = entries
+            .iter()
+            .position(|entry| entry.info.id == root)
+            .unwrap_or(0);
+
+        let mut entries = WrappedSelection::new(entries);
+        entries.set_selected(selected);
+
+        Self { entries }
+    }
+}
+
+struct FranchiseEntry {
+    info: SeriesInfo,
+    relation: &'static str,
+    aired: String,
+    /// The directory on disk that most closely matches the series' title, if there is one.
+    path: Option<SeriesPath>,
+    /// Indicates whether or not the series has been watched according to the user's remote list.
+    remote_watched: bool,
+}
+
+impl FranchiseEntry {
+    fn new(
+        info: SeriesInfo,
+        relation: &'static str,
+        list_entry: Option<&SeriesEntry>,
+        path: Option<SeriesPath>,
+    ) -> Self {
+        let aired = match (info.season, info.year) {
+            (Some(season), Some(year)) => {
+                let season: &'static str = season.into();
+                format!("{} {}", season, year)
+            }
+            (None, Some(year)) => year.to_string(),
+            (_, None) => "?".into(),
+        };
+
+        let remote_watched = list_entry.map_or(false, |entry| {
+            entry.watched_eps > 0 || entry.status == Status::Completed
+        });
+
+        Self {
+            info,
+            relation,
+            aired,
+            path,
+            remote_watched,
+        }
+    }
+
+    /// Returns the current status of the series in the program.
+    ///
+    /// Series that have already been added use their own state, since it may have changed after the franchise was retrieved.
+    #[allow(clippy::cast_possible_wrap)]
+    fn status(&self, state: &UIState) -> EntryStatus {
+        let id = self.info.id as i32;
+        let added = state.series.iter().find(|series| series.config().id == id);
+
+        match added {
+            Some(series) => EntryStatus {
+                added: true,
+                on_disk: matches!(series, LoadedSeries::Complete(_)),
+                watched: series.entry().map_or(false, |entry| {
+                    entry.watched_episodes() > 0 || entry.status() == Status::Completed
+                }),
+            },
+            None => EntryStatus {
+                added: false,
+                on_disk: self.path.is_some(),
+                watched: self.remote_watched,
+            },
+        }
+    }
+}
+
+struct EntryStatus {
+    added: bool,
+    on_disk: bool,
+    watched: bool,
+}
@@ -0,0 +1,438 @@
+use super::ShouldReset;
+use crate::file;
+use crate::series::config::SeriesConfig;
+use crate::series::entry::SeriesEntry;
+use crate::series::info::SeriesInfo;
+use crate::series::unmatched::UnmatchedImport;
+use crate::series::{self, SeriesData, SeriesParams, SeriesPath};
+use crate::tui::component::input::{
+    DrawInput, Input, InputFlags, ParsedValue, PathInput, ValidatedInput,
+};
+use crate::tui::component::Component;
+use crate::tui::state::{SharedState, UIState};
+use crate::util::{arc_mutex, ArcMutex, ScopedTask};
+use crate::{key::Key, tui::component::prompt::log::LogKind};
+use anime::local::EpisodeParser;
+use anime::remote::UserListEntry;
+use anyhow::{anyhow, Result};
+use crossterm::event::KeyCode;
+use std::sync::Arc;
+use tokio::task;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Direction, Rect};
+use tui::style::Color;
+use tui::terminal::Frame;
+use tui::text::Span;
+use tui_utils::{
+    helpers::{block, text},
+    layout::{RectExt, SimpleLayout},
+    list::WrappedSelection,
+    widgets::{OverflowMode, SimpleList, SimpleText},
+};
+
+/// A panel that imports every series on the user's remote list that can be matched to a directory on disk,
+/// and lists the ones that couldn't be so they can be assigned one manually.
+pub struct ImportListPanel {
+    state: ArcMutex<PanelState>,
+    #[allow(dead_code)]
+    import_task: ScopedTask<()>,
+}
+
+impl ImportListPanel {
+    pub fn new(state: &SharedState) -> Self {
+        let panel_state = arc_mutex(PanelState::Loading);
+        let import_task = Self::spawn_import_task(&panel_state, state).into();
+
+        Self {
+            state: panel_state,
+            import_task,
+        }
+    }
+
+    fn spawn_import_task(
+        panel_state: &ArcMutex<PanelState>,
+        state: &SharedState,
+    ) -> task::JoinHandle<()> {
+        let panel_state = Arc::clone(panel_state);
+        let state = state.clone();
+
+        task::spawn(async move {
+            let matched = Self::match_list(&state).await;
+            let mut state = state.lock();
+            let state = state.get_mut();
+
+            let matched = match matched {
+                Ok(matched) => matched,
+                Err(err) => {
+                    state.log.push_error(&err);
+                    return;
+                }
+            };
+
+            let imported = ImportedList::import(matched, state);
+
+            state.log.push(
+                LogKind::Info,
+                format!(
+                    "imported {} series from remote, {} need manual review",
+                    imported.num_imported,
+                    imported.unmatched.len()
+                ),
+            );
+
+            *panel_state.lock() = PanelState::Imported(imported);
+        })
+    }
+
+    /// Get the user's remote list and find the directory of each series on it that isn't in the library yet.
+    ///
+    /// The shared state is only locked briefly, as scanning the series directory can take a while.
+    #[allow(clippy::cast_possible_wrap)]
+    async fn match_list(state: &SharedState) -> Result<Vec<MatchedSeries>> {
+        let (remote, config, library_ids, library_paths) = {
+            let state = state.lock();
+            let remote = state.remote.get_logged_in_async()?.clone();
+
+            let ids = state
+                .series
+                .iter()
+                .map(|series| series.config().id)
+                .collect::<Vec<_>>();
+
+            let paths = state
+                .series
+                .iter()
+                .map(|series| series.path().inner().clone())
+                .collect::<Vec<_>>();
+
+            (remote, state.config.clone(), ids, paths)
+        };
+
+        let list = remote.get_user_list().await?;
+
+        task::spawn_blocking(move || -> Result<Vec<MatchedSeries>> {
+            // Series that have already been added should keep their current settings
+            let list = list
+                .into_iter()
+                .filter(|entry| !library_ids.contains(&(entry.info.id as i32)))
+                .collect::<Vec<_>>();
+
+            let dirs = file::subdirectories(&config.series_dir)?
+                .into_iter()
+                .filter(|dir| {
+                    let path = SeriesPath::new(dir.path(), &config);
+                    !library_paths.contains(path.inner())
+                })
+                .collect::<Vec<_>>();
+
+            let infos = list.iter().map(|entry| &entry.info).collect::<Vec<_>>();
+            let paths = SeriesPath::closest_matching_each(&infos, &dirs, &config);
+
+            let matched = list
+                .into_iter()
+                .zip(paths)
+                .map(|(list_entry, path)| MatchedSeries { list_entry, path })
+                .collect();
+
+            Ok(matched)
+        })
+        .await?
+    }
+
+    fn draw_loading_panel<B>(rect: Rect, frame: &mut Frame<B>)
+    where
+        B: Backend,
+    {
+        let layout = SimpleLayout::new(Direction::Vertical).split_evenly(rect);
+
+        let text = text::bold("Importing..");
+        let widget = SimpleText::new(text).alignment(Alignment::Center);
+
+        frame.render_widget(widget, layout.right);
+    }
+
+    fn draw_imported_panel<B>(imported: &ImportedList, rect: Rect, frame: &mut Frame<B>)
+    where
+        B: Backend,
+    {
+        let header_text = if imported.unmatched.is_empty() {
+            format!("Imported {} series", imported.num_imported)
+        } else {
+            format!(
+                "Imported {} series. No directory could be used for the following:",
+                imported.num_imported
+            )
+        };
+
+        let header = SimpleText::new(text::bold(header_text))
+            .alignment(Alignment::Center)
+            .overflow(OverflowMode::Truncate);
+
+        frame.render_widget(header, rect.lines_from_top(1));
+
+        let hint_text = match &imported.path_input {
+            Some(_) => "Enter - Add Series | Esc - Cancel",
+            None if imported.unmatched.is_empty() => "Esc - Close",
+            None => "Enter - Assign Directory | Esc - Close",
+        };
+
+        let hint = SimpleText::new(text::hint(hint_text)).alignment(Alignment::Center);
+        frame.render_widget(hint, rect.lines_from_bottom(1));
+
+        let input_height = if imported.path_input.is_some() {
+            Input::DRAW_LINES_REQUIRED + 1
+        } else {
+            0
+        };
+
+        // Leave room for the header and hint, along with a spacer between each of them and the list
+        let list_rect = Rect {
+            y: rect.y + 2,
+            height: rect.height.saturating_sub(4 + input_height),
+            ..rect
+        };
+
+        let items = imported
+            .unmatched
+            .iter()
+            .map(|series| Span::raw(series.description.as_str()));
+
+        let list = SimpleList::new(items)
+            .highlight_symbol(text::italic_with(">", |s| s.fg(Color::Green)))
+            .select(Some(imported.unmatched.index() as u16));
+
+        frame.render_widget(list, list_rect);
+
+        if let Some(input) = &imported.path_input {
+            let input_rect = Rect {
+                y: list_rect.y + list_rect.height + 1,
+                height: Input::DRAW_LINES_REQUIRED,
+                ..rect
+            }
+            .pad_horiz(2);
+
+            input.draw(input_rect, frame);
+        }
+    }
+
+    pub fn draw<B: Backend>(&mut self, rect: Rect, frame: &mut Frame<B>) {
+        let block = block::with_borders("Import List");
+        let block_area = block.inner(rect);
+
+        frame.render_widget(block, rect);
+
+        let state = self.state.lock();
+
+        match &*state {
+            PanelState::Loading => Self::draw_loading_panel(block_area, frame),
+            PanelState::Imported(imported) => {
+                Self::draw_imported_panel(imported, block_area, frame)
+            }
+        }
+    }
+}
+
+impl Component for ImportListPanel {
+    type State = UIState;
+    type KeyResult = ShouldReset;
+
+    fn process_key(&mut self, key: Key, state: &mut Self::State) -> Self::KeyResult {
+        let mut panel_state = self.state.lock();
+
+        let imported = match &mut *panel_state {
+            PanelState::Imported(imported) => imported,
+            PanelState::Loading if *key == KeyCode::Esc => return ShouldReset::Yes,
+            PanelState::Loading => return ShouldReset::No,
+        };
+
+        if imported.path_input.is_some() {
+            imported.process_input_key(key, state);
+            return ShouldReset::No;
+        }
+
+        match *key {
+            KeyCode::Esc => return ShouldReset::Yes,
+            KeyCode::Up => imported.unmatched.dec_selected(),
+            KeyCode::Down => imported.unmatched.inc_selected(),
+            KeyCode::Enter if imported.unmatched.is_valid_index() => {
+                let input = PathInput::new(InputFlags::SELECTED, &state.config);
+                imported.path_input = Some(input);
+            }
+            _ => (),
+        }
+
+        ShouldReset::No
+    }
+}
+
+enum PanelState {
+    Loading,
+    Imported(ImportedList),
+}
+
+struct MatchedSeries {
+    list_entry: UserListEntry,
+    path: Option<SeriesPath>,
+}
+
+/// A series from the remote list that couldn't be added to the library automatically.
+///
+/// Its list entry is staged in the database, so it only needs to be given a directory to be added.
+struct UnmatchedSeries {
+    id: i32,
+    description: String,
+}
+
+struct ImportedList {
+    num_imported: usize,
+    unmatched: WrappedSelection<Vec<UnmatchedSeries>, UnmatchedSeries>,
+    /// The input for the directory of the selected unmatched series, when one is being assigned.
+    path_input: Option<PathInput>,
+}
+
+impl ImportedList {
+    fn import(matched: Vec<MatchedSeries>, state: &mut UIState) -> Self {
+        let mut num_imported = 0;
+        let mut unmatched = Vec::new();
+
+        // Series left over from a previous import are either in the library by now or will be staged again
+        if let Err(err) = UnmatchedImport::clear(&state.db) {
+            state.log.push_error(&err.into());
+        }
+
+        for MatchedSeries { list_entry, path } in matched {
+            let info = SeriesInfo::from(list_entry.info.clone());
+
+            // The series may have been added while the directories were being matched
+            if state
+                .series
+                .iter()
+                .any(|series| series.config().id == info.id)
+            {
+                continue;
+            }
+
+            let result = match path {
+                Some(path) => {
+                    let entry = SeriesEntry::from(list_entry.entry.clone());
+                    Self::add_series(info.clone(), entry, path, state)
+                }
+                None => Err(anyhow!("no series found on disk")),
+            };
+
+            let err = match result {
+                Ok(()) => {
+                    num_imported += 1;
+                    continue;
+                }
+                Err(err) => err,
+            };
+
+            let description = format!("{} [ID {}]: {}", info.title_preferred, info.id, err);
+
+            let staged = UnmatchedImport::new(&list_entry)
+                .and_then(|import| import.save(&state.db).map_err(Into::into));
+
+            if let Err(err) = staged {
+                state.log.push_error(&err);
+            }
+
+            unmatched.push(UnmatchedSeries {
+                id: info.id,
+                description,
+            });
+        }
+
+        Self {
+            num_imported,
+            unmatched: WrappedSelection::new(unmatched),
+            path_input: None,
+        }
+    }
+
+    fn process_input_key(&mut self, key: Key, state: &mut UIState) {
+        let input = match &mut self.path_input {
+            Some(input) => input,
+            None => return,
+        };
+
+        match *key {
+            KeyCode::Esc => self.path_input = None,
+            KeyCode::Enter => {
+                input.validate();
+
+                let path = match input.parsed_value() {
+                    Some(path) => path.clone(),
+                    None => return,
+                };
+
+                match self.assign_selected(path, state) {
+                    Ok(()) => self.path_input = None,
+                    Err(err) => state.log.push_error(&err),
+                }
+            }
+            _ => {
+                input.input_mut().process_key(key);
+                input.validate();
+            }
+        }
+    }
+
+    /// Add the selected unmatched series to the library with the directory at `path`.
+    fn assign_selected(&mut self, path: SeriesPath, state: &mut UIState) -> Result<()> {
+        let id = match self.unmatched.selected() {
+            Some(series) => series.id,
+            None => return Ok(()),
+        };
+
+        let import = UnmatchedImport::load(&state.db, id)?;
+        let list_entry = import.list_entry()?;
+
+        let info = SeriesInfo::from(list_entry.info);
+        let entry = SeriesEntry::from(list_entry.entry);
+
+        Self::add_series(info, entry, path, state)?;
+        import.remove(&state.db)?;
+
+        let index = self.unmatched.index();
+        self.unmatched.items_mut().remove(index);
+        self.unmatched.update_bounds();
+
+        self.num_imported += 1;
+        Ok(())
+    }
+
+    fn add_series(
+        info: SeriesInfo,
+        entry: SeriesEntry,
+        path: SeriesPath,
+        state: &mut UIState,
+    ) -> Result<()> {
+        let existing = state
+            .series
+            .iter()
+            .find(|series| series.path().inner() == path.inner());
+
+        if let Some(existing) = existing {
+            return Err(anyhow!(
+                "{} already uses {}",
+                existing.nickname(),
+                path.inner().display()
+            ));
+        }
+
+        let nickname = series::generate_nickname(info.title_preferred.as_str())
+            .unwrap_or_else(|| info.title_preferred.clone());
+
+        let params = SeriesParams::new(nickname, path, EpisodeParser::Default);
+        let config = SeriesConfig::new(info.id, params, &state.db)?;
+
+        let data = SeriesData {
+            config,
+            info,
+            entry,
+        };
+
+        state.add_series(data, None)
+    }
+}
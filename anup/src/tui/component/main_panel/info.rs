@@ -265,7 +265,7 @@ impl InfoPanel {
 
         // Series title
         {
-            let mut fragments: SmallVec<[Fragment; 2]> = smallvec![Fragment::Span(
+            let mut fragments: SmallVec<[Fragment; 8]> = smallvec![Fragment::Span(
                 text::bold(&info.title_preferred),
                 SpanOptions::new().overflow(OverflowMode::Truncate)
             )];
@@ -274,6 +274,40 @@ impl InfoPanel {
                 fragments.push(Fragment::span(text::italic(" [*]")));
             }
 
+            if entry.private() {
+                fragments.push(Fragment::span(text::hint(" [Private]")));
+            }
+
+            if entry.hidden() {
+                fragments.push(Fragment::span(text::hint(" [Hidden]")));
+            }
+
+            let details = info.details();
+
+            if !details.is_empty() {
+                fragments.push(Fragment::Line);
+                fragments.push(Fragment::Span(
+                    text::italic(details),
+                    SpanOptions::new().overflow(OverflowMode::Truncate),
+                ));
+            }
+
+            if !info.genres.is_empty() {
+                fragments.push(Fragment::Line);
+                fragments.push(Fragment::Span(
+                    text::hint(info.genres.join(", ")),
+                    SpanOptions::new().overflow(OverflowMode::Truncate),
+                ));
+            }
+
+            if info.is_airing() {
+                fragments.push(Fragment::Line);
+                fragments.push(Fragment::Span(
+                    text::italic(Self::airing_text(series)),
+                    SpanOptions::new().overflow(OverflowMode::Truncate),
+                ));
+            }
+
             let title_widget = TextFragments::new(&fragments).alignment(Alignment::Center);
             frame.render_widget(title_widget, layout[0]);
         }
@@ -298,25 +332,35 @@ impl InfoPanel {
         // Left panel items
 
         draw_stat!(0, 0 => "Watch Time", {
-            let watch_time_mins = info.episodes * info.episode_length_mins;
-            util::hm_from_mins(f32::from(watch_time_mins))
+            info.episode_count().map_or_else(|| "??".into(), |episodes| {
+                let watch_time_mins = episodes * info.episode_length_mins;
+                util::hm_from_mins(f32::from(watch_time_mins))
+            })
         });
 
         draw_stat!(0, 1 => "Time Left", {
-            let eps_left = info.episodes - entry.watched_episodes().min(info.episodes);
-            let time_left_mins = eps_left * info.episode_length_mins;
-            util::hm_from_mins(f32::from(time_left_mins))
+            info.episode_count().map_or_else(|| "??".into(), |episodes| {
+                let eps_left = episodes - entry.watched_episodes().min(episodes);
+                let time_left_mins = eps_left * info.episode_length_mins;
+                util::hm_from_mins(f32::from(time_left_mins))
+            })
         });
 
         draw_stat!(0, 2 => "Episode Length", format!("{}M", info.episode_length_mins));
 
         // Middle panel items
 
-        draw_stat!(1, 0 => "Progress", format!("{}|{}", entry.watched_episodes(), info.episodes));
+        draw_stat!(1, 0 => "Progress", {
+            let episodes = info
+                .episode_count()
+                .map_or_else(|| Cow::Borrowed("??"), |episodes| episodes.to_string().into());
+
+            format!("{}|{}", entry.watched_episodes(), episodes)
+        });
 
         draw_stat!(1, 1 => "Score", {
             match (entry.score(), &state.remote) {
-                (Some(score), RemoteStatus::LoggedIn(remote)) => remote.score_to_str(score as u8),
+                (Some(score), RemoteStatus::LoggedIn(remote)) => remote.get().score_to_str(score as u8),
                 (Some(score), RemoteStatus::LoggingIn(_)) => score.to_string().into(),
                 (None, _) => "??".into(),
             }
@@ -341,7 +385,34 @@ impl InfoPanel {
         draw_stat!(2, 1 => "Finish Date", format_date(entry.end_date()));
         draw_stat!(2, 2 => "Rewatched", entry.times_rewatched().to_string());
 
-        self.draw_status_text(state, layout[2], frame);
+        self.draw_status_text(state, series, layout[2], frame);
+    }
+
+    /// Returns the number of episodes of `series` that have aired, are on disk, and have been watched,
+    /// along with when the next episode airs.
+    fn airing_text(series: &Series) -> String {
+        let info = &series.data.info;
+
+        let aired = info
+            .aired_episodes()
+            .map_or_else(|| Cow::Borrowed("??"), |aired| aired.to_string().into());
+
+        let mut parts = vec![
+            format!("{} Aired", aired),
+            format!("{} On Disk", series.episodes.len()),
+            format!("{} Watched", series.data.entry.watched_episodes()),
+        ];
+
+        if let (Some(next), Some(airs_at)) = (info.next_episode, info.next_episode_airs_at) {
+            let secs_left = airs_at - Utc::now().timestamp();
+
+            if secs_left > 0 {
+                let time_left = util::dh_from_secs(secs_left);
+                parts.push(format!("Episode {} In {}", next, time_left));
+            }
+        }
+
+        parts.join(" | ")
     }
 
     fn draw_stat<B, S>(header: &str, value: S, rect: Rect, frame: &mut Frame<B>)
@@ -359,7 +430,13 @@ impl InfoPanel {
         frame.render_widget(widget, rect);
     }
 
-    fn draw_status_text<B: Backend>(&self, state: &UIState, rect: Rect, frame: &mut Frame<B>) {
+    fn draw_status_text<B: Backend>(
+        &self,
+        state: &UIState,
+        series: &Series,
+        rect: Rect,
+        frame: &mut Frame<B>,
+    ) {
         let progress_remaining_secs = self.progress_remaining_secs.load(Ordering::SeqCst);
 
         // Remaining time until progression
@@ -387,6 +464,30 @@ impl InfoPanel {
             let widget = TextFragments::new(&fragments).alignment(Alignment::Center);
             frame.render_widget(widget, rect);
         }
+        // List entry notes and custom lists
+        else {
+            let entry = &series.data.entry;
+            let mut fragments: SmallVec<[Fragment; 3]> = SmallVec::new();
+
+            if !entry.custom_lists().is_empty() {
+                fragments.push(Fragment::Span(
+                    text::hint(format!("Lists: {}", entry.custom_lists().join(", "))),
+                    SpanOptions::new().overflow(OverflowMode::Truncate),
+                ));
+            }
+
+            if let Some(notes) = entry.notes() {
+                if !fragments.is_empty() {
+                    fragments.push(Fragment::Line);
+                }
+
+                fragments.push(Fragment::span(text::italic(notes)));
+            }
+
+            let wrapped = wrap::by_letters(fragments.into_iter(), rect.width);
+            let widget = TextFragments::new(&wrapped).alignment(Alignment::Center);
+            frame.render_widget(widget, rect);
+        }
     }
 
     pub fn draw<B: Backend>(&mut self, state: &UIState, rect: Rect, frame: &mut Frame<B>) {
@@ -0,0 +1,79 @@
+use crate::tui::state::{SharedState, UIState};
+use crate::util::ArcMutex;
+use anyhow::Result;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::task;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Direction, Rect};
+use tui::terminal::Frame;
+use tui_utils::{helpers::text, layout::SimpleLayout, widgets::SimpleText};
+
+/// The contents of a panel that are retrieved in the background.
+pub enum LoadState<T> {
+    Loading,
+    Loaded(T),
+    Failed,
+}
+
+impl<T> LoadState<T>
+where
+    T: Send + 'static,
+{
+    /// Run `load` in the background and store the contents `build` creates from its result in `panel_state`.
+    ///
+    /// If `load` fails, its error is logged and the panel is marked as failed.
+    pub fn spawn_load<F, R, B>(
+        panel_state: &ArcMutex<Self>,
+        state: &SharedState,
+        load: F,
+        build: B,
+    ) -> task::JoinHandle<()>
+    where
+        F: Future<Output = Result<R>> + Send + 'static,
+        R: Send + 'static,
+        B: FnOnce(R, &UIState) -> T + Send + 'static,
+    {
+        let panel_state = Arc::clone(panel_state);
+        let state = state.clone();
+
+        task::spawn(async move {
+            let result = load.await;
+            let mut state = state.lock();
+
+            match result {
+                Ok(value) => *panel_state.lock() = Self::Loaded(build(value, state.get())),
+                Err(err) => {
+                    state.get_mut().log.push_error(&err);
+                    *panel_state.lock() = Self::Failed;
+                }
+            }
+
+            state.mark_dirty();
+        })
+    }
+
+    /// Draw the contents with `draw_loaded` once they've been retrieved, or the status of the panel until then.
+    pub fn draw<B, D>(&self, failed_status: &str, rect: Rect, frame: &mut Frame<B>, draw_loaded: D)
+    where
+        B: Backend,
+        D: FnOnce(&T, Rect, &mut Frame<B>),
+    {
+        match self {
+            Self::Loading => draw_status("Loading..", rect, frame),
+            Self::Failed => draw_status(failed_status, rect, frame),
+            Self::Loaded(loaded) => draw_loaded(loaded, rect, frame),
+        }
+    }
+}
+
+/// Draw `status` in the middle of `rect`.
+pub fn draw_status<B>(status: &str, rect: Rect, frame: &mut Frame<B>)
+where
+    B: Backend,
+{
+    let layout = SimpleLayout::new(Direction::Vertical).split_evenly(rect);
+    let widget = SimpleText::new(text::bold(status)).alignment(Alignment::Center);
+
+    frame.render_widget(widget, layout.right);
+}
@@ -1,25 +1,42 @@
 mod add_series;
+mod calendar;
 mod delete_series;
+mod franchise;
+mod import_list;
 mod info;
+mod load_state;
+mod recommendations;
+mod resolve_conflict;
+mod season;
 mod select_series;
 mod split_series;
 mod user_panel;
 
 use super::Component;
-use crate::series::info::InfoResult;
+use crate::series::entry::EntryConflict;
+use crate::series::info::{InfoResult, InfoSelector, SearchResults, SeriesInfo};
 use crate::try_opt_r;
 use crate::tui::state::{InputState, UIState};
+use crate::util::{arc_mutex, ArcMutex, ScopedTask};
 use crate::{key::Key, series::config::SeriesConfig};
 use crate::{series::SeriesParams, tui::state::SharedState};
 use add_series::{AddSeriesPanel, AddSeriesResult};
 use anime::local::SortedEpisodes;
 use anime::remote::RemoteService;
 use anyhow::{anyhow, Result};
+use calendar::CalendarPanel;
 use delete_series::DeleteSeriesPanel;
+use franchise::FranchisePanel;
+use import_list::ImportListPanel;
 use info::InfoPanel;
-use select_series::{SelectSeriesPanel, SelectSeriesResult, SelectState};
+use recommendations::RecommendationsPanel;
+use resolve_conflict::ResolveConflictPanel;
+use season::SeasonPanel;
+use select_series::{SelectSeriesPanel, SelectSeriesResult};
 use split_series::{SplitPanelResult, SplitSeriesPanel};
 use std::mem;
+use std::sync::Arc;
+use tokio::task;
 use tui::backend::Backend;
 use tui::layout::Rect;
 use tui::terminal::Frame;
@@ -28,6 +45,9 @@ use user_panel::UserPanel;
 pub struct MainPanel {
     current: Panel,
     state: SharedState,
+    found_series: ArcMutex<Option<PartialSeries>>,
+    #[allow(dead_code)]
+    search_task: Option<ScopedTask<()>>,
 }
 
 impl MainPanel {
@@ -35,20 +55,51 @@ impl MainPanel {
         Self {
             current: Panel::info(&state),
             state,
+            found_series: arc_mutex(None),
+            search_task: None,
         }
     }
 
+    /// Handle the results of any background work started by the current panel.
+    pub fn process_state_change(&mut self, state: &mut UIState) -> Result<()> {
+        let found = self.found_series.lock().take();
+
+        match found {
+            Some(series) => self.add_partial_series(series, state),
+            None => Ok(()),
+        }
+    }
+
+    fn search_series(&mut self, search: SeriesSearch, state: &UIState) -> Result<()> {
+        let remote = state.remote.get_logged_in_async()?.clone();
+        let found_series = Arc::clone(&self.found_series);
+        let shared_state = self.state.clone();
+
+        let task = task::spawn(async move {
+            let result =
+                SeriesInfo::from_remote_async(search.selector, search.episodes.as_ref(), &remote)
+                    .await;
+            let mut state = shared_state.lock();
+
+            match result {
+                Ok(info) => {
+                    let partial = PartialSeries::new(info, search.params, search.episodes);
+                    *found_series.lock() = Some(partial);
+                    state.mark_dirty();
+                }
+                Err(err) => state.get_mut().log.push_error(&err),
+            }
+        });
+
+        self.search_task = Some(task.into());
+        Ok(())
+    }
+
     fn default_panel(&self) -> Panel {
         Panel::info(&self.state)
     }
 
     pub fn switch_to_add_series(&mut self, state: &mut UIState) -> Result<()> {
-        let remote = state.remote.get_logged_in()?;
-
-        if remote.is_offline() {
-            return Err(anyhow!("must be online to add a series"));
-        }
-
         self.current = Panel::add_series(state, &self.state)?;
         state.input_state = InputState::FocusedOnMainPanel;
 
@@ -62,13 +113,18 @@ impl MainPanel {
     }
 
     pub fn switch_to_delete_series(&mut self, state: &mut UIState) -> Result<()> {
-        self.current = Panel::delete_series(state)?;
+        self.current = Panel::delete_series(state, &self.state)?;
         state.input_state = InputState::FocusedOnMainPanel;
         Ok(())
     }
 
-    fn switch_to_select_series(&mut self, select: SelectState, state: &mut UIState) {
-        self.current = Panel::select_series(select);
+    fn switch_to_select_series(
+        &mut self,
+        results: SearchResults,
+        params: SeriesParams,
+        state: &mut UIState,
+    ) {
+        self.current = Panel::select_series(results, params, &self.state);
         state.input_state = InputState::FocusedOnMainPanel;
     }
 
@@ -77,33 +133,74 @@ impl MainPanel {
         state.input_state = InputState::FocusedOnMainPanel;
     }
 
-    pub fn switch_to_split_series(&mut self, state: &mut UIState) -> Result<()> {
+    pub fn switch_to_import_list(&mut self, state: &mut UIState) -> Result<()> {
         let remote = state.remote.get_logged_in()?;
 
         if remote.is_offline() {
-            return Err(anyhow!("must be online to split a series"));
+            return Err(anyhow!("must be online to import a list"));
         }
 
-        let panel = Panel::split_series(&self.state);
+        self.current = Panel::import_list(&self.state);
+        state.input_state = InputState::FocusedOnMainPanel;
+        Ok(())
+    }
+
+    pub fn switch_to_calendar(&mut self, state: &mut UIState) -> Result<()> {
+        state.remote.get_logged_in()?;
+
+        self.current = Panel::calendar(&self.state);
+        state.input_state = InputState::FocusedOnMainPanel;
+        Ok(())
+    }
+
+    pub fn switch_to_franchise(&mut self, state: &mut UIState) -> Result<()> {
+        self.current = Panel::franchise(state, &self.state)?;
+        state.input_state = InputState::FocusedOnMainPanel;
+        Ok(())
+    }
+
+    pub fn switch_to_recommendations(&mut self, state: &mut UIState) -> Result<()> {
+        self.current = Panel::recommendations(state, &self.state)?;
+        state.input_state = InputState::FocusedOnMainPanel;
+        Ok(())
+    }
 
-        self.current = panel;
+    pub fn switch_to_season(&mut self, state: &mut UIState) -> Result<()> {
+        self.current = Panel::season(state, &self.state)?;
         state.input_state = InputState::FocusedOnMainPanel;
         Ok(())
     }
 
+    /// Switch to the conflict resolution panel if there are list entry conflicts waiting to be resolved and the
+    /// user isn't doing anything else.
+    pub fn switch_to_queued_conflict(&mut self, state: &mut UIState) {
+        if !matches!(state.input_state, InputState::Idle) || state.conflicts.is_empty() {
+            return;
+        }
+
+        let conflict = state.conflicts.remove(0);
+
+        self.current = Panel::resolve_conflict(conflict, state, &self.state);
+        state.input_state = InputState::FocusedOnMainPanel;
+    }
+
+    pub fn switch_to_split_series(&mut self, state: &mut UIState) {
+        self.current = Panel::split_series(&self.state);
+        state.input_state = InputState::FocusedOnMainPanel;
+    }
+
     fn add_partial_series(&mut self, series: PartialSeries, state: &mut UIState) -> Result<()> {
         match series.info {
             InfoResult::Confident(info) => {
                 self.reset(state);
 
                 let config = SeriesConfig::new(info.id, series.params, &state.db)?;
-                state.add_series(config, info, series.episodes)?;
+                self.state.add_series_async(config, info, series.episodes);
 
                 Ok(())
             }
-            InfoResult::Unconfident(info_list) => {
-                let select = SelectState::new(info_list, series.params);
-                self.switch_to_select_series(select, state);
+            InfoResult::Unconfident(results) => {
+                self.switch_to_select_series(results, series.params, state);
                 Ok(())
             }
         }
@@ -111,6 +208,7 @@ impl MainPanel {
 
     fn reset(&mut self, state: &mut UIState) {
         self.current = self.default_panel();
+        self.search_task = None;
         state.input_state.reset();
     }
 
@@ -122,6 +220,12 @@ impl MainPanel {
             Panel::DeleteSeries(panel) => panel.draw(rect, frame),
             Panel::User(user) => user.draw(state, rect, frame),
             Panel::SplitSeries(split) => split.draw(rect, frame),
+            Panel::ImportList(import) => import.draw(rect, frame),
+            Panel::Calendar(calendar) => calendar.draw(rect, frame),
+            Panel::ResolveConflict(panel) => panel.draw(rect, frame),
+            Panel::Franchise(panel) => panel.draw(state, rect, frame),
+            Panel::Recommendations(panel) => panel.draw(state, rect, frame),
+            Panel::Season(panel) => panel.draw(rect, frame),
         }
     }
 }
@@ -139,7 +243,7 @@ impl Component for MainPanel {
                     self.reset(state);
                     Ok(())
                 }
-                Ok(AddSeriesResult::AddSeries(partial)) => self.add_partial_series(*partial, state),
+                Ok(AddSeriesResult::AddSeries(search)) => self.search_series(*search, state),
                 Ok(AddSeriesResult::UpdateSeries(params)) => {
                     let selected = try_opt_r!(state.series.selected_mut());
                     let remote = state.remote.get_logged_in()?;
@@ -151,9 +255,9 @@ impl Component for MainPanel {
                 }
                 Err(err) => Err(err),
             },
-            Panel::SelectSeries(panel) => match panel.process_key(key, &mut ()) {
-                SelectSeriesResult::Ok => Ok(()),
-                SelectSeriesResult::AddSeries(info) => {
+            Panel::SelectSeries(panel) => match panel.process_key(key, state) {
+                Ok(SelectSeriesResult::Ok) => Ok(()),
+                Ok(SelectSeriesResult::AddSeries(info)) => {
                     let default_panel = self.default_panel();
 
                     let params = match mem::replace(&mut self.current, default_panel) {
@@ -161,15 +265,16 @@ impl Component for MainPanel {
                         _ => unreachable!(),
                     };
 
-                    let series = PartialSeries::new(InfoResult::Confident(info), params, None);
+                    let series = PartialSeries::new(InfoResult::Confident(*info), params, None);
                     self.add_partial_series(series, state)?;
 
                     Ok(())
                 }
-                SelectSeriesResult::Reset => {
+                Ok(SelectSeriesResult::Reset) => {
                     self.reset(state);
                     Ok(())
                 }
+                Err(err) => Err(err),
             },
             Panel::DeleteSeries(panel) => match panel.process_key(key, state) {
                 Ok(ShouldReset::Yes) => {
@@ -194,10 +299,57 @@ impl Component for MainPanel {
                     Ok(())
                 }
                 Ok(SplitPanelResult::AddSeries(info, cfg)) => {
-                    state.add_series(*cfg, (*info).into(), None)
+                    self.state.add_series_async(*cfg, (*info).into(), None);
+                    Ok(())
                 }
                 Err(err) => Err(err),
             },
+            Panel::ImportList(import) => {
+                if let ShouldReset::Yes = import.process_key(key, state) {
+                    self.reset(state);
+                }
+
+                Ok(())
+            }
+            Panel::Calendar(calendar) => {
+                if let ShouldReset::Yes = calendar.process_key(key, state) {
+                    self.reset(state);
+                }
+
+                Ok(())
+            }
+            Panel::ResolveConflict(panel) => match panel.process_key(key, state) {
+                Ok(ShouldReset::Yes) => {
+                    self.reset(state);
+                    Ok(())
+                }
+                Ok(ShouldReset::No) => Ok(()),
+                Err(err) => Err(err),
+            },
+            Panel::Franchise(panel) => match panel.process_key(key, state) {
+                Ok(ShouldReset::Yes) => {
+                    self.reset(state);
+                    Ok(())
+                }
+                Ok(ShouldReset::No) => Ok(()),
+                Err(err) => Err(err),
+            },
+            Panel::Recommendations(panel) => match panel.process_key(key, state) {
+                Ok(ShouldReset::Yes) => {
+                    self.reset(state);
+                    Ok(())
+                }
+                Ok(ShouldReset::No) => Ok(()),
+                Err(err) => Err(err),
+            },
+            Panel::Season(panel) => match panel.process_key(key, state) {
+                Ok(ShouldReset::Yes) => {
+                    self.reset(state);
+                    Ok(())
+                }
+                Ok(ShouldReset::No) => Ok(()),
+                Err(err) => Err(err),
+            },
         }
     }
 }
@@ -207,8 +359,14 @@ enum Panel {
     AddSeries(Box<AddSeriesPanel>),
     SelectSeries(SelectSeriesPanel),
     DeleteSeries(DeleteSeriesPanel),
-    User(UserPanel),
+    User(Box<UserPanel>),
     SplitSeries(SplitSeriesPanel),
+    ImportList(ImportListPanel),
+    Calendar(CalendarPanel),
+    ResolveConflict(Box<ResolveConflictPanel>),
+    Franchise(Box<FranchisePanel>),
+    Recommendations(Box<RecommendationsPanel>),
+    Season(Box<SeasonPanel>),
 }
 
 impl Panel {
@@ -229,23 +387,55 @@ impl Panel {
         Ok(Self::AddSeries(panel.into()))
     }
 
-    fn delete_series(state: &UIState) -> Result<Self> {
-        let panel = DeleteSeriesPanel::init(state)?;
+    fn delete_series(state: &UIState, shared_state: &SharedState) -> Result<Self> {
+        let panel = DeleteSeriesPanel::init(state, shared_state)?;
         Ok(Self::DeleteSeries(panel))
     }
 
-    fn select_series(select: SelectState) -> Self {
-        Self::SelectSeries(SelectSeriesPanel::new(select))
+    fn select_series(results: SearchResults, params: SeriesParams, state: &SharedState) -> Self {
+        Self::SelectSeries(SelectSeriesPanel::new(results, params, state))
     }
 
     fn user(state: SharedState) -> Self {
-        Self::User(UserPanel::new(state))
+        Self::User(UserPanel::new(state).into())
     }
 
     fn split_series(state: &SharedState) -> Self {
         let panel = SplitSeriesPanel::new(state);
         Self::SplitSeries(panel)
     }
+
+    fn import_list(state: &SharedState) -> Self {
+        Self::ImportList(ImportListPanel::new(state))
+    }
+
+    fn calendar(state: &SharedState) -> Self {
+        Self::Calendar(CalendarPanel::new(state))
+    }
+
+    fn resolve_conflict(
+        conflict: EntryConflict,
+        state: &UIState,
+        shared_state: &SharedState,
+    ) -> Self {
+        let panel = ResolveConflictPanel::new(conflict, state, shared_state);
+        Self::ResolveConflict(panel.into())
+    }
+
+    fn franchise(state: &UIState, shared_state: &SharedState) -> Result<Self> {
+        let panel = FranchisePanel::init(state, shared_state)?;
+        Ok(Self::Franchise(panel.into()))
+    }
+
+    fn recommendations(state: &UIState, shared_state: &SharedState) -> Result<Self> {
+        let panel = RecommendationsPanel::init(state, shared_state)?;
+        Ok(Self::Recommendations(panel.into()))
+    }
+
+    fn season(state: &UIState, shared_state: &SharedState) -> Result<Self> {
+        let panel = SeasonPanel::init(state, shared_state)?;
+        Ok(Self::Season(panel.into()))
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -254,6 +444,26 @@ pub enum ShouldReset {
     No,
 }
 
+pub struct SeriesSearch {
+    selector: InfoSelector,
+    params: SeriesParams,
+    episodes: Option<SortedEpisodes>,
+}
+
+impl SeriesSearch {
+    #[inline(always)]
+    fn new<E>(selector: InfoSelector, params: SeriesParams, episodes: E) -> Self
+    where
+        E: Into<Option<SortedEpisodes>>,
+    {
+        Self {
+            selector,
+            params,
+            episodes: episodes.into(),
+        }
+    }
+}
+
 pub struct PartialSeries {
     info: InfoResult,
     params: SeriesParams,
@@ -0,0 +1,284 @@
+use super::load_state::{self, LoadState};
+use super::ShouldReset;
+use crate::key::Key;
+use crate::tui::component::Component;
+use crate::tui::state::{SharedState, UIState};
+use crate::util::{arc_mutex, ArcMutex, ScopedTask};
+use anime::remote::{AsyncRemote, Recommendation, SeriesEntry, SeriesID};
+use anyhow::{anyhow, Result};
+use crossterm::event::KeyCode;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::task;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Direction, Rect};
+use tui::style::Color;
+use tui::terminal::Frame;
+use tui::text::Span;
+use tui_utils::{
+    helpers::{block, style, text},
+    layout::{BasicConstraint, RectExt, SimpleLayout},
+    list::WrappedSelection,
+    widgets::{SimpleTable, SimpleText},
+};
+
+/// A panel that lists the series recommended to people who watched the selected series, and lets them be added to
+/// the user's list as planned.
+pub struct RecommendationsPanel {
+    title: String,
+    state: ArcMutex<PanelState>,
+    shared_state: SharedState,
+    #[allow(dead_code)]
+    load_task: ScopedTask<()>,
+}
+
+impl RecommendationsPanel {
+    #[allow(clippy::cast_sign_loss)]
+    pub fn init(state: &UIState, shared_state: &SharedState) -> Result<Self> {
+        let series = match state.series.selected() {
+            Some(series) => series,
+            None => return Err(anyhow!("must select a series to see its recommendations")),
+        };
+
+        let remote = state.remote.get_logged_in_async()?.clone();
+
+        if remote.get().is_offline() {
+            return Err(anyhow!("recommendations can't be retrieved while offline"));
+        }
+
+        let id = series.config().id as SeriesID;
+
+        let panel_state = arc_mutex(PanelState::Loading);
+        let load_task = Self::spawn_load_task(id, remote, &panel_state, shared_state).into();
+
+        Ok(Self {
+            title: format!("{} Recommendations", series.nickname()),
+            state: panel_state,
+            shared_state: shared_state.clone(),
+            load_task,
+        })
+    }
+
+    fn spawn_load_task(
+        id: SeriesID,
+        remote: AsyncRemote,
+        panel_state: &ArcMutex<PanelState>,
+        state: &SharedState,
+    ) -> task::JoinHandle<()> {
+        let load = async move {
+            let recommendations = remote.get_recommendations(id).await?;
+            let ids = recommendations.iter().map(|rec| rec.info.id).collect();
+            let entries = remote.get_list_entries(ids).await?;
+
+            Ok::<_, anyhow::Error>((recommendations, entries))
+        };
+
+        PanelState::spawn_load(panel_state, state, load, |(recommendations, entries), _| {
+            Recommendations::new(recommendations, &entries)
+        })
+    }
+
+    fn plan_selected(&self, state: &UIState) -> Result<()> {
+        let (id, title) = match &*self.state.lock() {
+            PanelState::Loaded(recommendations) => match recommendations.entries.selected() {
+                Some(entry) if entry.listed => {
+                    return Err(anyhow!(
+                        "{} is already on your list",
+                        entry.rec.info.title.preferred
+                    ))
+                }
+                Some(entry) => (entry.rec.info.id, entry.rec.info.title.preferred.clone()),
+                None => return Ok(()),
+            },
+            PanelState::Loading | PanelState::Failed => return Ok(()),
+        };
+
+        let panel_state = Arc::clone(&self.state);
+
+        self.shared_state
+            .plan_to_watch_async(state, id, title, move |_| {
+                if let PanelState::Loaded(recommendations) = &mut *panel_state.lock() {
+                    recommendations.mark_listed(id);
+                }
+            })
+    }
+
+    fn draw_recommendations<B>(
+        recommendations: &Recommendations,
+        state: &UIState,
+        rect: Rect,
+        frame: &mut Frame<B>,
+    ) where
+        B: Backend,
+    {
+        let hints_rect = rect.lines_from_bottom(1);
+
+        let hints = SimpleLayout::new(Direction::Horizontal).split_evenly(hints_rect);
+
+        let plan_hint =
+            SimpleText::new(text::hint("P - Plan to watch")).alignment(Alignment::Center);
+        frame.render_widget(plan_hint, hints.left);
+
+        let close_hint = SimpleText::new(text::hint("Esc - Close")).alignment(Alignment::Center);
+        frame.render_widget(close_hint, hints.right);
+
+        // Leave room for the hints, along with a spacer between them and the table
+        let table_rect = Rect {
+            height: rect.height.saturating_sub(2),
+            ..rect
+        };
+
+        let rows = recommendations.entries.iter().map(|entry| {
+            let info = &entry.rec.info;
+            let kind: &'static str = info.kind.into();
+
+            let year = info
+                .year
+                .map_or_else(|| "?".into(), |year| year.to_string());
+
+            let episodes = info
+                .episode_count()
+                .map_or_else(|| "?".into(), |episodes| episodes.to_string());
+
+            [
+                Span::raw(kind),
+                Span::raw(year),
+                Span::raw(episodes),
+                entry.library_status(state),
+                Span::raw(info.title.preferred.as_str()),
+            ]
+        });
+
+        let header = [
+            Span::raw("Type"),
+            Span::raw("Year"),
+            Span::raw("Episodes"),
+            Span::raw("Library"),
+            Span::raw("Title"),
+        ];
+
+        let layout = [
+            BasicConstraint::Length(8),
+            BasicConstraint::Length(6),
+            BasicConstraint::Length(10),
+            BasicConstraint::Length(9),
+            BasicConstraint::Percentage(100),
+        ];
+
+        let table = SimpleTable::new(rows, &layout)
+            .header(&header)
+            .highlight_symbol(Span::styled(">", style::list_selector(true)))
+            .select(Some(recommendations.entries.index() as u16));
+
+        frame.render_widget(table, table_rect);
+    }
+
+    pub fn draw<B: Backend>(&mut self, state: &UIState, rect: Rect, frame: &mut Frame<B>) {
+        let block = block::with_borders(self.title.as_str());
+        let block_area = block.inner(rect);
+
+        frame.render_widget(block, rect);
+
+        self.state.lock().draw(
+            "Failed to get recommendations",
+            block_area,
+            frame,
+            |recommendations, rect, frame| {
+                if recommendations.entries.selected().is_none() {
+                    load_state::draw_status("No recommendations found", rect, frame);
+                } else {
+                    Self::draw_recommendations(recommendations, state, rect, frame);
+                }
+            },
+        );
+    }
+}
+
+impl Component for RecommendationsPanel {
+    type State = UIState;
+    type KeyResult = Result<ShouldReset>;
+
+    fn process_key(&mut self, key: Key, state: &mut Self::State) -> Self::KeyResult {
+        match *key {
+            KeyCode::Esc => return Ok(ShouldReset::Yes),
+            KeyCode::Char('p') => self.plan_selected(state)?,
+            KeyCode::Up => {
+                if let PanelState::Loaded(recommendations) = &mut *self.state.lock() {
+                    recommendations.entries.dec_selected();
+                }
+            }
+            KeyCode::Down => {
+                if let PanelState::Loaded(recommendations) = &mut *self.state.lock() {
+                    recommendations.entries.inc_selected();
+                }
+            }
+            _ => (),
+        }
+
+        Ok(ShouldReset::No)
+    }
+}
+
+type PanelState = LoadState<Recommendations>;
+
+struct Recommendations {
+    entries: WrappedSelection<Vec<RecommendationEntry>, RecommendationEntry>,
+}
+
+impl Recommendations {
+    fn new(recommendations: Vec<Recommendation>, list_entries: &[SeriesEntry]) -> Self {
+        let listed = list_entries
+            .iter()
+            .map(|entry| entry.id)
+            .collect::<HashSet<_>>();
+
+        let entries = recommendations
+            .into_iter()
+            .map(|rec| RecommendationEntry {
+                listed: listed.contains(&rec.info.id),
+                rec,
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            entries: WrappedSelection::new(entries),
+        }
+    }
+
+    fn mark_listed(&mut self, id: SeriesID) {
+        let entry = self
+            .entries
+            .items_mut()
+            .iter_mut()
+            .find(|entry| entry.rec.info.id == id);
+
+        if let Some(entry) = entry {
+            entry.listed = true;
+        }
+    }
+}
+
+struct RecommendationEntry {
+    rec: Recommendation,
+    /// Indicates whether or not the series is on the user's remote list.
+    listed: bool,
+}
+
+impl RecommendationEntry {
+    /// Returns a marker for where the series can already be found.
+    ///
+    /// Series that have been added to the program are checked against its current state, since they may have been
+    /// added after the recommendations were retrieved.
+    #[allow(clippy::cast_possible_wrap)]
+    fn library_status(&self, state: &UIState) -> Span<'static> {
+        let id = self.rec.info.id as i32;
+
+        if state.series.iter().any(|series| series.config().id == id) {
+            Span::styled("Added", style::fg(Color::Blue))
+        } else if self.listed {
+            Span::styled("On List", style::fg(Color::Green))
+        } else {
+            Span::raw("")
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use super::ShouldReset;
+use crate::key::Key;
+use crate::remote::RemoteStatus;
+use crate::series::entry::{ConflictField, EntryConflict, SeriesEntry};
+use crate::series::LoadedSeries;
+use crate::tui::component::Component;
+use crate::tui::state::{SharedState, SyncDirection, UIState};
+use anyhow::{anyhow, Result};
+use chrono::{Local, TimeZone};
+use crossterm::event::KeyCode;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Direction, Rect};
+use tui::style::Color;
+use tui::terminal::Frame;
+use tui::text::Span;
+use tui_utils::{
+    helpers::{block, text},
+    layout::{BasicConstraint, RectExt, SimpleLayout},
+    widgets::{OverflowMode, SimpleText},
+};
+
+/// A panel that lets the user pick between the local and remote versions of a list entry that was changed in both places.
+pub struct ResolveConflictPanel {
+    conflict: EntryConflict,
+    title: String,
+    rows: Vec<ConflictRow>,
+    state: SharedState,
+}
+
+impl ResolveConflictPanel {
+    pub fn new(conflict: EntryConflict, state: &UIState, shared_state: &SharedState) -> Self {
+        let id = conflict.id();
+
+        let nickname = state
+            .series
+            .iter()
+            .find(|series| series.config().id == id)
+            .map_or("Unknown series", LoadedSeries::nickname);
+
+        let title = format!("{} was changed both locally and remotely", nickname);
+
+        let mut rows = conflict
+            .fields
+            .iter()
+            .map(|&field| ConflictRow {
+                name: field.into(),
+                local: Self::field_value(field, &conflict.local, state),
+                remote: Self::field_value(field, &conflict.remote, state),
+            })
+            .collect::<Vec<_>>();
+
+        rows.push(ConflictRow {
+            name: "Last Changed",
+            local: Self::timestamp_value(conflict.local.modified_at()),
+            remote: Self::timestamp_value(conflict.remote.remote_updated_at()),
+        });
+
+        Self {
+            conflict,
+            title,
+            rows,
+            state: shared_state.clone(),
+        }
+    }
+
+    fn field_value(field: ConflictField, entry: &SeriesEntry, state: &UIState) -> String {
+        match field {
+            ConflictField::Status => {
+                let status: &'static str = entry.status().into();
+                status.into()
+            }
+            ConflictField::Score => match (entry.score(), &state.remote) {
+                (Some(score), RemoteStatus::LoggedIn(remote)) => {
+                    remote.get().score_to_str(score as u8).into_owned()
+                }
+                (Some(score), RemoteStatus::LoggingIn(_)) => score.to_string(),
+                (None, _) => "None".into(),
+            },
+            ConflictField::Notes => entry.notes().unwrap_or("None").into(),
+            ConflictField::Private => Self::bool_value(entry.private()),
+            ConflictField::Hidden => Self::bool_value(entry.hidden()),
+            ConflictField::CustomLists if entry.custom_lists().is_empty() => "None".into(),
+            ConflictField::CustomLists => entry.custom_lists().join(", "),
+        }
+    }
+
+    fn bool_value(value: bool) -> String {
+        let value = if value { "Yes" } else { "No" };
+        value.into()
+    }
+
+    fn timestamp_value(timestamp: Option<i64>) -> String {
+        match timestamp {
+            Some(timestamp) => Local
+                .timestamp(timestamp, 0)
+                .format("%b %-d %H:%M")
+                .to_string(),
+            None => "Unknown".into(),
+        }
+    }
+
+    fn resolve(&self, entry: SeriesEntry, state: &mut UIState) -> Result<()> {
+        let id = self.conflict.id();
+
+        let series = state
+            .series
+            .items_mut()
+            .iter_mut()
+            .filter_map(LoadedSeries::complete_mut)
+            .find(|series| series.data.info.id == id)
+            .ok_or_else(|| anyhow!("series no longer exists"))?;
+
+        series.data.entry = entry;
+        series.save(&state.db)?;
+
+        self.state.sync_series_async(id, SyncDirection::ToRemote);
+        Ok(())
+    }
+
+    fn draw_row<B: Backend>(
+        name: Span,
+        local: Span,
+        remote: Span,
+        rect: Rect,
+        frame: &mut Frame<B>,
+    ) {
+        let layout = SimpleLayout::new(Direction::Horizontal).split(
+            rect,
+            &[
+                BasicConstraint::Percentage(20),
+                BasicConstraint::Percentage(40),
+                BasicConstraint::Percentage(40),
+            ],
+        );
+
+        for (span, rect) in [name, local, remote].iter().cloned().zip(layout.iter()) {
+            let widget = SimpleText::new(span)
+                .alignment(Alignment::Center)
+                .overflow(OverflowMode::Truncate);
+
+            frame.render_widget(widget, *rect);
+        }
+    }
+
+    fn draw_hints<B: Backend>(rect: Rect, frame: &mut Frame<B>) {
+        let layout = SimpleLayout::new(Direction::Horizontal).split(
+            rect,
+            &[
+                BasicConstraint::Percentage(33),
+                BasicConstraint::Percentage(33),
+                BasicConstraint::Percentage(33),
+            ],
+        );
+
+        let hints = ["L - Keep local", "R - Keep remote", "Esc - Decide later"];
+
+        for (hint, rect) in hints.iter().zip(layout.iter()) {
+            let widget = SimpleText::new(text::hint(*hint)).alignment(Alignment::Center);
+            frame.render_widget(widget, *rect);
+        }
+    }
+
+    pub fn draw<B: Backend>(&mut self, rect: Rect, frame: &mut Frame<B>) {
+        let block = block::with_borders("Resolve Conflict");
+        let block_area = block.inner(rect);
+
+        frame.render_widget(block, rect);
+
+        let area = Rect {
+            x: block_area.x + 1,
+            width: block_area.width.saturating_sub(2),
+            ..block_area
+        };
+
+        let title = SimpleText::new(text::bold_with(&self.title, |s| s.fg(Color::Yellow)))
+            .alignment(Alignment::Center)
+            .overflow(OverflowMode::Truncate);
+
+        frame.render_widget(title, area.lines_from_top(1));
+
+        let mut row_rect = Rect {
+            y: area.y + 2,
+            height: 1,
+            ..area
+        };
+
+        Self::draw_row(
+            text::bold("Field"),
+            text::bold("Local"),
+            text::bold("Remote"),
+            row_rect,
+            frame,
+        );
+
+        for row in &self.rows {
+            row_rect.y += 1;
+
+            // Leave room for the hints
+            if row_rect.y + 2 >= area.bottom() {
+                break;
+            }
+
+            Self::draw_row(
+                text::italic(row.name),
+                Span::raw(row.local.as_str()),
+                Span::raw(row.remote.as_str()),
+                row_rect,
+                frame,
+            );
+        }
+
+        Self::draw_hints(area.lines_from_bottom(1), frame);
+    }
+}
+
+impl Component for ResolveConflictPanel {
+    type State = UIState;
+    type KeyResult = Result<ShouldReset>;
+
+    fn process_key(&mut self, key: Key, state: &mut Self::State) -> Self::KeyResult {
+        match *key {
+            // The conflict will come up again the next time the entry is synced
+            KeyCode::Esc => Ok(ShouldReset::Yes),
+            KeyCode::Char('l') => {
+                self.resolve(self.conflict.local.clone(), state)?;
+                Ok(ShouldReset::Yes)
+            }
+            KeyCode::Char('r') => {
+                self.resolve(self.conflict.remote.clone(), state)?;
+                Ok(ShouldReset::Yes)
+            }
+            _ => Ok(ShouldReset::No),
+        }
+    }
+}
+
+struct ConflictRow {
+    name: &'static str,
+    local: String,
+    remote: String,
+}
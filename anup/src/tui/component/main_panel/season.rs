@@ -0,0 +1,397 @@
+use super::load_state::{self, LoadState};
+use super::select_series::{next_filter, KIND_FILTERS};
+use super::ShouldReset;
+use crate::key::Key;
+use crate::tui::component::Component;
+use crate::tui::state::{SharedState, UIState};
+use crate::util::{arc_mutex, ArcMutex, ScopedTask};
+use anime::remote::{AsyncRemote, Season, SeriesEntry, SeriesID, SeriesInfo};
+use anime::SeriesKind;
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Local};
+use crossterm::event::KeyCode;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::task;
+use tui::backend::Backend;
+use tui::layout::{Alignment, Direction, Rect};
+use tui::terminal::Frame;
+use tui::text::Span;
+use tui_utils::{
+    helpers::{block, style, text},
+    layout::{BasicConstraint, RectExt, SimpleLayout},
+    list::WrappedSelection,
+    widgets::{SimpleTable, SimpleText},
+};
+
+/// A panel that lists every series airing in a season that hasn't been added or put on the user's list yet, and
+/// lets them be added to the user's list as planned.
+pub struct SeasonPanel {
+    season: ChartSeason,
+    kind: Option<SeriesKind>,
+    remote: AsyncRemote,
+    state: ArcMutex<PanelState>,
+    shared_state: SharedState,
+    #[allow(dead_code)]
+    load_task: ScopedTask<()>,
+}
+
+impl SeasonPanel {
+    pub fn init(state: &UIState, shared_state: &SharedState) -> Result<Self> {
+        let remote = state.remote.get_logged_in_async()?.clone();
+
+        if remote.get().is_offline() {
+            return Err(anyhow!("seasons can't be retrieved while offline"));
+        }
+
+        let season = ChartSeason::current();
+
+        let panel_state = arc_mutex(PanelState::Loading);
+        let load_task =
+            Self::spawn_load_task(season, None, remote.clone(), &panel_state, shared_state).into();
+
+        Ok(Self {
+            season,
+            kind: None,
+            remote,
+            state: panel_state,
+            shared_state: shared_state.clone(),
+            load_task,
+        })
+    }
+
+    fn spawn_load_task(
+        season: ChartSeason,
+        kind: Option<SeriesKind>,
+        remote: AsyncRemote,
+        panel_state: &ArcMutex<PanelState>,
+        state: &SharedState,
+    ) -> task::JoinHandle<()> {
+        let load = async move {
+            // Every kind is retrieved so the filter can be changed without making another request
+            let infos = remote.get_season(season.season, season.year, None).await?;
+            let ids = infos.iter().map(|info| info.id).collect();
+            let entries = remote.get_list_entries(ids).await?;
+
+            Ok::<_, anyhow::Error>((infos, entries))
+        };
+
+        PanelState::spawn_load(panel_state, state, load, move |(infos, entries), state| {
+            Chart::new(infos, &entries, kind, state)
+        })
+    }
+
+    fn switch_season(&mut self, season: ChartSeason) {
+        self.season = season;
+        *self.state.lock() = PanelState::Loading;
+
+        self.load_task = Self::spawn_load_task(
+            season,
+            self.kind,
+            self.remote.clone(),
+            &self.state,
+            &self.shared_state,
+        )
+        .into();
+    }
+
+    fn cycle_kind(&mut self, state: &UIState) {
+        let mut panel_state = self.state.lock();
+
+        let chart = match &mut *panel_state {
+            PanelState::Loaded(chart) => chart,
+            PanelState::Loading | PanelState::Failed => return,
+        };
+
+        self.kind = next_filter(&KIND_FILTERS, self.kind);
+
+        chart.kind = self.kind;
+        chart.refresh(state);
+    }
+
+    fn plan_selected(&self, state: &UIState) -> Result<()> {
+        let (id, title) = match &*self.state.lock() {
+            PanelState::Loaded(chart) => match chart.selected() {
+                Some(entry) => (entry.info.id, entry.info.title.preferred.clone()),
+                None => return Ok(()),
+            },
+            PanelState::Loading | PanelState::Failed => return Ok(()),
+        };
+
+        let panel_state = Arc::clone(&self.state);
+
+        self.shared_state
+            .plan_to_watch_async(state, id, title, move |state| {
+                if let PanelState::Loaded(chart) = &mut *panel_state.lock() {
+                    chart.mark_listed(id, state);
+                }
+            })
+    }
+
+    fn draw_hints<B>(kind: Option<SeriesKind>, rect: Rect, frame: &mut Frame<B>)
+    where
+        B: Backend,
+    {
+        let rows = SimpleLayout::new(Direction::Vertical).split_evenly(rect.lines_from_bottom(2));
+        let top = SimpleLayout::new(Direction::Horizontal).split_evenly(rows.left);
+        let bottom = SimpleLayout::new(Direction::Horizontal).split_evenly(rows.right);
+
+        let kind: &'static str = kind.map_or("Any", Into::into);
+        let filter_hint = format!("F - Format: {}", kind);
+
+        let hints = [
+            ("Left / Right - Change season", top.left),
+            (filter_hint.as_str(), top.right),
+            ("P - Plan to watch", bottom.left),
+            ("Esc - Close", bottom.right),
+        ];
+
+        for (hint, rect) in hints.iter() {
+            let widget = SimpleText::new(text::hint(*hint)).alignment(Alignment::Center);
+            frame.render_widget(widget, *rect);
+        }
+    }
+
+    fn draw_chart<B>(chart: &Chart, rect: Rect, frame: &mut Frame<B>)
+    where
+        B: Backend,
+    {
+        let rows = chart.visible.iter().map(|&index| {
+            let info = &chart.entries[index].info;
+            let kind: &'static str = info.kind.into();
+
+            let episodes = info
+                .episode_count()
+                .map_or_else(|| "?".into(), |episodes| episodes.to_string());
+
+            let studio = info.studios.first().map_or("", String::as_str);
+
+            [
+                Span::raw(kind),
+                Span::raw(episodes),
+                Span::raw(studio),
+                Span::raw(info.title.preferred.as_str()),
+            ]
+        });
+
+        let header = [
+            Span::raw("Type"),
+            Span::raw("Episodes"),
+            Span::raw("Studio"),
+            Span::raw("Title"),
+        ];
+
+        let layout = [
+            BasicConstraint::Length(8),
+            BasicConstraint::Length(10),
+            BasicConstraint::Length(20),
+            BasicConstraint::Percentage(100),
+        ];
+
+        let table = SimpleTable::new(rows, &layout)
+            .header(&header)
+            .highlight_symbol(Span::styled(">", style::list_selector(true)))
+            .select(Some(chart.visible.index() as u16));
+
+        frame.render_widget(table, rect);
+    }
+
+    pub fn draw<B: Backend>(&mut self, rect: Rect, frame: &mut Frame<B>) {
+        let season: &'static str = self.season.season.into();
+        let title = format!("{} {} Season", season, self.season.year);
+
+        let block = block::with_borders(title.as_str());
+        let block_area = block.inner(rect);
+
+        frame.render_widget(block, rect);
+
+        Self::draw_hints(self.kind, block_area, frame);
+
+        // Leave room for the hints, along with a spacer between them and the table
+        let table_rect = Rect {
+            height: block_area.height.saturating_sub(3),
+            ..block_area
+        };
+
+        self.state.lock().draw(
+            "Failed to get the season",
+            table_rect,
+            frame,
+            |chart, rect, frame| {
+                if chart.visible.selected().is_none() {
+                    load_state::draw_status("No new series found", rect, frame);
+                } else {
+                    Self::draw_chart(chart, rect, frame);
+                }
+            },
+        );
+    }
+}
+
+impl Component for SeasonPanel {
+    type State = UIState;
+    type KeyResult = Result<ShouldReset>;
+
+    fn process_key(&mut self, key: Key, state: &mut Self::State) -> Self::KeyResult {
+        match *key {
+            KeyCode::Esc => return Ok(ShouldReset::Yes),
+            KeyCode::Char('p') => self.plan_selected(state)?,
+            KeyCode::Char('f') => self.cycle_kind(state),
+            KeyCode::Left => self.switch_season(self.season.previous()),
+            KeyCode::Right => self.switch_season(self.season.next()),
+            KeyCode::Up => {
+                if let PanelState::Loaded(chart) = &mut *self.state.lock() {
+                    chart.visible.dec_selected();
+                }
+            }
+            KeyCode::Down => {
+                if let PanelState::Loaded(chart) = &mut *self.state.lock() {
+                    chart.visible.inc_selected();
+                }
+            }
+            _ => (),
+        }
+
+        Ok(ShouldReset::No)
+    }
+}
+
+/// A season of a specific year.
+#[derive(Copy, Clone)]
+struct ChartSeason {
+    season: Season,
+    year: u32,
+}
+
+impl ChartSeason {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn current() -> Self {
+        let now = Local::now();
+
+        Self {
+            season: Season::from_month(now.month() as u8).unwrap_or(Season::Winter),
+            year: now.year() as u32,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self.season {
+            Season::Winter => self.with_season(Season::Spring),
+            Season::Spring => self.with_season(Season::Summer),
+            Season::Summer => self.with_season(Season::Fall),
+            Season::Fall => Self {
+                season: Season::Winter,
+                year: self.year + 1,
+            },
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self.season {
+            Season::Winter => Self {
+                season: Season::Fall,
+                year: self.year.saturating_sub(1),
+            },
+            Season::Spring => self.with_season(Season::Winter),
+            Season::Summer => self.with_season(Season::Spring),
+            Season::Fall => self.with_season(Season::Summer),
+        }
+    }
+
+    fn with_season(self, season: Season) -> Self {
+        Self { season, ..self }
+    }
+}
+
+type PanelState = LoadState<Chart>;
+
+struct Chart {
+    entries: Vec<ChartEntry>,
+    /// The indices of the entries that pass the current filter.
+    visible: WrappedSelection<Vec<usize>, usize>,
+    kind: Option<SeriesKind>,
+}
+
+impl Chart {
+    fn new(
+        infos: Vec<SeriesInfo>,
+        list_entries: &[SeriesEntry],
+        kind: Option<SeriesKind>,
+        state: &UIState,
+    ) -> Self {
+        let listed = list_entries
+            .iter()
+            .map(|entry| entry.id)
+            .collect::<HashSet<_>>();
+
+        let entries = infos
+            .into_iter()
+            .map(|info| ChartEntry {
+                listed: listed.contains(&info.id),
+                info,
+            })
+            .collect();
+
+        let mut chart = Self {
+            entries,
+            visible: WrappedSelection::new(Vec::new()),
+            kind,
+        };
+
+        chart.refresh(state);
+        chart
+    }
+
+    /// Hide every entry that doesn't match the current filter, is on the user's list, or has been added to the
+    /// program.
+    ///
+    /// The program is checked against its current state, since series may have been added after the season was
+    /// retrieved.
+    #[allow(clippy::cast_possible_wrap)]
+    fn refresh(&mut self, state: &UIState) {
+        let added = state
+            .series
+            .iter()
+            .map(|series| series.config().id)
+            .collect::<HashSet<_>>();
+
+        let visible = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                !entry.listed
+                    && !added.contains(&(entry.info.id as i32))
+                    && self.kind.map_or(true, |kind| entry.info.kind == kind)
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        let selected = self.visible.index().min(visible.len().saturating_sub(1));
+
+        self.visible = WrappedSelection::new(visible);
+        self.visible.set_selected(selected);
+    }
+
+    fn selected(&self) -> Option<&ChartEntry> {
+        self.visible
+            .selected()
+            .and_then(|&index| self.entries.get(index))
+    }
+
+    fn mark_listed(&mut self, id: SeriesID, state: &UIState) {
+        let entry = self.entries.iter_mut().find(|entry| entry.info.id == id);
+
+        if let Some(entry) = entry {
+            entry.listed = true;
+        }
+
+        self.refresh(state);
+    }
+}
+
+struct ChartEntry {
+    info: SeriesInfo,
+    /// Indicates whether or not the series is on the user's remote list.
+    listed: bool,
+}
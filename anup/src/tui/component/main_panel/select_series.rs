@@ -1,30 +1,163 @@
+use crate::series::info::{InfoSearch, SearchResults};
 use crate::series::SeriesParams;
 use crate::tui::component::Component;
+use crate::tui::state::{SharedState, UIState};
+use crate::util::{arc_mutex, ArcMutex, ScopedTask};
 use crate::{key::Key, series::info::SeriesInfo};
+use anime::remote::{AiringStatus, SearchOptions};
+use anime::SeriesKind;
+use anyhow::Result;
+use chrono::{Datelike, Local};
 use crossterm::event::KeyCode;
-use tui::layout::Rect;
+use std::sync::Arc;
+use tokio::task;
+use tui::layout::{Alignment, Rect};
 use tui::style::Color;
 use tui::terminal::Frame;
 use tui::{backend::Backend, text::Span};
 use tui_utils::{
     helpers::{block, text},
+    layout::RectExt,
     list::WrappedSelection,
-    widgets::SimpleList,
+    widgets::{OverflowMode, SimpleList, SimpleText},
 };
 
+/// The series kinds that search results can be narrowed down to, in the order they're cycled through.
+pub(super) const KIND_FILTERS: [Option<SeriesKind>; 7] = [
+    None,
+    Some(SeriesKind::Season),
+    Some(SeriesKind::Movie),
+    Some(SeriesKind::Special),
+    Some(SeriesKind::OVA),
+    Some(SeriesKind::ONA),
+    Some(SeriesKind::Music),
+];
+
+/// The airing statuses that search results can be narrowed down to, in the order they're cycled through.
+const STATUS_FILTERS: [Option<AiringStatus>; 6] = [
+    None,
+    Some(AiringStatus::Finished),
+    Some(AiringStatus::Airing),
+    Some(AiringStatus::NotYetAired),
+    Some(AiringStatus::Cancelled),
+    Some(AiringStatus::Hiatus),
+];
+
 pub struct SelectSeriesPanel {
-    state: SelectState,
+    select: ArcMutex<SelectState>,
+    params: SeriesParams,
+    state: SharedState,
+    #[allow(dead_code)]
+    search_task: Option<ScopedTask<()>>,
 }
 
 impl SelectSeriesPanel {
-    #[inline(always)]
-    pub fn new(state: SelectState) -> Self {
-        Self { state }
+    pub fn new(results: SearchResults, params: SeriesParams, state: &SharedState) -> Self {
+        Self {
+            select: arc_mutex(SelectState::new(results)),
+            params,
+            state: state.clone(),
+            search_task: None,
+        }
     }
 
     #[inline(always)]
     pub fn take_params(self) -> SeriesParams {
-        self.state.params
+        self.params
+    }
+
+    /// Run `search` in the background and either replace the current results with the ones found, or add them to the end of the list.
+    fn search(&mut self, search: InfoSearch, append: bool, state: &UIState) -> Result<()> {
+        let remote = state.remote.get_logged_in_async()?.clone();
+        let select = Arc::clone(&self.select);
+        let shared_state = self.state.clone();
+
+        self.select.lock().loading = true;
+
+        let task = task::spawn(async move {
+            let result = search.run(&remote).await;
+            let mut state = shared_state.lock();
+            let mut select = select.lock();
+
+            select.loading = false;
+
+            match result {
+                Ok(results) if append => select.append(results),
+                Ok(results) => *select = SelectState::new(results),
+                Err(err) => state.get_mut().log.push_error(&err),
+            }
+
+            state.mark_dirty();
+        });
+
+        self.search_task = Some(task.into());
+        Ok(())
+    }
+
+    fn load_next_page(&mut self, state: &UIState) -> Result<()> {
+        let search = {
+            let select = self.select.lock();
+
+            if select.loading || !select.has_next_page {
+                return Ok(());
+            }
+
+            let mut search = select.search.clone();
+            search.options = search.options.next_page();
+            search
+        };
+
+        self.search(search, true, state)
+    }
+
+    /// Restart the search from the first page with the options returned by `modify`.
+    fn refilter<F>(&mut self, modify: F, state: &UIState) -> Result<()>
+    where
+        F: FnOnce(SearchOptions) -> SearchOptions,
+    {
+        let search = {
+            let select = self.select.lock();
+
+            if select.loading {
+                return Ok(());
+            }
+
+            let mut search = select.search.clone();
+            search.options = modify(search.options).page(1);
+            search
+        };
+
+        self.search(search, false, state)
+    }
+
+    fn draw_hints<B: Backend>(select: &SelectState, rect: Rect, frame: &mut Frame<B>) {
+        let options = &select.search.options;
+
+        let kind = options.kind.map_or("Any", Into::into);
+        let status = options.status.map_or("Any", Into::into);
+
+        let year = options
+            .season_year
+            .map_or_else(|| "Any".into(), |year| year.to_string());
+
+        let next_page = if select.loading {
+            "Loading.."
+        } else if select.has_next_page {
+            "N - Load more"
+        } else {
+            "No more results"
+        };
+
+        let hint_text = format!(
+            "F - Format: {} | S - Status: {} | Y / Shift + Y - Year: {} | {}",
+            kind, status, year, next_page
+        );
+
+        let hint = SimpleText::new(text::hint(hint_text))
+            .alignment(Alignment::Center)
+            .overflow(OverflowMode::Truncate);
+
+        frame.render_widget(hint, rect);
     }
 
     pub fn draw<B: Backend>(&mut self, rect: Rect, frame: &mut Frame<B>) {
@@ -33,66 +166,159 @@ impl SelectSeriesPanel {
 
         frame.render_widget(block, rect);
 
-        let names = self
-            .state
-            .series_list
-            .iter()
-            .map(|info| Span::raw(info.title_preferred.as_str()));
+        let select = self.select.lock();
+
+        Self::draw_hints(&select, block_area.lines_from_bottom(1), frame);
+
+        // Leave room for the hints, along with a spacer between them and the list
+        let list_rect = Rect {
+            height: block_area.height.saturating_sub(2),
+            ..block_area
+        };
+
+        let names = select.series_list.iter().map(|info| {
+            let details = info.details();
+
+            if details.is_empty() {
+                Span::raw(info.title_preferred.as_str())
+            } else {
+                Span::raw(format!("{} [{}]", info.title_preferred, details))
+            }
+        });
 
         let items = SimpleList::new(names)
             .highlight_symbol(text::italic_with(">", |s| s.fg(Color::Green)))
-            .select(Some(self.state.series_list.index() as u16));
+            .select(Some(select.series_list.index() as u16));
 
-        frame.render_widget(items, block_area);
+        frame.render_widget(items, list_rect);
     }
 }
 
 impl Component for SelectSeriesPanel {
-    type State = ();
-    type KeyResult = SelectSeriesResult;
+    type State = UIState;
+    type KeyResult = Result<SelectSeriesResult>;
 
-    fn process_key(&mut self, key: Key, _: &mut Self::State) -> Self::KeyResult {
+    fn process_key(&mut self, key: Key, state: &mut Self::State) -> Self::KeyResult {
         match *key {
             KeyCode::Up => {
-                self.state.series_list.dec_selected();
-                SelectSeriesResult::Ok
+                self.select.lock().series_list.dec_selected();
+                Ok(SelectSeriesResult::Ok)
             }
             KeyCode::Down => {
-                self.state.series_list.inc_selected();
-                SelectSeriesResult::Ok
+                self.select.lock().series_list.inc_selected();
+                Ok(SelectSeriesResult::Ok)
+            }
+            KeyCode::Char('n') => {
+                self.load_next_page(state)?;
+                Ok(SelectSeriesResult::Ok)
+            }
+            KeyCode::Char('f') => {
+                self.refilter(
+                    |options| {
+                        let kind = next_filter(&KIND_FILTERS, options.kind);
+                        options.kind(kind)
+                    },
+                    state,
+                )?;
+
+                Ok(SelectSeriesResult::Ok)
+            }
+            KeyCode::Char('s') => {
+                self.refilter(
+                    |options| {
+                        let status = next_filter(&STATUS_FILTERS, options.status);
+                        options.status(status)
+                    },
+                    state,
+                )?;
+
+                Ok(SelectSeriesResult::Ok)
+            }
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let later = *key == KeyCode::Char('Y');
+
+                self.refilter(
+                    |options| {
+                        let year = step_year_filter(options.season_year, later);
+                        options.season_year(year)
+                    },
+                    state,
+                )?;
+
+                Ok(SelectSeriesResult::Ok)
             }
             KeyCode::Enter => {
-                if !self.state.series_list.is_valid_index() {
-                    return SelectSeriesResult::Reset;
+                let mut select = self.select.lock();
+
+                if !select.series_list.is_valid_index() {
+                    return Ok(SelectSeriesResult::Reset);
                 }
 
-                let selected = self.state.series_list.index();
-                let info = self.state.series_list.swap_remove(selected);
+                let selected = select.series_list.index();
+                let info = select.series_list.swap_remove(selected);
 
-                SelectSeriesResult::AddSeries(info)
+                Ok(SelectSeriesResult::AddSeries(info.into()))
             }
-            KeyCode::Esc => SelectSeriesResult::Reset,
-            _ => SelectSeriesResult::Ok,
+            KeyCode::Esc => Ok(SelectSeriesResult::Reset),
+            _ => Ok(SelectSeriesResult::Ok),
         }
     }
 }
 
-pub struct SelectState {
-    pub series_list: WrappedSelection<Vec<SeriesInfo>, SeriesInfo>,
-    pub params: SeriesParams,
+/// Returns the filter after `current` in `filters`, wrapping back around to the first one.
+pub(super) fn next_filter<T>(filters: &[Option<T>], current: Option<T>) -> Option<T>
+where
+    T: Copy + PartialEq,
+{
+    let index = filters
+        .iter()
+        .position(|&filter| filter == current)
+        .unwrap_or(0);
+
+    filters[(index + 1) % filters.len()]
+}
+
+/// Returns the year filter one year before or `later` than `current`.
+///
+/// Filtering starts from the current year, and moving past next year clears the filter so announced series can be found.
+#[allow(clippy::cast_sign_loss)]
+fn step_year_filter(current: Option<u32>, later: bool) -> Option<u32> {
+    let this_year = Local::now().year() as u32;
+
+    match (current, later) {
+        (None, false) => Some(this_year),
+        (None, true) => None,
+        (Some(year), false) => Some(year.saturating_sub(1)),
+        (Some(year), true) => Some(year + 1).filter(|&year| year <= this_year + 1),
+    }
+}
+
+struct SelectState {
+    series_list: WrappedSelection<Vec<SeriesInfo>, SeriesInfo>,
+    search: InfoSearch,
+    has_next_page: bool,
+    loading: bool,
 }
 
 impl SelectState {
-    pub fn new(series_list: Vec<SeriesInfo>, params: SeriesParams) -> Self {
+    fn new(results: SearchResults) -> Self {
         Self {
-            series_list: WrappedSelection::new(series_list),
-            params,
+            series_list: WrappedSelection::new(results.results),
+            search: results.search,
+            has_next_page: results.has_next_page,
+            loading: false,
         }
     }
+
+    fn append(&mut self, results: SearchResults) {
+        self.series_list.extend(results.results);
+        self.search = results.search;
+        self.has_next_page = results.has_next_page;
+    }
 }
 
 pub enum SelectSeriesResult {
     Ok,
-    AddSeries(SeriesInfo),
+    AddSeries(Box<SeriesInfo>),
     Reset,
 }
@@ -12,15 +12,13 @@ use crate::{
 };
 use crate::{tui::component::Component, util::ArcMutex};
 use add::AddPanel;
-use anime::local::{CategorizedEpisodes, SortedEpisodes};
-use anime::remote::{Remote, RemoteService, SeriesInfo as RemoteInfo};
+use anime::local::{CategorizedEpisodes, EpisodeParser, SortedEpisodes};
+use anime::remote::{AsyncRemote, SeriesID, SeriesInfo as RemoteInfo};
 use anime::SeriesKind;
 use anyhow::{anyhow, Context, Result};
 use split::{SplitPanel, SplitResult};
 use std::mem;
 use std::path::PathBuf;
-use std::thread;
-use std::time::Duration;
 use std::{borrow::Cow, sync::Arc};
 use std::{fs, io};
 use tokio::task;
@@ -58,27 +56,34 @@ impl SplitSeriesPanel {
         let state = state.clone();
 
         task::spawn(async move {
-            let mut state = state.lock();
+            let (data, config, remote) = {
+                let mut state = state.lock();
+
+                let data = match state.series.selected() {
+                    Some(LoadedSeries::Complete(series)) => SplitData::new(&series.data),
+                    Some(LoadedSeries::Partial(data, _)) => SplitData::new(data),
+                    Some(LoadedSeries::None(_, _)) | None => {
+                        state
+                            .get_mut()
+                            .log
+                            .push(LogKind::Error, "cannot split a series with errors");
+
+                        return;
+                    }
+                };
 
-            let series = match state.series.selected() {
-                Some(LoadedSeries::Complete(series)) => &series.data,
-                Some(LoadedSeries::Partial(data, _)) => data,
-                Some(LoadedSeries::None(_, _)) | None => {
-                    state
-                        .get_mut()
-                        .log
-                        .push(LogKind::Error, "cannot split a series with errors");
+                let remote = match state.remote.get_logged_in_async() {
+                    Ok(remote) => remote.clone(),
+                    Err(_) => return,
+                };
 
-                    return;
-                }
+                (data, state.config.clone(), remote)
             };
 
-            let remote = match state.remote.get_logged_in() {
-                Ok(remote) => remote,
-                Err(_) => return,
-            };
+            let result = MergedSeries::resolve(data, &remote, &config).await;
+            let mut state = state.lock();
 
-            let merged_series = match MergedSeries::resolve(series, remote, &state.config) {
+            let merged_series = match result {
                 Ok(merged) => merged,
                 Err(err) => {
                     state.get_mut().log.push_error(&err);
@@ -134,7 +139,7 @@ impl Component for SplitSeriesPanel {
                 Ok(SplitResult::Ok) => Ok(SplitPanelResult::Ok),
                 Ok(SplitResult::Reset) => Ok(SplitPanelResult::Reset),
                 Ok(SplitResult::AddSeries(info, path)) => {
-                    let add_panel = AddPanel::new(info, path);
+                    let add_panel = AddPanel::new(*info, path);
                     let split_panel = mem::take(split_panel);
 
                     *panel_state = PanelState::AddingSeries(add_panel.into(), split_panel);
@@ -181,6 +186,23 @@ impl SplitPanelResult {
     }
 }
 
+/// The parts of a series needed to resolve its merged series.
+struct SplitData {
+    id: SeriesID,
+    path: SeriesPath,
+    parser: EpisodeParser,
+}
+
+impl SplitData {
+    fn new(data: &SeriesData) -> Self {
+        Self {
+            id: data.info.id as SeriesID,
+            path: data.config.path.clone(),
+            parser: data.config.episode_parser.clone(),
+        }
+    }
+}
+
 #[allow(variant_size_differences)]
 enum MergedSeries {
     Resolved(Box<ResolvedSeries>),
@@ -193,13 +215,9 @@ impl MergedSeries {
         Self::Resolved(Box::new(resolved))
     }
 
-    fn resolve(data: &SeriesData, remote: &Remote, config: &Config) -> Result<Vec<Self>> {
-        let episodes = CategorizedEpisodes::parse(
-            data.config.path.absolute(config),
-            &data.config.episode_parser,
-        )?;
-
-        let base_info = remote.search_info_by_id(data.info.id as u32)?;
+    async fn resolve(data: SplitData, remote: &AsyncRemote, config: &Config) -> Result<Vec<Self>> {
+        let episodes = CategorizedEpisodes::parse(data.path.absolute(config), &data.parser)?;
+        let base_info = remote.search_info_by_id(data.id).await?;
 
         if base_info.sequels.is_empty() {
             return Ok(Vec::new());
@@ -217,27 +235,25 @@ impl MergedSeries {
             if let SeriesKind::Season = sequel.kind {
                 Self::resolve_merged_season(
                     &base_info,
-                    &data.config.path,
+                    &data.path,
                     remote,
                     eps,
                     config,
                     &mut results,
-                );
+                )
+                .await;
 
                 continue;
             }
 
-            thread::sleep(Duration::from_millis(250));
-
-            let sequel_info = if let Ok(info) = remote.search_info_by_id(sequel.id) {
+            let sequel_info = if let Ok(info) = remote.search_info_by_id(sequel.id).await {
                 info
             } else {
                 results.push(Self::Failed(sequel.kind));
                 continue;
             };
 
-            let resolved =
-                ResolvedSeries::new(sequel_info, data.config.path.clone(), eps, 0, config);
+            let resolved = ResolvedSeries::new(sequel_info, data.path.clone(), eps, 0, config);
 
             results.push(Self::resolved(resolved));
         }
@@ -245,10 +261,10 @@ impl MergedSeries {
         Ok(results)
     }
 
-    fn resolve_merged_season(
+    async fn resolve_merged_season(
         base_info: &RemoteInfo,
         base_path: &SeriesPath,
-        remote: &Remote,
+        remote: &AsyncRemote,
         episodes: &SortedEpisodes,
         config: &Config,
         results: &mut Vec<Self>,
@@ -256,15 +272,15 @@ impl MergedSeries {
         let highest_episode = episodes.highest_episode_number();
         let mut info = Cow::Borrowed(base_info);
 
-        // Exit early if we don't have enough episodes locally to have any merged seasons
-        if info.episodes > highest_episode {
-            return;
-        }
-
-        let mut episode_offset = info.episodes;
+        // Exit early if we don't have enough episodes locally to have any merged seasons,
+        // or if the series is still airing with an unknown number of episodes
+        let mut episode_offset = match info.episode_count() {
+            Some(episodes) if episodes <= highest_episode => episodes,
+            Some(_) | None => return,
+        };
 
         while let Some(sequel) = info.direct_sequel() {
-            info = if let Ok(info) = remote.search_info_by_id(sequel.id) {
+            info = if let Ok(info) = remote.search_info_by_id(sequel.id).await {
                 info.into()
             } else {
                 results.push(Self::Failed(sequel.kind));
@@ -281,14 +297,18 @@ impl MergedSeries {
 
             results.push(Self::resolved(resolved));
 
-            episode_offset += info.episodes;
+            // A sequel with an unknown number of episodes is still airing, so it has to be the last one
+            let sequel_episodes = match info.episode_count() {
+                Some(episodes) => episodes,
+                None => break,
+            };
+
+            episode_offset += sequel_episodes;
 
             // We can stop if we don't have anymore sequels or if we don't have enough episodes locally to have another merged season
             if episode_offset > highest_episode || info.direct_sequel().is_none() {
                 break;
             }
-
-            thread::sleep(Duration::from_millis(250));
         }
     }
 
@@ -403,7 +423,11 @@ impl SplitAction {
         let mut actions = Vec::new();
 
         let sequel_start = 1 + offset;
-        let sequel_end = offset + info.episodes;
+
+        // Every remaining episode belongs to a sequel that's still airing with an unknown number of episodes
+        let sequel_end = info
+            .episode_count()
+            .map_or_else(|| episodes.highest_episode_number(), |eps| offset + eps);
 
         for real_ep_num in sequel_start..=sequel_end {
             let episode = match episodes.find(real_ep_num) {
@@ -431,3 +455,124 @@ impl SplitAction {
         actions
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anime::remote::anilist::AniList;
+    use anime::remote::replay::{Cassette, Recorder, Replay};
+    use anime::remote::transport::{Client, ClientConfig, Request, Response, Transport};
+    use anime::remote::{Remote, RemoteService};
+    use std::path::Path;
+
+    const SEASON_1_JSON: &str = r#"{
+        "data": {
+            "Media": {
+                "id": 1,
+                "title": { "romaji": "Series", "userPreferred": "Series" },
+                "episodes": 12,
+                "duration": 24,
+                "format": "TV",
+                "relations": {
+                    "edges": [{ "relationType": "SEQUEL", "node": { "id": 2, "format": "TV" } }]
+                }
+            }
+        }
+    }"#;
+
+    const SEASON_2_JSON: &str = r#"{
+        "data": {
+            "Media": {
+                "id": 2,
+                "title": { "romaji": "Series 2nd Season", "userPreferred": "Series 2nd Season" },
+                "episodes": 12,
+                "duration": 24,
+                "format": "TV",
+                "relations": {
+                    "edges": [{ "relationType": "PREQUEL", "node": { "id": 1, "format": "TV" } }]
+                }
+            }
+        }
+    }"#;
+
+    /// Stands in for AniList while recording, answering info queries for the two seasons of a series.
+    #[derive(Debug)]
+    struct SeasonTransport;
+
+    impl Transport for SeasonTransport {
+        fn send(&self, request: Request) -> anime::Result<Response> {
+            let body = request.body.unwrap_or_default();
+            let body = String::from_utf8_lossy(&body);
+
+            let json = if body.contains(r#""variables":{"id":2}"#) {
+                SEASON_2_JSON
+            } else {
+                SEASON_1_JSON
+            };
+
+            Ok(Response {
+                status: 200,
+                headers: vec![("Content-Type".into(), "application/json".into())],
+                body: json.as_bytes().to_vec(),
+            })
+        }
+    }
+
+    fn record_cassette(path: &Path) -> Cassette {
+        let client = Client::with_transport(
+            ClientConfig::default(),
+            Recorder::new(SeasonTransport, path.to_path_buf()),
+        );
+
+        let anilist = AniList::unauthenticated().with_client(client);
+
+        anilist.search_info_by_id(1).unwrap();
+        anilist.search_info_by_id(2).unwrap();
+
+        Cassette::load(path).unwrap()
+    }
+
+    #[test]
+    fn merged_seasons_are_resolved_from_a_replay() {
+        let dir = std::env::temp_dir().join(format!("anup-split-{}", std::process::id()));
+        let series_dir = dir.join("Series");
+        fs::create_dir_all(&series_dir).unwrap();
+
+        for episode in 1..=24 {
+            let filename = format!("[Group] Series - {:02}.mkv", episode);
+            fs::write(series_dir.join(filename), "").unwrap();
+        }
+
+        let cassette = record_cassette(&dir.join("cassette.json"));
+        let remote = AsyncRemote::new(Remote::from(Replay::new(cassette)));
+        let config = Config::new(&dir);
+
+        let data = SplitData {
+            id: 1,
+            path: SeriesPath::new(series_dir.as_path(), &config),
+            parser: EpisodeParser::Default,
+        };
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let merged = rt
+            .block_on(MergedSeries::resolve(data, &remote, &config))
+            .unwrap();
+
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(merged.len(), 1);
+
+        let resolved = match &merged[0] {
+            MergedSeries::Resolved(resolved) => resolved,
+            MergedSeries::Failed(kind) => panic!("failed to resolve {:?}", kind),
+        };
+
+        assert_eq!(resolved.info.id, 2);
+        assert_eq!(resolved.actions.len(), 12);
+        assert_eq!(resolved.actions[0].old_name, "[Group] Series - 13.mkv");
+        assert_eq!(resolved.actions[0].new_name, "Series 2nd Season - 01.mkv");
+    }
+}
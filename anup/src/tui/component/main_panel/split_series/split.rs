@@ -139,7 +139,7 @@ impl Component for SplitPanel {
                 };
 
                 Ok(SplitResult::AddSeries(
-                    series.info.clone(),
+                    series.info.clone().into(),
                     series.out_dir.clone(),
                 ))
             }
@@ -163,5 +163,5 @@ impl Component for SplitPanel {
 pub enum SplitResult {
     Ok,
     Reset,
-    AddSeries(RemoteInfo, SeriesPath),
+    AddSeries(Box<RemoteInfo>, SeriesPath),
 }
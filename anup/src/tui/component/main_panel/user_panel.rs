@@ -1,18 +1,24 @@
 use super::{Component, ShouldReset};
+use crate::callback::{CallbackListener, Credential};
 use crate::try_opt_r;
 use crate::tui::component::input::{Input, InputFlags};
+use crate::tui::component::prompt::log::LogKind;
 use crate::tui::UIState;
 use crate::user::{RemoteType, UserInfo};
 use crate::{file::SerializedFile, key::Key};
 use crate::{
-    remote::{RemoteLogin, RemoteStatus},
+    remote::{self, RemoteLogin, RemoteStatus},
     tui::state::SharedState,
+    util::{arc_mutex, ArcMutex, ScopedTask},
 };
 use anime::remote::anilist::AniList;
-use anime::remote::{AccessToken, Remote, RemoteService};
+use anime::remote::myanimelist::{MyAnimeList, PkceChallenge};
+use anime::remote::{AccessToken, Remote, RemoteService, TokenGrant};
 use anyhow::{anyhow, Context, Result};
 use crossterm::event::KeyCode;
 use std::process::Command;
+use std::sync::Arc;
+use tokio::task;
 use tui::layout::{Alignment, Direction, Rect};
 use tui::style::Color;
 use tui::terminal::Frame;
@@ -29,8 +35,18 @@ pub struct UserPanel {
     selected_user: WrappingIndex,
     selected_service: SelectableEnum<RemoteType>,
     token_input: Input,
+    password_input: Input,
+    selected_input: AddUserInput,
+    /// The PKCE challenge used to generate the last auth URL for `MyAnimeList`.
+    mal_challenge: Option<PkceChallenge>,
     current_panel: SelectedPanel,
+    /// The status of the user being added from the inputs, which is retrieved in the background.
+    add_status: ArcMutex<AddUserStatus>,
     state: SharedState,
+    #[allow(dead_code)]
+    add_task: Option<ScopedTask<()>>,
+    #[allow(dead_code)]
+    callback_task: Option<ScopedTask<()>>,
 }
 
 impl UserPanel {
@@ -38,38 +54,127 @@ impl UserPanel {
         Self {
             selected_user: WrappingIndex::new(0),
             selected_service: SelectableEnum::new(),
-            token_input: Input::new(InputFlags::empty(), "Paste Token"),
+            token_input: Input::new(InputFlags::empty(), RemoteType::AniList.auth_input_label()),
+            password_input: Input::new(InputFlags::MASKED, "Password"),
+            selected_input: AddUserInput::Token,
+            mal_challenge: None,
             current_panel: SelectedPanel::SelectUser,
+            add_status: arc_mutex(AddUserStatus::Idle),
             state,
+            add_task: None,
+            callback_task: None,
         }
     }
 
     fn add_user_from_inputs(&mut self, state: &mut UIState) -> Result<()> {
-        use anime::remote::anilist::Auth;
-
         let token_text = self.token_input.text();
 
-        if token_text.is_empty() {
+        if token_text.is_empty() || self.is_adding_user() {
             return Ok(());
         }
 
         match self.selected_service.selected() {
-            service @ RemoteType::AniList => {
+            RemoteType::AniList => {
                 let token = AccessToken::encode(token_text);
-                let auth = Auth::retrieve(token.clone()).context("failed to get new user auth")?;
 
-                let info = UserInfo::new(service, &auth.user.name);
+                self.spawn_add_user(move || {
+                    let (info, remote) = anilist_user(&token)?;
+                    Ok((info, token.into(), remote))
+                });
+
+                Ok(())
+            }
+            service @ RemoteType::Kitsu => {
+                use anime::remote::kitsu::{Auth, Kitsu};
+
+                let password = self.password_input.text();
+
+                if password.is_empty() {
+                    self.selected_input = AddUserInput::Password;
+                    return Ok(());
+                }
+
+                let username = token_text.to_string();
+                let password = password.to_string();
+
+                self.spawn_add_user(move || {
+                    let grant = Auth::login(&username, &password).context("failed to login")?;
+                    let auth = Auth::retrieve(grant.token.clone())
+                        .context("failed to get new user auth")?;
+
+                    let info = UserInfo::new(service, &auth.user.name);
+                    Ok((info, grant, Kitsu::authenticated(auth).into()))
+                });
+
+                Ok(())
+            }
+            RemoteType::MyAnimeList => {
+                let client_id = state.config.mal_client_id()?.to_string();
+
+                let challenge = self
+                    .mal_challenge
+                    .clone()
+                    .ok_or_else(|| anyhow!("must open the auth URL with Ctrl + O first"))?;
 
-                state.remote = RemoteStatus::LoggedIn(AniList::Authenticated(auth).into());
-                state.users.add_and_set_last(info, token);
-                state.users.save().context("failed to save new user")?;
+                let code = parse_auth_code(token_text).to_string();
 
-                self.token_input.clear();
+                self.spawn_add_user(move || mal_user(&client_id, &code, &challenge, None));
                 Ok(())
             }
         }
     }
 
+    /// Authenticate a new user with `login` in the background, and add them once it succeeds.
+    ///
+    /// The inputs are kept until then, so they can be corrected if the login fails.
+    fn spawn_add_user<F>(&mut self, login: F)
+    where
+        F: FnOnce() -> Result<(UserInfo, TokenGrant, Remote)> + Send + 'static,
+    {
+        *self.add_status.lock() = AddUserStatus::Adding;
+
+        let add_status = Arc::clone(&self.add_status);
+        let shared_state = self.state.clone();
+
+        let task = task::spawn(async move {
+            let result = task::spawn_blocking(login)
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result);
+
+            let added = add_login_result(result, &shared_state);
+
+            *add_status.lock() = if added {
+                AddUserStatus::Added
+            } else {
+                AddUserStatus::Idle
+            };
+        });
+
+        self.add_task = Some(task.into());
+    }
+
+    fn is_adding_user(&self) -> bool {
+        *self.add_status.lock() == AddUserStatus::Adding
+    }
+
+    /// Clear the inputs if the user being added from them was just added.
+    fn clear_added_user(&mut self) {
+        let mut status = self.add_status.lock();
+
+        if *status != AddUserStatus::Added {
+            return;
+        }
+
+        *status = AddUserStatus::Idle;
+        drop(status);
+
+        self.token_input.clear();
+        self.password_input.clear();
+        self.selected_input = AddUserInput::Token;
+        self.mal_challenge = None;
+    }
+
     fn selected_user<'a>(&'a self, state: &'a UIState) -> Option<(&'a UserInfo, &'a AccessToken)> {
         let index = self.selected_user.get();
         state.users.get().iter().nth(index)
@@ -84,7 +189,7 @@ impl UserPanel {
         let remote = state.remote.get_logged_in()?;
 
         if user.is_logged_in(remote) {
-            state.remote = RemoteStatus::LoggedIn(Remote::offline());
+            state.remote = RemoteStatus::logged_in(remote::offline(Some(user.service)));
         }
 
         state.users.remove(&user);
@@ -97,39 +202,119 @@ impl UserPanel {
 
     fn login_as_selected_user(&mut self, state: &mut UIState) -> Result<()> {
         let (info, token) = try_opt_r!(self.selected_user(state));
+        let info = info.to_owned();
 
-        match info.service {
-            RemoteType::AniList => {
-                self.state.login_to_remote_async(RemoteLogin::AniList(
-                    info.username.clone(),
-                    token.clone(),
-                ));
-
-                state.users.last_used = Some(info.to_owned());
-                state.users.save()?;
-            }
-        }
+        self.state
+            .login_to_remote_async(RemoteLogin::from_user(info.clone(), token.clone()));
 
-        Ok(())
+        state.users.last_used = Some(info);
+        state.users.save()
     }
 
-    fn open_auth_url(&self) -> Result<()> {
+    fn open_auth_url(&mut self, state: &UIState) -> Result<()> {
         let url = match self.selected_service.selected() {
             RemoteType::AniList => anime::remote::anilist::auth_url(crate::ANILIST_CLIENT_ID),
+            RemoteType::Kitsu => return Ok(()),
+            RemoteType::MyAnimeList => {
+                let client_id = state.config.mal_client_id()?;
+                let challenge = PkceChallenge::new();
+                let url = anime::remote::myanimelist::auth_url(client_id, &challenge, None);
+
+                self.mal_challenge = Some(challenge);
+                url
+            }
         };
 
-        #[cfg(target_os = "linux")]
-        let opener = "xdg-open";
-        #[cfg(target_os = "macos")]
-        let opener = "open";
-        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
-        compile_error!("must specify URL opener for this platform");
-
-        Command::new(opener)
-            .arg(url)
-            .spawn()
-            .with_context(|| anyhow!("failed to open URL in browser with {}", opener))
-            .map(|_| ())
+        open_url(url)
+    }
+
+    /// Open the auth URL of the selected service and wait for it to redirect back to a local listener in the background.
+    ///
+    /// The user is added once the redirect is received, so nothing needs to be pasted.
+    fn login_through_browser(&mut self, state: &mut UIState) -> Result<()> {
+        let service = self.selected_service.selected();
+        let callback = &state.config.login_callback;
+
+        let (url, mal_login) = match service {
+            RemoteType::AniList => {
+                let client_id = callback.anilist_client_id()?;
+                (anime::remote::anilist::auth_url(client_id), None)
+            }
+            RemoteType::Kitsu => return Ok(()),
+            RemoteType::MyAnimeList => {
+                let client_id = state.config.mal_client_id()?.to_string();
+                let challenge = PkceChallenge::new();
+                let redirect_url = callback.redirect_url();
+
+                // The application may have other redirect URLs registered for pasting the code, so the listener
+                // has to be asked for explicitly
+                let url = anime::remote::myanimelist::auth_url(
+                    &client_id,
+                    &challenge,
+                    Some(&redirect_url),
+                );
+
+                (
+                    url,
+                    Some(MalLogin {
+                        client_id,
+                        challenge,
+                        redirect_url,
+                    }),
+                )
+            }
+        };
+
+        // A previous login attempt may still be listening on the same port
+        self.callback_task = None;
+
+        // Listening before opening the URL ensures the redirect can't be missed
+        let listener = CallbackListener::bind(callback.port)?;
+        open_url(listener.auth_url(&url))?;
+
+        let shared_state = self.state.clone();
+
+        let task = task::spawn(async move {
+            let result =
+                Self::add_user_from_callback(listener, service, mal_login, &shared_state).await;
+
+            add_login_result(result, &shared_state);
+        });
+
+        self.callback_task = Some(task.into());
+
+        state
+            .log
+            .push(LogKind::Info, "waiting for login in browser");
+
+        Ok(())
+    }
+
+    async fn add_user_from_callback(
+        listener: CallbackListener,
+        service: RemoteType,
+        mal_login: Option<MalLogin>,
+        shared_state: &SharedState,
+    ) -> Result<(UserInfo, TokenGrant, Remote)> {
+        let credential = listener
+            .wait(|err| shared_state.lock().get_mut().log.push_error(&err))
+            .await?;
+
+        task::spawn_blocking(move || match (service, credential, mal_login) {
+            (RemoteType::AniList, Credential::Token(token), _) => {
+                let token = AccessToken::encode(token);
+                let (info, remote) = anilist_user(&token)?;
+                Ok((info, token.into(), remote))
+            }
+            (RemoteType::MyAnimeList, Credential::Code(code), Some(login)) => mal_user(
+                &login.client_id,
+                &code,
+                &login.challenge,
+                Some(&login.redirect_url),
+            ),
+            _ => Err(anyhow!("received unexpected login redirect")),
+        })
+        .await?
     }
 
     fn draw_add_user_panel<B>(&mut self, rect: Rect, frame: &mut Frame<B>)
@@ -137,12 +322,19 @@ impl UserPanel {
         B: Backend,
     {
         let is_panel_selected = self.current_panel == SelectedPanel::AddUser;
+        let service = self.selected_service.selected();
 
         let block = block::selectable("Add User", is_panel_selected);
         let block_area = block.inner(rect);
 
         frame.render_widget(block, rect);
 
+        let password_lines = if service.needs_password() {
+            Input::DRAW_LINES_REQUIRED
+        } else {
+            0
+        };
+
         let vert_split = SimpleLayout::new(Direction::Vertical)
             .horizontal_margin(2)
             .vertical_margin(1)
@@ -151,6 +343,8 @@ impl UserPanel {
                 &[
                     // Token input
                     BasicConstraint::Length(Input::DRAW_LINES_REQUIRED),
+                    // Password input
+                    BasicConstraint::Length(password_lines),
                     // Spacer
                     BasicConstraint::Length(1),
                     // Service selection
@@ -162,13 +356,20 @@ impl UserPanel {
                 ],
             );
 
-        self.token_input.set_selected(is_panel_selected);
+        self.token_input
+            .set_selected(is_panel_selected && self.selected_input == AddUserInput::Token);
         self.token_input.draw(vert_split[0], frame);
 
+        if service.needs_password() {
+            self.password_input
+                .set_selected(is_panel_selected && self.selected_input == AddUserInput::Password);
+            self.password_input.draw(vert_split[1], frame);
+        }
+
         let services_block = block::selectable("Service", is_panel_selected);
-        let services_block_area = services_block.inner(vert_split[2]);
+        let services_block_area = services_block.inner(vert_split[3]);
 
-        frame.render_widget(services_block, vert_split[2]);
+        frame.render_widget(services_block, vert_split[3]);
 
         let services = RemoteType::items()
             .iter()
@@ -182,16 +383,23 @@ impl UserPanel {
 
         frame.render_widget(services_widget, services_block_area);
 
+        let (first_hint, second_hint) = if self.is_adding_user() {
+            ("Logging In..", "")
+        } else if service.needs_password() {
+            ("Tab - Next input", "")
+        } else {
+            ("Ctrl + O - Open auth URL", "Ctrl + L - Login in browser")
+        };
+
         let hint_fragments = [
-            Fragment::span(text::hint("Ctrl + O")),
+            Fragment::span(text::hint(first_hint)),
             Fragment::Line,
-            Fragment::span(text::hint("-")),
             Fragment::Line,
-            Fragment::span(text::hint("Open auth URL")),
+            Fragment::span(text::hint(second_hint)),
         ];
 
         let hint_widget = TextFragments::new(&hint_fragments).alignment(Alignment::Center);
-        frame.render_widget(hint_widget, vert_split[4]);
+        frame.render_widget(hint_widget, vert_split[5]);
     }
 
     fn draw_user_selection_panel<B>(&self, state: &UIState, rect: Rect, frame: &mut Frame<B>)
@@ -246,7 +454,7 @@ impl UserPanel {
                 let widget = TextFragments::new(&fragments).alignment(Alignment::Center);
                 frame.render_widget(widget, layout[3]);
             }
-            RemoteStatus::LoggedIn(remote) if remote.is_offline() => {
+            RemoteStatus::LoggedIn(remote) if remote.get().is_offline() => {
                 let widget =
                     SimpleText::new(yellow_text("Currently Offline")).alignment(Alignment::Center);
                 frame.render_widget(widget, layout[3]);
@@ -278,17 +486,33 @@ impl UserPanel {
                 Style::default()
             };
 
+            let token_status = state.users.token_status(user);
+
+            let login = if token_status.needs_login() {
+                Span::styled("Login again", style::fg(Color::Red))
+            } else if let Some(days) = token_status.expires_soon() {
+                Span::styled(format!("Expires in {}d", days), style::fg(Color::Yellow))
+            } else {
+                Span::styled("OK", style)
+            };
+
             [
                 Span::styled(user.username.as_str(), style),
                 Span::styled(user.service.as_str(), style),
+                login,
             ]
         });
 
-        let header = [Span::raw("Username"), Span::raw("Service")];
+        let header = [
+            Span::raw("Username"),
+            Span::raw("Service"),
+            Span::raw("Login"),
+        ];
 
         let layout = [
-            BasicConstraint::Percentage(50),
-            BasicConstraint::Percentage(50),
+            BasicConstraint::Percentage(40),
+            BasicConstraint::Percentage(30),
+            BasicConstraint::Percentage(30),
         ];
 
         let users_widget = SimpleTable::new(users, &layout)
@@ -300,6 +524,8 @@ impl UserPanel {
     }
 
     pub fn draw<B: Backend>(&mut self, state: &UIState, rect: Rect, frame: &mut Frame<B>) {
+        self.clear_added_user();
+
         let horiz_split = SimpleLayout::new(Direction::Horizontal).split(
             rect,
             &[
@@ -321,7 +547,18 @@ impl Component for UserPanel {
         match *key {
             KeyCode::Esc => Ok(ShouldReset::Yes),
             KeyCode::Tab => {
-                self.current_panel.increment();
+                let needs_password = self.selected_service.selected().needs_password();
+
+                match (self.current_panel, self.selected_input) {
+                    (SelectedPanel::AddUser, AddUserInput::Token) if needs_password => {
+                        self.selected_input = AddUserInput::Password;
+                    }
+                    _ => {
+                        self.selected_input = AddUserInput::Token;
+                        self.current_panel.increment();
+                    }
+                }
+
                 Ok(ShouldReset::No)
             }
             _ => match self.current_panel {
@@ -344,7 +581,8 @@ impl Component for UserPanel {
                         Ok(ShouldReset::No)
                     }
                     KeyCode::Char('o') => {
-                        state.remote = RemoteStatus::LoggedIn(Remote::offline());
+                        let service = state.users.last_used_service();
+                        state.remote = RemoteStatus::logged_in(remote::offline(service));
                         Ok(ShouldReset::Yes)
                     }
                     _ => Ok(ShouldReset::No),
@@ -357,10 +595,18 @@ impl Component for UserPanel {
                             _ => unreachable!(),
                         }
 
+                        self.token_input.label =
+                            self.selected_service.selected().auth_input_label();
+                        self.password_input.clear();
+                        self.selected_input = AddUserInput::Token;
                         Ok(ShouldReset::No)
                     }
                     KeyCode::Char('o') if key.ctrl_pressed() => {
-                        self.open_auth_url()?;
+                        self.open_auth_url(state)?;
+                        Ok(ShouldReset::No)
+                    }
+                    KeyCode::Char('l') if key.ctrl_pressed() => {
+                        self.login_through_browser(state)?;
                         Ok(ShouldReset::No)
                     }
                     KeyCode::Enter => {
@@ -368,7 +614,11 @@ impl Component for UserPanel {
                         Ok(ShouldReset::No)
                     }
                     _ => {
-                        self.token_input.process_key(key);
+                        match self.selected_input {
+                            AddUserInput::Token => self.token_input.process_key(key),
+                            AddUserInput::Password => self.password_input.process_key(key),
+                        }
+
                         Ok(ShouldReset::No)
                     }
                 },
@@ -377,6 +627,116 @@ impl Component for UserPanel {
     }
 }
 
+/// Add a new user that was just authenticated and switch to them.
+fn add_user(info: UserInfo, grant: TokenGrant, remote: Remote, state: &mut UIState) -> Result<()> {
+    state.remote = RemoteStatus::logged_in(remote);
+    state.users.add_and_set_last(info, grant);
+    state.users.save().context("failed to save new user")
+}
+
+/// Add the user a login attempt authenticated, or log why it failed.
+///
+/// Returns true if the user was added.
+fn add_login_result(
+    result: Result<(UserInfo, TokenGrant, Remote)>,
+    shared_state: &SharedState,
+) -> bool {
+    let mut state = shared_state.lock();
+    let state = state.get_mut();
+
+    let result = result.and_then(|(info, grant, remote)| {
+        let username = info.username.clone();
+        add_user(info, grant, remote, state)?;
+        Ok(username)
+    });
+
+    match result {
+        Ok(username) => {
+            state
+                .log
+                .push(LogKind::Info, format!("logged in as {}", username));
+            true
+        }
+        Err(err) => {
+            state.log.push_error(&err);
+            false
+        }
+    }
+}
+
+/// Retrieve the account of a new AniList user with their access `token`.
+fn anilist_user(token: &AccessToken) -> Result<(UserInfo, Remote)> {
+    use anime::remote::anilist::Auth;
+
+    let auth = Auth::retrieve(token.clone()).context("failed to get new user auth")?;
+    let info = UserInfo::new(RemoteType::AniList, &auth.user.name);
+
+    Ok((info, AniList::authenticated(auth).into()))
+}
+
+/// Exchange the authorization `code` of a new MyAnimeList user for an access token and retrieve their account.
+///
+/// `redirect_url` must be the one the auth URL was generated with, if any.
+fn mal_user(
+    client_id: &str,
+    code: &str,
+    challenge: &PkceChallenge,
+    redirect_url: Option<&str>,
+) -> Result<(UserInfo, TokenGrant, Remote)> {
+    use anime::remote::myanimelist::Auth;
+
+    let grant = Auth::exchange_code(client_id, code, challenge, redirect_url)
+        .context("failed to get access token from auth code")?;
+
+    let auth =
+        Auth::retrieve(client_id, grant.token.clone()).context("failed to get new user auth")?;
+
+    let info = UserInfo::new(RemoteType::MyAnimeList, &auth.user.name);
+    let remote = MyAnimeList::authenticated(client_id, auth);
+
+    Ok((info, grant, remote.into()))
+}
+
+fn open_url(url: String) -> Result<()> {
+    #[cfg(target_os = "linux")]
+    let opener = "xdg-open";
+    #[cfg(target_os = "macos")]
+    let opener = "open";
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    compile_error!("must specify URL opener for this platform");
+
+    Command::new(opener)
+        .arg(url)
+        .spawn()
+        .with_context(|| anyhow!("failed to open URL in browser with {}", opener))
+        .map(|_| ())
+}
+
+/// Extract the authorization code from `input`.
+///
+/// `input` can either be the code itself, or the entire URL that was redirected to after authorizing.
+fn parse_auth_code(input: &str) -> &str {
+    input
+        .split(&['?', '&'][..])
+        .find_map(|param| param.strip_prefix("code="))
+        .unwrap_or(input)
+}
+
+/// The details of a MyAnimeList login through the browser that are needed to exchange its code.
+struct MalLogin {
+    client_id: String,
+    challenge: PkceChallenge,
+    redirect_url: String,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum AddUserStatus {
+    Idle,
+    Adding,
+    /// The user was added, but the inputs haven't been cleared yet.
+    Added,
+}
+
 #[derive(Copy, Clone, PartialEq)]
 enum SelectedPanel {
     SelectUser,
@@ -396,3 +756,9 @@ impl SelectedPanel {
         *self = self.next();
     }
 }
+
+#[derive(Copy, Clone, PartialEq)]
+enum AddUserInput {
+    Token,
+    Password,
+}
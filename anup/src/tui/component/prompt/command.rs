@@ -285,13 +285,25 @@ pub enum Command {
     SyncFromRemote,
     /// Syncronize the selected season from the remote service.
     SyncToRemote,
+    /// Syncronize every season without unsynced changes from the remote service.
+    SyncAllFromRemote,
     /// Rate the selected season.
     Score(String),
     /// Set the watch status of the selected season.
     Status(anime::remote::Status),
+    /// Set the notes of the selected season, or clear them if empty.
+    Notes(String),
+    /// Set whether or not the selected season is private.
+    Private(bool),
+    /// Set whether or not the selected season is hidden from status lists.
+    Hidden(bool),
+    /// Set the custom lists the selected season is on.
+    CustomLists(Vec<String>),
+    /// Set whether or not the selected season is one of the user's favourites, or toggle it if `None`.
+    Favourite(Option<bool>),
 }
 
-impl_command_matching!(Command, 6,
+impl_command_matching!(Command, 12,
     PlayerArgs(_) => {
         name: "args",
         usage: "<player args>",
@@ -325,6 +337,12 @@ impl_command_matching!(Command, 6,
         min_args: 0,
         fn: |_, _| Ok(Command::SyncToRemote),
     },
+    SyncAllFromRemote => {
+        name: "syncallfromremote",
+        usage: "",
+        min_args: 0,
+        fn: |_, _| Ok(Command::SyncAllFromRemote),
+    },
     Score(_) => {
         name: "rate",
         usage: "<0-100>",
@@ -356,6 +374,53 @@ impl_command_matching!(Command, 6,
             Ok(Command::Status(status))
         },
     },
+    Notes(_) => {
+        name: "notes",
+        usage: "<notes>",
+        min_args: 0,
+        fn: |args: &[&str], _| Ok(Command::Notes(args.join(" "))),
+    },
+    Private(_) => {
+        name: "private",
+        usage: "<on | off>",
+        min_args: 1,
+        fn: |args: &[&str], _| parse_toggle(args[0]).map(Command::Private),
+    },
+    Hidden(_) => {
+        name: "hidden",
+        usage: "<on | off>",
+        min_args: 1,
+        fn: |args: &[&str], _| parse_toggle(args[0]).map(Command::Hidden),
+    },
+    CustomLists(_) => {
+        name: "lists",
+        usage: "<custom list names, quoted or separated by commas>",
+        min_args: 0,
+        fn: |args: &[&str], _| {
+            // Names with spaces can be quoted, or every name can be separated by a comma instead
+            let lists = if args.iter().any(|arg| arg.contains(',')) {
+                args.join(" ")
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|list| !list.is_empty())
+                    .map(Into::into)
+                    .collect()
+            } else {
+                args.iter().map(|&list| list.to_string()).collect()
+            };
+
+            Ok(Command::CustomLists(lists))
+        },
+    },
+    Favourite(_) => {
+        name: "fav",
+        usage: "[on | off]",
+        min_args: 0,
+        fn: |args: &[&str], _| match args.first() {
+            Some(arg) => parse_toggle(arg).map(|fav| Command::Favourite(Some(fav))),
+            None => Ok(Command::Favourite(None)),
+        },
+    },
 );
 
 impl Command {
@@ -373,6 +438,15 @@ impl Command {
     }
 }
 
+/// Parse an on / off argument of a command.
+fn parse_toggle(value: &str) -> Result<bool> {
+    match value.to_ascii_lowercase().as_ref() {
+        "on" | "y" | "yes" | "true" => Ok(true),
+        "off" | "n" | "no" | "false" => Ok(false),
+        _ => Err(anyhow!("unknown argument: {}", value)),
+    }
+}
+
 /// Indicates which way to advance the episode count of a season.
 #[derive(Copy, Clone)]
 #[cfg_attr(test, derive(Debug))]
@@ -470,6 +544,28 @@ mod tests {
         );
 
         test_command!("status watching", Command::Status(Status::Watching));
+        test_command!("private on", Command::Private(true));
+        test_command!("hidden off", Command::Hidden(false));
+        test_command!("fav", Command::Favourite(None));
+        test_command!("fav off", Command::Favourite(Some(false)));
+
+        match enter_command("notes \"rewatch with subs\"") {
+            Command::Notes(notes) if notes == "rewatch with subs" => (),
+            other => expected!(other, Command::Notes("rewatch with subs".into())),
+        }
+
+        for &input in &[
+            "lists \"Summer 2021\" Favourites",
+            "lists Summer 2021, Favourites",
+        ] {
+            match enter_command(input) {
+                Command::CustomLists(lists) if lists == ["Summer 2021", "Favourites"] => (),
+                other => expected!(
+                    other,
+                    Command::CustomLists(vec!["Summer 2021".into(), "Favourites".into()])
+                ),
+            }
+        }
     }
 
     #[test]
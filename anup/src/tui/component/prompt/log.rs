@@ -15,6 +15,7 @@ use tui_utils::{
 #[derive(Copy, Clone)]
 pub enum LogKind {
     Info,
+    Warning,
     Error,
     Context,
 }
@@ -23,6 +24,7 @@ impl<'a> Into<Span<'a>> for LogKind {
     fn into(self) -> Span<'a> {
         match self {
             Self::Info => Span::styled("info: ", style::fg(Color::Green)),
+            Self::Warning => Span::styled("warning: ", style::fg(Color::Yellow)),
             Self::Error => Span::styled("error: ", style::fg(Color::Red)),
             Self::Context => Span::styled("^ ", style::fg(Color::Yellow)),
         }
@@ -2,6 +2,7 @@ use crate::tui::state::{InputState, UIState};
 use crate::{key::Key, series::LoadedSeries};
 use anime::remote::Status;
 use crossterm::event::KeyCode;
+use std::fmt::Write;
 use tui::backend::Backend;
 use tui::layout::Rect;
 use tui::style::Color;
@@ -12,6 +13,9 @@ use tui_utils::{
     widgets::SimpleList,
 };
 
+/// The text shown before the name of series the user has marked as a favourite.
+const FAVOURITE_MARKER: &str = "\u{2665} ";
+
 pub struct SeriesList;
 
 impl SeriesList {
@@ -26,7 +30,39 @@ impl SeriesList {
                     Status::PlanToWatch => Color::Gray,
                 };
 
-                text::with_color(series.data.config.nickname.as_str(), color)
+                let info = &series.data.info;
+                let nickname = series.data.config.nickname.as_str();
+                let favourite = series.data.entry.favourite();
+
+                if !info.is_airing() && !favourite {
+                    return text::with_color(nickname, color);
+                }
+
+                let mut name = String::with_capacity(nickname.len() + 16);
+
+                if favourite {
+                    name.push_str(FAVOURITE_MARKER);
+                }
+
+                name.push_str(nickname);
+
+                if info.is_airing() {
+                    // Show the number of episodes that have aired, are on disk, and have been watched
+                    let aired = info
+                        .aired_episodes()
+                        .map_or_else(|| "?".into(), |aired| aired.to_string());
+
+                    write!(
+                        name,
+                        " [{}/{}/{}]",
+                        aired,
+                        series.episodes.len(),
+                        series.data.entry.watched_episodes()
+                    )
+                    .ok();
+                }
+
+                text::with_color(name, color)
             }
             LoadedSeries::Partial(data, _) => {
                 text::with_color(data.config.nickname.as_str(), Color::LightRed)
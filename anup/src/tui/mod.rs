@@ -3,8 +3,9 @@ mod state;
 
 use self::state::{InputState, Reactive, UIEvents, UIState};
 use crate::key::Key;
+use crate::remote::{self, RemoteLogin, RemoteStatus};
 use crate::Args;
-use crate::{file::SerializedFile, remote::RemoteLogin, try_opt_r, user::Users};
+use crate::{file::SerializedFile, try_opt_r, user::Users};
 use anime::remote::ScoreParser;
 use anyhow::{anyhow, Context, Result};
 use component::prompt::command::Command;
@@ -14,7 +15,7 @@ use component::series_list::SeriesList;
 use component::Component;
 use component::{main_panel::MainPanel, prompt::command::CommandPrompt};
 use crossterm::{event::KeyCode, terminal};
-use state::{SharedState, UIErrorKind, UIEvent};
+use state::{SharedState, SyncDirection, UIErrorKind, UIEvent};
 use std::{io, sync::Arc};
 use tokio::sync::Notify;
 use tui::{backend::CrosstermBackend, layout::Direction, Terminal};
@@ -43,6 +44,11 @@ impl UI {
         let events = UIEvents::new().context("UI events init")?;
 
         let mut state = UIState::init().context("UI state init")?;
+        state.config.record_requests = args.record.clone();
+
+        if let Some(path) = &args.replay {
+            state.remote = RemoteStatus::logged_in(remote::replay(path)?);
+        }
 
         state
             .select_initial_series(args)
@@ -53,9 +59,9 @@ impl UI {
 
         let panels = Panels::init(&shared_state);
 
-        if !args.offline {
+        if !args.offline && args.replay.is_none() {
             if let Some((user, token)) = Users::load_or_create()?.take_last_used_user() {
-                shared_state.login_to_remote_async(RemoteLogin::AniList(user.username, token));
+                shared_state.login_to_remote_async(RemoteLogin::from_user(user, token));
             }
         }
 
@@ -117,9 +123,12 @@ impl UI {
 
         let result = match event {
             UIEvent::Key(key) => self.panels.process_key(key, state).await,
-            UIEvent::StateChange | UIEvent::Resize => CycleResult::Ok,
+            UIEvent::StateChange => self.panels.process_state_change(state),
+            UIEvent::Resize => CycleResult::Ok,
         };
 
+        self.panels.main_panel.switch_to_queued_conflict(state);
+
         if let Err(err) = self.panels.draw(state, &mut self.terminal) {
             return CycleResult::Error(err);
         }
@@ -129,7 +138,15 @@ impl UI {
 
     pub fn exit(mut self) -> Result<()> {
         self.terminal.clear().ok();
-        terminal::disable_raw_mode().map_err(Into::into)
+        terminal::disable_raw_mode()?;
+
+        let state = self.state.lock();
+
+        if let Ok(remote) = state.get().remote.get_logged_in() {
+            remote::save_cache(remote)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -160,7 +177,7 @@ impl Panels {
                 match $result {
                     Ok(value) => value,
                     Err(err) => {
-                        state.log.push_error(&err);
+                        state.report_error(err);
                         return CycleResult::Ok;
                     }
                 }
@@ -189,8 +206,21 @@ impl Panels {
                     capture!(self.main_panel.switch_to_delete_series(state))
                 }
                 KeyCode::Char('u') => self.main_panel.switch_to_user_panel(state),
-                KeyCode::Char('s') => {
-                    capture!(self.main_panel.switch_to_split_series(state))
+                KeyCode::Char('s') => self.main_panel.switch_to_split_series(state),
+                KeyCode::Char('I') => {
+                    capture!(self.main_panel.switch_to_import_list(state))
+                }
+                KeyCode::Char('C') => {
+                    capture!(self.main_panel.switch_to_calendar(state))
+                }
+                KeyCode::Char('F') => {
+                    capture!(self.main_panel.switch_to_franchise(state))
+                }
+                KeyCode::Char('R') => {
+                    capture!(self.main_panel.switch_to_recommendations(state))
+                }
+                KeyCode::Char('S') => {
+                    capture!(self.main_panel.switch_to_season(state))
                 }
                 KeyCode::Char(COMMAND_KEY) => state.input_state = InputState::EnteringCommand,
                 _ => SeriesList::process_key(key, state),
@@ -207,7 +237,7 @@ impl Panels {
 
                 match capture!(result) {
                     InputResult::Command(cmd) => {
-                        capture!(Self::process_command(cmd, state))
+                        capture!(Self::process_command(cmd, state, &self.state))
                     }
                     InputResult::Done | InputResult::Continue => (),
                 }
@@ -217,6 +247,14 @@ impl Panels {
         CycleResult::Ok
     }
 
+    fn process_state_change(&mut self, state: &mut UIState) -> CycleResult {
+        if let Err(err) = self.main_panel.process_state_change(state) {
+            state.report_error(err);
+        }
+
+        CycleResult::Ok
+    }
+
     fn draw(&mut self, state: &UIState, terminal: &mut CrosstermTerminal) -> Result<()> {
         terminal
             .draw(|mut frame| {
@@ -252,7 +290,11 @@ impl Panels {
             .map_err(Into::into)
     }
 
-    fn process_command(command: Command, state: &mut UIState) -> Result<()> {
+    fn process_command(
+        command: Command,
+        state: &mut UIState,
+        shared_state: &SharedState,
+    ) -> Result<()> {
         let remote = &mut state.remote;
         let config = &state.config;
         let db = &state.db;
@@ -278,15 +320,18 @@ impl Panels {
             }
             cmd @ Command::SyncFromRemote | cmd @ Command::SyncToRemote => {
                 let series = try_opt_r!(state.series.get_valid_sel_series_mut());
-                let remote = remote.get_logged_in()?;
 
-                match cmd {
-                    Command::SyncFromRemote => series.data.force_sync_from_remote(remote)?,
-                    Command::SyncToRemote => series.data.entry.force_sync_to_remote(remote)?,
+                let direction = match cmd {
+                    Command::SyncFromRemote => SyncDirection::FromRemote,
+                    Command::SyncToRemote => SyncDirection::ForceToRemote,
                     _ => unreachable!(),
-                }
+                };
 
-                series.save(db)?;
+                shared_state.sync_series_async(series.data.info.id, direction);
+                Ok(())
+            }
+            Command::SyncAllFromRemote => {
+                shared_state.sync_all_from_remote_async();
                 Ok(())
             }
             Command::Score(raw_score) => {
@@ -300,19 +345,61 @@ impl Panels {
                 };
 
                 series.data.entry.set_score(score.map(i16::from));
-                series.data.entry.sync_to_remote(remote)?;
                 series.save(db)?;
 
+                shared_state.sync_series_async(series.data.info.id, SyncDirection::ToRemote);
                 Ok(())
             }
             Command::Status(status) => {
                 let series = try_opt_r!(state.series.get_valid_sel_series_mut());
-                let remote = remote.get_logged_in()?;
 
                 series.data.entry.set_status(status, config);
-                series.data.entry.sync_to_remote(remote)?;
                 series.save(db)?;
 
+                shared_state.sync_series_async(series.data.info.id, SyncDirection::ToRemote);
+                Ok(())
+            }
+            Command::Notes(notes) => {
+                let series = try_opt_r!(state.series.get_valid_sel_series_mut());
+
+                series.data.entry.set_notes(notes);
+                series.save(db)?;
+
+                shared_state.sync_series_async(series.data.info.id, SyncDirection::ToRemote);
+                Ok(())
+            }
+            Command::Private(private) => {
+                let series = try_opt_r!(state.series.get_valid_sel_series_mut());
+
+                series.data.entry.set_private(private);
+                series.save(db)?;
+
+                shared_state.sync_series_async(series.data.info.id, SyncDirection::ToRemote);
+                Ok(())
+            }
+            Command::Hidden(hidden) => {
+                let series = try_opt_r!(state.series.get_valid_sel_series_mut());
+
+                series.data.entry.set_hidden(hidden);
+                series.save(db)?;
+
+                shared_state.sync_series_async(series.data.info.id, SyncDirection::ToRemote);
+                Ok(())
+            }
+            Command::CustomLists(lists) => {
+                let series = try_opt_r!(state.series.get_valid_sel_series_mut());
+
+                series.data.entry.set_custom_lists(lists);
+                series.save(db)?;
+
+                shared_state.sync_series_async(series.data.info.id, SyncDirection::ToRemote);
+                Ok(())
+            }
+            Command::Favourite(favourite) => {
+                let series = try_opt_r!(state.series.get_valid_sel_series_mut());
+                let favourite = favourite.unwrap_or_else(|| !series.data.entry.favourite());
+
+                shared_state.set_favourite_async(series.data.info.id, favourite);
                 Ok(())
             }
         }
@@ -1,24 +1,26 @@
-use super::component::prompt::log::Log;
-use crate::user::Users;
+use super::component::prompt::log::{Log, LogKind};
+use crate::series::entry::{EntryConflict, PendingDeletion};
+use crate::user::{UserInfo, Users};
 use crate::{config::Config, util::ArcMutex};
 use crate::{database::Database, series::LastWatched};
+use crate::{err, try_opt_ret, util::arc_mutex};
 use crate::{file::SerializedFile, key::Key};
 use crate::{remote::RemoteLogin, series::info::SeriesInfo};
 use crate::{
-    remote::RemoteStatus,
+    remote::{self, RemoteStatus},
     series::{LoadedSeries, Series, SeriesData},
 };
 use crate::{series::config::SeriesConfig, Args};
-use crate::{try_opt_ret, util::arc_mutex};
-use anime::remote::{anilist::AniList, Remote};
-use anime::{local::SortedEpisodes, remote::anilist::Auth};
-use anyhow::{anyhow, Context, Result};
+use anime::local::SortedEpisodes;
+use anime::remote::{AsyncRemote, RemoteService, SeriesEntry as RemoteEntry, SeriesID, Status};
+use anyhow::{anyhow, Context, Error, Result};
 use chrono::{DateTime, Utc};
 use crossterm::event::{Event, EventStream};
 use futures::{select, FutureExt, StreamExt};
 use parking_lot::MutexGuard;
 use std::{
     borrow::Cow,
+    collections::HashMap,
     mem,
     ops::{Deref, DerefMut},
     sync::Arc,
@@ -67,6 +69,8 @@ pub struct UIState {
     pub users: Users,
     pub remote: RemoteStatus,
     pub db: Database,
+    /// List entry conflicts waiting to be resolved by the user.
+    pub conflicts: Vec<EntryConflict>,
 }
 
 impl UIState {
@@ -85,6 +89,7 @@ impl UIState {
         series.sort_unstable();
 
         let (events_tx, _) = broadcast::channel(8);
+        let remote = remote::offline(users.last_used_service());
 
         Ok(Self {
             series: WrappedSeriesSelection::new(series),
@@ -94,8 +99,9 @@ impl UIState {
             log: Log::new(15),
             config,
             users,
-            remote: RemoteStatus::LoggedIn(Remote::offline()),
+            remote: RemoteStatus::logged_in(remote),
             db,
+            conflicts: Vec::new(),
         })
     }
 
@@ -122,18 +128,10 @@ impl UIState {
         Ok(())
     }
 
-    pub fn add_series<E>(
-        &mut self,
-        config: SeriesConfig,
-        info: SeriesInfo,
-        episodes: E,
-    ) -> Result<()>
+    pub fn add_series<E>(&mut self, data: SeriesData, episodes: E) -> Result<()>
     where
         E: Into<Option<SortedEpisodes>>,
     {
-        let remote = self.remote.get_logged_in()?;
-        let data = SeriesData::from_remote(config, info, remote)?;
-
         let series = match episodes.into() {
             Some(episodes) => LoadedSeries::Complete(Series::with_episodes(data, episodes)),
             None => Series::init(data, &self.config),
@@ -141,6 +139,12 @@ impl UIState {
 
         series.save(&self.db)?;
 
+        // The series may have been deleted from the remote service while offline before being added again, either
+        // by the current user or before the user of each deletion was tracked
+        let id = series.config().id;
+        PendingDeletion::new(id, self.users.last_used.as_ref()).remove(&self.db)?;
+        PendingDeletion::new(id, None).remove(&self.db)?;
+
         let nickname = series.nickname().to_string();
 
         self.series.push(series);
@@ -156,6 +160,73 @@ impl UIState {
         Ok(())
     }
 
+    /// Log `err`, or queue it to be resolved by the user if it's a list entry conflict.
+    pub fn report_error(&mut self, err: Error) {
+        if err::is_invalid_token(&err) {
+            if let Some(user) = self.users.last_used.clone() {
+                self.token_rejected(&user);
+            }
+        }
+
+        let conflict = match err.downcast::<EntryConflict>() {
+            Ok(conflict) => conflict,
+            Err(err) => {
+                self.log.push_error(&err);
+                return;
+            }
+        };
+
+        let id = conflict.id();
+
+        let nickname = self
+            .series
+            .iter()
+            .find(|series| series.config().id == id)
+            .map_or("series", LoadedSeries::nickname);
+
+        self.log.push(
+            LogKind::Info,
+            format!("{} has conflicting changes on the remote service", nickname),
+        );
+
+        self.conflicts.retain(|queued| queued.id() != id);
+        self.conflicts.push(conflict);
+    }
+
+    /// Mark the access token of `user` as rejected so they can be prompted to login again.
+    fn token_rejected(&mut self, user: &UserInfo) {
+        if !self.users.set_token_rejected(user, true) {
+            return;
+        }
+
+        self.save_users();
+        self.warn_about_token(user);
+    }
+
+    /// Clear any previous rejection of the access token of `user`, and warn if it's about to expire.
+    fn token_accepted(&mut self, user: &UserInfo) {
+        if self.users.set_token_rejected(user, false) {
+            self.save_users();
+        }
+
+        self.warn_about_token(user);
+    }
+
+    fn warn_about_token(&mut self, user: &UserInfo) {
+        let warning = try_opt_ret!(self.users.token_status(user).warning(user));
+
+        self.log.push(
+            LogKind::Warning,
+            format!("{}, add the user again from the user panel", warning),
+        );
+    }
+
+    fn save_users(&mut self) {
+        if let Err(err) = self.users.save() {
+            self.log.push_error(&err.context("failed to save users"));
+        }
+    }
+
     pub fn init_selected_series(&mut self) {
         let selected = try_opt_ret!(self.series.selected_mut());
         selected.try_load(&self.config, &self.db)
@@ -228,7 +299,7 @@ impl UIState {
             let state = state.get_mut();
 
             if let Err(err) = result {
-                state.log.push_error(&err);
+                state.report_error(err);
             }
 
             state.input_state.reset();
@@ -252,33 +323,485 @@ impl SharedState {
     pub fn login_to_remote_async(&self, login: RemoteLogin) {
         let shared_state = self.clone();
 
-        task::spawn_blocking(move || match login {
-            RemoteLogin::AniList(username, token) => {
-                {
-                    let mut state = shared_state.lock();
-                    state.get_mut().remote = RemoteStatus::LoggingIn(username);
-                }
+        task::spawn(async move {
+            let config = {
+                let mut state = shared_state.lock();
+                let state = state.get_mut();
+
+                state.remote = RemoteStatus::LoggingIn(login.username().clone());
+                state.config.clone()
+            };
 
-                let auth = Auth::retrieve(token);
+            let user = login.user();
+            let login = shared_state.refresh_login_token(login, &config).await;
+            let result = login.login_async(config).await;
+
+            {
                 let mut state = shared_state.lock();
                 let state = state.get_mut();
 
-                let remote = match auth {
-                    Ok(auth) => {
-                        let anilist = AniList::Authenticated(auth);
-                        RemoteStatus::LoggedIn(anilist.into())
+                let remote = match result {
+                    Ok(remote) => {
+                        state.token_accepted(&user);
+                        RemoteStatus::LoggedIn(remote)
                     }
                     Err(err) => {
-                        state.log.push_error(&err.into());
-                        RemoteStatus::LoggedIn(Remote::offline())
+                        if err::is_invalid_token(&err) {
+                            state.token_rejected(&user);
+                        }
+
+                        state.log.push_error(&err);
+                        RemoteStatus::logged_in(remote::offline(Some(user.service)))
                     }
                 };
 
                 state.remote = remote;
             }
+
+            shared_state.sync_pending_deletions_async();
         });
     }
 
+    /// Refresh the access token used by `login` if it's about to expire, and return the login to use instead.
+    ///
+    /// The current token may still be valid, so a failed refresh is only logged.
+    async fn refresh_login_token(&self, login: RemoteLogin, config: &Config) -> RemoteLogin {
+        let user = login.user();
+
+        let refresh_token = match self.lock().get().users.due_refresh_token(&user) {
+            Some(refresh_token) => refresh_token.clone(),
+            None => return login,
+        };
+
+        let result = {
+            let user = user.clone();
+            let config = config.clone();
+
+            task::spawn_blocking(move || remote::refresh_token(&user, &refresh_token, &config))
+                .await
+                .map_err(Error::from)
+                .and_then(|result| result)
+        };
+
+        let mut state = self.lock();
+        let state = state.get_mut();
+
+        match result {
+            Ok(grant) => {
+                let token = grant.token.clone();
+
+                state.users.set_grant(user.clone(), grant);
+                state.save_users();
+
+                RemoteLogin::from_user(user, token)
+            }
+            Err(err) => {
+                state.log.push_error(&err);
+                login
+            }
+        }
+    }
+
+    /// Perform a queued list entry `deletion` with the remote service in the background.
+    ///
+    /// The deletion will stay queued until the next sync if the remote service is offline or the request fails.
+    pub fn delete_remote_entry_async(&self, deletion: PendingDeletion) {
+        let shared_state = self.clone();
+
+        task::spawn(async move {
+            let result = shared_state.delete_remote_entry(&deletion).await;
+
+            if let Err(err) = result {
+                shared_state.lock().get_mut().log.push_error(&err);
+            }
+        });
+    }
+
+    async fn delete_remote_entry(&self, deletion: &PendingDeletion) -> Result<()> {
+        let remote = match &self.lock().remote {
+            RemoteStatus::LoggedIn(remote) if deletion.belongs_to(remote.get()) => remote.clone(),
+            // The deletion will be performed once we're logged in to the user it was made for
+            RemoteStatus::LoggedIn(_) | RemoteStatus::LoggingIn(_) => return Ok(()),
+        };
+
+        remote
+            .delete_list_entry(deletion.id() as SeriesID)
+            .await
+            .context("deleting list entry from remote")?;
+
+        deletion.remove(&self.lock().db)?;
+        Ok(())
+    }
+
+    /// Perform every queued list entry deletion with the remote service in the background.
+    pub fn sync_pending_deletions_async(&self) {
+        let shared_state = self.clone();
+
+        task::spawn(async move {
+            let result = shared_state.sync_pending_deletions().await;
+            let mut state = shared_state.lock();
+            let state = state.get_mut();
+
+            let (deleted, errors) = match result {
+                Ok(result) => result,
+                Err(err) => {
+                    state.log.push_error(&err);
+                    return;
+                }
+            };
+
+            if deleted > 0 {
+                state.log.push(
+                    LogKind::Info,
+                    format!("deleted {} series from remote", deleted),
+                );
+            }
+
+            for err in &errors {
+                state.log.push_error(err);
+            }
+        });
+    }
+
+    /// Perform every queued list entry deletion that belongs to the current user.
+    ///
+    /// A failed deletion doesn't stop the rest from being performed. Returns the number of list entries that were
+    /// deleted, along with the error of each deletion that failed.
+    async fn sync_pending_deletions(&self) -> Result<(usize, Vec<Error>)> {
+        let deletions = PendingDeletion::load_all(&self.lock().db)?;
+        let mut deleted = 0;
+        let mut errors = Vec::new();
+
+        for deletion in &deletions {
+            let remote = match &self.lock().remote {
+                RemoteStatus::LoggedIn(remote) if deletion.belongs_to(remote.get()) => {
+                    remote.clone()
+                }
+                RemoteStatus::LoggedIn(_) => continue,
+                RemoteStatus::LoggingIn(_) => break,
+            };
+
+            let result = remote
+                .delete_list_entry(deletion.id() as SeriesID)
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to delete anime with ID {} from remote",
+                        deletion.id()
+                    )
+                })
+                .and_then(|_| deletion.remove(&self.lock().db).map_err(Into::into));
+
+            match result {
+                Ok(_) => deleted += 1,
+                Err(err) => errors.push(err),
+            }
+        }
+
+        Ok((deleted, errors))
+    }
+
+    /// Retrieve the list entry for a new series from the remote service and add it in the background.
+    pub fn add_series_async<E>(&self, config: SeriesConfig, info: SeriesInfo, episodes: E)
+    where
+        E: Into<Option<SortedEpisodes>>,
+    {
+        let shared_state = self.clone();
+        let episodes = episodes.into();
+
+        task::spawn(async move {
+            let result = shared_state.add_series(config, info, episodes).await;
+
+            if let Err(err) = result {
+                shared_state.lock().get_mut().log.push_error(&err);
+            }
+        });
+    }
+
+    async fn add_series(
+        &self,
+        config: SeriesConfig,
+        info: SeriesInfo,
+        episodes: Option<SortedEpisodes>,
+    ) -> Result<()> {
+        let remote = self.lock().remote.get_logged_in_async()?.clone();
+        let data = SeriesData::from_remote(config, info, &remote).await?;
+
+        self.lock().get_mut().add_series(data, episodes)
+    }
+
+    /// Add the series with the specified `id` to the user's remote list as planned to watch in the background.
+    ///
+    /// Series that are already on the list are left as they are. Once the series is known to be on the list,
+    /// `on_listed` is called with the current state.
+    pub fn plan_to_watch_async<F>(
+        &self,
+        state: &UIState,
+        id: SeriesID,
+        title: String,
+        on_listed: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&UIState) + Send + 'static,
+    {
+        let remote = state.remote.get_logged_in_async()?.clone();
+        let shared_state = self.clone();
+
+        task::spawn(async move {
+            let result = Self::plan_to_watch(&remote, id).await;
+            let mut state = shared_state.lock();
+
+            if result.is_ok() {
+                on_listed(state.get());
+            }
+
+            match result {
+                Ok(true) => state.get_mut().log.push(
+                    LogKind::Info,
+                    format!("added {} to your plan to watch list", title),
+                ),
+                Ok(false) => state
+                    .get_mut()
+                    .log
+                    .push(LogKind::Info, format!("{} is already on your list", title)),
+                Err(err) => state.get_mut().report_error(err.into()),
+            }
+
+            state.mark_dirty();
+        });
+
+        Ok(())
+    }
+
+    /// Returns false if the series was already on the list.
+    async fn plan_to_watch(remote: &AsyncRemote, id: SeriesID) -> anime::Result<bool> {
+        // The list may have changed since the series was retrieved, and an existing entry shouldn't be overwritten
+        if remote.get_list_entry(id).await?.is_some() {
+            return Ok(false);
+        }
+
+        let entry = RemoteEntry {
+            status: Status::PlanToWatch,
+            ..RemoteEntry::new(id)
+        };
+
+        remote.update_list_entry(entry).await?;
+        Ok(true)
+    }
+
+    /// Sync the list entry of the series with the specified `id` with the remote service in the background.
+    pub fn sync_series_async(&self, id: i32, direction: SyncDirection) {
+        let shared_state = self.clone();
+
+        task::spawn(async move {
+            let result = shared_state.sync_series(id, direction).await;
+
+            if let Err(err) = result {
+                shared_state.lock().get_mut().report_error(err);
+            }
+        });
+    }
+
+    async fn sync_series(&self, id: i32, direction: SyncDirection) -> Result<()> {
+        let (remote, mut entry) = {
+            let state = self.lock();
+            let remote = state.remote.get_logged_in_async()?.clone();
+
+            let entry = state
+                .series
+                .iter()
+                .find_map(|series| match series {
+                    LoadedSeries::Complete(series) if series.data.info.id == id => {
+                        Some(series.data.entry.clone())
+                    }
+                    _ => None,
+                })
+                .ok_or_else(|| anyhow!("series no longer exists"))?;
+
+            (remote, entry)
+        };
+
+        // The entry can be changed while the sync is in progress, so only the result of the sync is applied to it
+        let original = entry.clone();
+
+        let info = match direction {
+            SyncDirection::ToRemote => {
+                entry.sync_to_remote_async(&remote).await?;
+                None
+            }
+            SyncDirection::ForceToRemote => {
+                entry.force_sync_to_remote_async(&remote).await?;
+                None
+            }
+            SyncDirection::FromRemote => {
+                // We don't want to set the new info now in case the entry sync fails
+                let info = remote.search_info_by_id(id as SeriesID).await?;
+                entry.force_sync_from_remote_async(&remote).await?;
+                Some(info)
+            }
+        };
+
+        let mut state = self.lock();
+        let state = state.get_mut();
+
+        let series = state
+            .series
+            .items_mut()
+            .iter_mut()
+            .filter_map(LoadedSeries::complete_mut)
+            .find(|series| series.data.info.id == id);
+
+        let series = match series {
+            Some(series) => series,
+            None => return Ok(()),
+        };
+
+        if let Some(info) = info {
+            series.data.info = info.into();
+        }
+
+        series.data.entry.apply_sync(&original, entry);
+        series.save(&state.db)?;
+
+        Ok(())
+    }
+
+    /// Mark or unmark the series with the specified `id` as one of the user's favourites in the background.
+    pub fn set_favourite_async(&self, id: i32, favourite: bool) {
+        let shared_state = self.clone();
+
+        task::spawn(async move {
+            let result = shared_state.set_favourite(id, favourite).await;
+            let mut state = shared_state.lock();
+            let state = state.get_mut();
+
+            match result {
+                Ok(nickname) if favourite => state
+                    .log
+                    .push(LogKind::Info, format!("added {} to favourites", nickname)),
+                Ok(nickname) => state.log.push(
+                    LogKind::Info,
+                    format!("removed {} from favourites", nickname),
+                ),
+                Err(err) => state.report_error(err),
+            }
+        });
+    }
+
+    async fn set_favourite(&self, id: i32, favourite: bool) -> Result<String> {
+        let remote = self.lock().remote.get_logged_in_async()?.clone();
+
+        if remote.get().is_offline() {
+            return Err(anyhow!("favourites can't be changed while offline"));
+        }
+
+        remote.set_favourite(id as SeriesID, favourite).await?;
+
+        let mut state = self.lock();
+        let state = state.get_mut();
+
+        let series = state
+            .series
+            .items_mut()
+            .iter_mut()
+            .filter_map(LoadedSeries::complete_mut)
+            .find(|series| series.data.info.id == id)
+            .ok_or_else(|| anyhow!("series no longer exists"))?;
+
+        series.data.entry.set_favourite(favourite);
+        series.save(&state.db)?;
+
+        Ok(series.data.config.nickname.clone())
+    }
+
+    /// Sync the info and list entry of every series without unsynced changes from the remote service in the background.
+    ///
+    /// This uses as few requests as the remote service allows, rather than one for each series.
+    pub fn sync_all_from_remote_async(&self) {
+        let shared_state = self.clone();
+
+        task::spawn(async move {
+            let result = shared_state.sync_all_from_remote().await;
+            let mut state = shared_state.lock();
+            let state = state.get_mut();
+
+            match result {
+                Ok(synced) => state.log.push(
+                    LogKind::Info,
+                    format!("synced {} series from remote", synced),
+                ),
+                Err(err) => state.log.push_error(&err),
+            }
+        });
+    }
+
+    async fn sync_all_from_remote(&self) -> Result<usize> {
+        let (remote, ids) = {
+            let state = self.lock();
+            let remote = state.remote.get_logged_in_async()?.clone();
+
+            let ids = state
+                .series
+                .iter()
+                .filter_map(|series| match series {
+                    LoadedSeries::Complete(series) if !series.data.entry.needs_sync() => {
+                        Some(series.data.info.id as SeriesID)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            (remote, ids)
+        };
+
+        if ids.is_empty() || remote.get().is_offline() {
+            return Ok(0);
+        }
+
+        let infos = remote.search_info_by_ids(ids.clone()).await?;
+        let entries = remote.get_list_entries(ids).await?;
+
+        let mut infos = infos
+            .into_iter()
+            .map(|info| (info.id, info))
+            .collect::<HashMap<_, _>>();
+
+        let mut entries = entries
+            .into_iter()
+            .map(|entry| (entry.id, entry))
+            .collect::<HashMap<_, _>>();
+
+        let mut state = self.lock();
+        let state = state.get_mut();
+        let mut synced = 0;
+
+        for series in state.series.items_mut() {
+            let series = match series {
+                LoadedSeries::Complete(series) if !series.data.entry.needs_sync() => series,
+                _ => continue,
+            };
+
+            let id = series.data.info.id as SeriesID;
+
+            // Series that weren't requested won't have any info, so they should be left alone
+            let info = match infos.remove(&id) {
+                Some(info) => info,
+                None => continue,
+            };
+
+            series.data.info = info.into();
+            series.data.entry = match entries.remove(&id) {
+                Some(entry) => entry.into(),
+                None => series.data.info.id.into(),
+            };
+
+            series.save(&state.db)?;
+            synced += 1;
+        }
+
+        Ok(synced)
+    }
+
     async fn track_episode_finish(
         &self,
         mut ep_process: Child,
@@ -317,6 +840,15 @@ impl SharedState {
     }
 }
 
+#[derive(Clone, Copy)]
+pub enum SyncDirection {
+    /// Upload unsynced changes, merging in any changes made on the remote service first.
+    ToRemote,
+    /// Upload the entry as-is, overwriting any changes made on the remote service.
+    ForceToRemote,
+    FromRemote,
+}
+
 #[derive(Clone, Copy)]
 pub enum InputState {
     Idle,
@@ -1,9 +1,20 @@
+use crate::err;
 use crate::file::{FileFormat, SaveDir, SerializedFile};
-use anime::remote::{AccessToken, Remote};
+use anime::remote::anilist::TokenLifetime;
+use anime::remote::{AccessToken, Remote, TokenGrant};
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, Utc};
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 use tui_utils::list::EnumListItems;
 
+/// The number of days before a user's access token expires to start warning about it.
+const TOKEN_EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// The number of days before a user's access token expires to refresh it, if their service allows it.
+const TOKEN_REFRESH_DAYS: i64 = 7;
+
 /// Represents all (non-offline) remote types from the anime library.
 ///
 /// When dealing with users, this type should be used instead of the
@@ -13,6 +24,8 @@ use tui_utils::list::EnumListItems;
 #[serde(rename_all = "lowercase")]
 pub enum RemoteType {
     AniList,
+    Kitsu,
+    MyAnimeList,
 }
 
 impl RemoteType {
@@ -20,13 +33,45 @@ impl RemoteType {
     pub fn as_str(self) -> &'static str {
         match self {
             Self::AniList => "AniList",
+            Self::Kitsu => "Kitsu",
+            Self::MyAnimeList => "MyAnimeList",
+        }
+    }
+
+    /// Returns true if the service requires a password to add a new user.
+    pub fn needs_password(self) -> bool {
+        match self {
+            Self::Kitsu => true,
+            Self::AniList | Self::MyAnimeList => false,
+        }
+    }
+
+    /// Returns the label to use for the input that takes a new user's credentials.
+    pub fn auth_input_label(self) -> &'static str {
+        match self {
+            Self::AniList => "Paste Token",
+            Self::Kitsu => "Username / Email",
+            Self::MyAnimeList => "Paste Code / URL",
+        }
+    }
+}
+
+impl FromStr for RemoteType {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_ref() {
+            "anilist" => Ok(Self::AniList),
+            "kitsu" => Ok(Self::Kitsu),
+            "myanimelist" | "mal" => Ok(Self::MyAnimeList),
+            _ => Err(anyhow!("unknown service: {}", value)),
         }
     }
 }
 
 impl EnumListItems for RemoteType {
     fn items<'a>() -> &'a [Self] {
-        &[Self::AniList]
+        &[Self::AniList, Self::Kitsu, Self::MyAnimeList]
     }
 }
 
@@ -51,25 +96,120 @@ impl UserInfo {
     }
 
     pub fn is_logged_in(&self, remote: &Remote) -> bool {
-        use anime::remote::anilist::AniList;
-
-        match (self.service, remote) {
-            (RemoteType::AniList, Remote::AniList(anilist)) => match anilist {
-                AniList::Authenticated(auth) => auth.user.name == self.username,
-                AniList::Unauthenticated => false,
-            },
-            (RemoteType::AniList, Remote::Offline(_)) => false,
+        match (self.service, remote.uncached()) {
+            (RemoteType::AniList, Remote::AniList(anilist)) => {
+                matches!(anilist.auth(), Some(auth) if auth.user.name == self.username)
+            }
+            (RemoteType::Kitsu, Remote::Kitsu(kitsu)) => {
+                matches!(kitsu.auth(), Some(auth) if auth.user.name == self.username)
+            }
+            (RemoteType::MyAnimeList, Remote::MyAnimeList(mal)) => {
+                matches!(mal.auth(), Some(auth) if auth.user.name == self.username)
+            }
+            _ => false,
         }
     }
 }
 
 pub type UserMap = HashMap<UserInfo, AccessToken>;
 
+/// What is known about the access token of a user.
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+pub struct TokenStatus {
+    /// When the token was issued and when it expires, if the remote service provides that.
+    pub lifetime: Option<TokenLifetime>,
+    /// Whether the remote service rejected the token, meaning the user needs to be added again.
+    pub rejected: bool,
+    /// Whether the remote service issued a refresh token, so a new token can be retrieved before this one expires.
+    pub refreshable: bool,
+}
+
+impl TokenStatus {
+    fn new(service: RemoteType, grant: &TokenGrant) -> Self {
+        let lifetime = match (service, grant.expires_in) {
+            (RemoteType::AniList, _) => TokenLifetime::from_token(&grant.token),
+            (RemoteType::Kitsu, Some(expires_in)) | (RemoteType::MyAnimeList, Some(expires_in)) => {
+                let now = Utc::now().timestamp();
+
+                Some(TokenLifetime {
+                    issued_at: now,
+                    expires_at: now + expires_in,
+                })
+            }
+            (RemoteType::Kitsu, None) | (RemoteType::MyAnimeList, None) => None,
+        };
+
+        Self {
+            lifetime,
+            rejected: false,
+            refreshable: grant.refresh_token.is_some(),
+        }
+    }
+
+    /// Returns true if the user needs to be added again, either because the token was rejected or has expired
+    /// without a way to refresh it.
+    pub fn needs_login(self) -> bool {
+        let now = Utc::now().timestamp();
+
+        self.rejected
+            || (!self.refreshable
+                && matches!(self.lifetime, Some(lifetime) if lifetime.is_expired(now)))
+    }
+
+    /// Returns true if the token can be refreshed and is close enough to expiring that it should be.
+    pub fn needs_refresh(self) -> bool {
+        self.refreshable
+            && self
+                .remaining_days()
+                .map_or(false, |days| days <= TOKEN_REFRESH_DAYS)
+    }
+
+    /// Returns the number of days left until the token expires, if it's going to expire soon.
+    ///
+    /// Tokens that can be refreshed are never considered to expire soon, since they're refreshed automatically.
+    pub fn expires_soon(self) -> Option<i64> {
+        if self.refreshable {
+            return None;
+        }
+
+        self.remaining_days()
+            .filter(|&days| days <= TOKEN_EXPIRY_WARNING_DAYS)
+    }
+
+    fn remaining_days(self) -> Option<i64> {
+        let lifetime = self.lifetime?;
+        let remaining = Duration::seconds(lifetime.expires_at - Utc::now().timestamp());
+
+        Some(remaining.num_days().max(0))
+    }
+
+    /// Returns a warning describing why `user` needs to be added again, or when their token is about to expire.
+    pub fn warning(self, user: &UserInfo) -> Option<String> {
+        let service = user.service.as_str();
+
+        if self.needs_login() {
+            return Some(format!(
+                "the {} login for {} has expired or was revoked",
+                service, user.username
+            ));
+        }
+
+        let days = self.expires_soon()?;
+
+        Some(format!(
+            "the {} login for {} expires in {} days",
+            service, user.username, days
+        ))
+    }
+}
+
 /// A map containing all users along with the last used one.
 #[derive(Default, Deserialize, Serialize)]
 pub struct Users {
     users: UserMap,
     pub last_used: Option<UserInfo>,
+    tokens: HashMap<UserInfo, TokenStatus>,
+    refresh_tokens: HashMap<UserInfo, AccessToken>,
 }
 
 impl Users {
@@ -78,13 +218,33 @@ impl Users {
         Self {
             users: UserMap::new(),
             last_used: None,
+            tokens: HashMap::new(),
+            refresh_tokens: HashMap::new(),
         }
     }
 
     /// Adds a new (unique) `user` to the user map and sets the last used user to `user`.
-    pub fn add_and_set_last(&mut self, user: UserInfo, token: AccessToken) {
+    pub fn add_and_set_last(&mut self, user: UserInfo, grant: TokenGrant) {
         self.last_used = Some(user.clone());
-        self.users.insert(user, token);
+        self.set_grant(user, grant);
+    }
+
+    /// Replace the access token of `user` with a newly granted one.
+    ///
+    /// The current refresh token is kept if the new grant doesn't come with one.
+    pub fn set_grant(&mut self, user: UserInfo, grant: TokenGrant) {
+        let mut status = TokenStatus::new(user.service, &grant);
+
+        match grant.refresh_token {
+            Some(refresh_token) => {
+                self.refresh_tokens.insert(user.clone(), refresh_token);
+            }
+            None if self.refresh_tokens.contains_key(&user) => status.refreshable = true,
+            None => (),
+        }
+
+        self.tokens.insert(user.clone(), status);
+        self.users.insert(user, grant.token);
     }
 
     /// Removes the specified `user` from the user map.
@@ -92,6 +252,8 @@ impl Users {
     /// This also unsets the last used user if it was set to `user`.
     pub fn remove(&mut self, user: &UserInfo) {
         self.users.remove(user);
+        self.tokens.remove(user);
+        self.refresh_tokens.remove(user);
 
         if let Some(last) = &self.last_used {
             if user == last {
@@ -100,10 +262,40 @@ impl Users {
         }
     }
 
-    /// Returns the last used user's access token if it was set.
-    pub fn take_last_used_token(mut self) -> Option<AccessToken> {
-        let last = self.last_used?;
-        self.users.remove(&last)
+    /// Returns what is known about the access token of `user`.
+    pub fn token_status(&self, user: &UserInfo) -> TokenStatus {
+        self.tokens.get(user).copied().unwrap_or_default()
+    }
+
+    /// Returns the refresh token of `user` if their access token should be refreshed before logging in with it.
+    pub fn due_refresh_token(&self, user: &UserInfo) -> Option<&AccessToken> {
+        if !self.token_status(user).needs_refresh() {
+            return None;
+        }
+
+        self.refresh_tokens.get(user)
+    }
+
+    /// Set whether the access token of `user` was rejected by its remote service.
+    ///
+    /// Returns true if this changed the token's status.
+    pub fn set_token_rejected(&mut self, user: &UserInfo, rejected: bool) -> bool {
+        let status = self.tokens.entry(user.clone()).or_default();
+        let changed = status.rejected != rejected;
+
+        status.rejected = rejected;
+        changed
+    }
+
+    /// Returns the last used user along with their access token.
+    pub fn last_used_user(&self) -> Option<(&UserInfo, &AccessToken)> {
+        let last = self.last_used.as_ref()?;
+        self.users.get_key_value(last)
+    }
+
+    /// Returns the service the last used user is on.
+    pub fn last_used_service(&self) -> Option<RemoteType> {
+        self.last_used.as_ref().map(|user| user.service)
     }
 
     /// Consumes the `Users` struct and returns the last used user.
@@ -135,6 +327,48 @@ impl SerializedFile for Users {
     fn format() -> FileFormat {
         FileFormat::Binary
     }
+
+    fn load() -> Result<Self> {
+        let path = Self::validated_save_path().context("getting path")?;
+
+        match Self::format().deserialize(&path) {
+            Ok(users) => Ok(users),
+            Err(err) if err::is_file_nonexistant(&err) => Err(err),
+            // Users saved before token statuses were tracked have to be converted
+            Err(err) => Self::format()
+                .deserialize::<_, LegacyUsers>(&path)
+                .map(Self::from)
+                .map_err(|_| err)
+                .context("deserializing file"),
+        }
+    }
+}
+
+/// The format users were saved in before the status of their tokens was tracked.
+#[derive(Deserialize)]
+struct LegacyUsers {
+    users: UserMap,
+    last_used: Option<UserInfo>,
+}
+
+impl From<LegacyUsers> for Users {
+    fn from(legacy: LegacyUsers) -> Self {
+        let tokens = legacy
+            .users
+            .iter()
+            .map(|(user, token)| {
+                let grant = TokenGrant::from(token.clone());
+                (user.clone(), TokenStatus::new(user.service, &grant))
+            })
+            .collect();
+
+        Self {
+            users: legacy.users,
+            last_used: legacy.last_used,
+            tokens,
+            refresh_tokens: HashMap::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -148,14 +382,68 @@ mod tests {
         let user1 = UserInfo::new(RemoteType::AniList, "User 1");
         let user1_duplicate = user1.clone();
 
-        users.add_and_set_last(user1, AccessToken::encode("token1"));
-        users.add_and_set_last(user1_duplicate, AccessToken::encode("token2"));
+        users.add_and_set_last(user1, AccessToken::encode("token1").into());
+        users.add_and_set_last(user1_duplicate, AccessToken::encode("token2").into());
 
         assert_eq!(users.len(), 1);
 
         let user2 = UserInfo::new(RemoteType::AniList, "User 2");
-        users.add_and_set_last(user2, AccessToken::encode("token3"));
+        users.add_and_set_last(user2, AccessToken::encode("token3").into());
 
         assert_eq!(users.len(), 2);
     }
+
+    #[test]
+    fn legacy_users_are_converted() {
+        let user = UserInfo::new(RemoteType::AniList, "User 1");
+
+        let mut map = UserMap::new();
+        map.insert(user.clone(), AccessToken::encode("token1"));
+
+        let legacy = bincode::serialize(&(map, Some(user.clone()))).unwrap();
+        assert!(bincode::deserialize::<Users>(&legacy).is_err());
+
+        let legacy: LegacyUsers = bincode::deserialize(&legacy).unwrap();
+        let mut users = Users::from(legacy);
+
+        assert_eq!(users.len(), 1);
+        assert!(users.last_used_user().is_some());
+        assert!(!users.token_status(&user).needs_login());
+
+        assert!(users.set_token_rejected(&user, true));
+        assert!(users.token_status(&user).needs_login());
+
+        // Adding the user again should replace the rejected token
+        users.add_and_set_last(user.clone(), AccessToken::encode("token2").into());
+        assert!(!users.token_status(&user).needs_login());
+    }
+
+    #[test]
+    fn refreshable_tokens_are_refreshed_before_expiring() {
+        let mut users = Users::new();
+        let user = UserInfo::new(RemoteType::MyAnimeList, "User 1");
+
+        let grant = |expires_in| TokenGrant {
+            token: AccessToken::encode("token"),
+            refresh_token: Some(AccessToken::encode("refresh")),
+            expires_in: Some(expires_in),
+        };
+
+        users.add_and_set_last(user.clone(), grant(Duration::days(31).num_seconds()));
+        assert!(users.due_refresh_token(&user).is_none());
+        assert!(users.token_status(&user).expires_soon().is_none());
+
+        users.set_grant(user.clone(), grant(Duration::days(2).num_seconds()));
+        assert!(users.due_refresh_token(&user).is_some());
+
+        // An expired token can still be refreshed, so the user doesn't have to be added again
+        users.set_grant(user.clone(), grant(-1));
+        assert!(!users.token_status(&user).needs_login());
+
+        // Grants without a refresh token keep the previous one
+        let mut refreshed = grant(-1);
+        refreshed.refresh_token = None;
+        users.set_grant(user.clone(), refreshed);
+        assert!(users.due_refresh_token(&user).is_some());
+    }
 }
@@ -37,6 +37,18 @@ where
     format!("{:02}:{:02}H", hours, minutes)
 }
 
+/// Format `total_secs` as days and hours, or hours and minutes when it's less than a day.
+pub fn dh_from_secs(total_secs: i64) -> String {
+    let total_mins = total_secs / 60;
+    let total_hours = total_mins / 60;
+
+    if total_hours >= 24 {
+        format!("{}d {}h", total_hours / 24, total_hours % 24)
+    } else {
+        format!("{}h {}m", total_hours, total_mins % 60)
+    }
+}
+
 pub type ArcMutex<T> = Arc<Mutex<T>>;
 
 pub fn arc_mutex<T>(value: T) -> ArcMutex<T> {